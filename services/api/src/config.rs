@@ -40,6 +40,38 @@ fn def_aws_uploads_bucket_name() -> String {
     String::from("rastercar-uploads")
 }
 
+fn def_position_batch_size() -> usize {
+    500
+}
+
+fn def_position_batch_interval_ms() -> u64 {
+    250
+}
+
+fn def_position_filter_max_speed() -> f64 {
+    300.0
+}
+
+fn def_position_filter_min_parked_distance() -> f64 {
+    15.0
+}
+
+fn def_position_filter_max_clock_skew_secs() -> i64 {
+    120
+}
+
+fn def_position_filter_flag_only() -> bool {
+    false
+}
+
+fn def_trip_min_moving_speed() -> f64 {
+    5.0
+}
+
+fn def_trip_min_stop_duration_secs() -> i64 {
+    3 * 60
+}
+
 #[derive(Deserialize, Debug)]
 pub struct AppConfig {
     /// if the application is running in `development` mode
@@ -77,6 +109,44 @@ pub struct AppConfig {
     /// AWS S3 bucket used for all uploads by the API
     #[serde(default = "def_aws_uploads_bucket_name")]
     pub aws_uploads_bucket_name: String,
+
+    /// maximum amount of tracker positions stored with a single insert
+    #[serde(default = "def_position_batch_size")]
+    pub position_batch_size: usize,
+
+    /// maximum time in milliseconds a tracker position waits for its batch to be stored
+    #[serde(default = "def_position_batch_interval_ms")]
+    pub position_batch_interval_ms: u64,
+
+    /// maximum speed in km/h between consecutive tracker positions, positions that
+    /// would need a higher speed to be reached are rejected, 0 disables the check
+    #[serde(default = "def_position_filter_max_speed")]
+    pub position_filter_max_speed: f64,
+
+    /// minimum distance in meters from the previous tracker position for a position to not be
+    /// rejected as GPS drift while the vehicle ignition is off, 0 disables the check
+    #[serde(default = "def_position_filter_min_parked_distance")]
+    pub position_filter_min_parked_distance: f64,
+
+    /// maximum amount of seconds tracker position timestamps can be in the
+    /// future before the position is rejected, 0 disables the check
+    #[serde(default = "def_position_filter_max_clock_skew_secs")]
+    pub position_filter_max_clock_skew_secs: i64,
+
+    /// if positions rejected by the position filter should only be flagged, recording
+    /// the rejection but handling them as usual, instead of dropping them
+    #[serde(default = "def_position_filter_flag_only")]
+    pub position_filter_flag_only: bool,
+
+    /// minimum speed in km/h for a vehicle to be considered moving when segmenting its
+    /// positions into trips, so the GPS drift of a parked vehicle is not a trip
+    #[serde(default = "def_trip_min_moving_speed")]
+    pub trip_min_moving_speed: f64,
+
+    /// minimum amount of seconds a vehicle must stay still for it to be a stop
+    /// ending its trip, so trips are not split on traffic lights and congestions
+    #[serde(default = "def_trip_min_stop_duration_secs")]
+    pub trip_min_stop_duration_secs: i64,
 }
 
 impl AppConfig {
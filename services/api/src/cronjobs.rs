@@ -1,6 +1,11 @@
+use crate::modules::tracking::decoder::status;
 use chrono::Utc;
-use sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
-use shared::entity::session;
+use sea_orm::{ActiveValue::Set, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use shared::{
+    constants::{TrackerCommandStatus, TrackerConnectivity, TRACKER_OFFLINE_AFTER_SECS},
+    entity::{session, tracker_command, vehicle_tracker},
+};
+use socketioxide::SocketIo;
 use std::time::Duration;
 
 /// starts a tokio task that deletes all the expired user sessions every inteval
@@ -20,3 +25,89 @@ pub fn start_clear_sessions_cronjob(db: DatabaseConnection, interval: Duration)
         }
     });
 }
+
+/// starts a tokio task that expires the tracker commands that were not
+/// acknowledged by the tracker within the `ttl` every interval
+pub fn start_expire_tracker_commands_cronjob(
+    db: DatabaseConnection,
+    interval: Duration,
+    ttl: Duration,
+) {
+    println!("[CRON] expiring unacknowledged tracker commands every {interval:?}");
+
+    let ttl = chrono::Duration::from_std(ttl).expect("invalid tracker command ttl");
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+
+        loop {
+            interval.tick().await;
+
+            let _ = tracker_command::Entity::update_many()
+                .set(tracker_command::ActiveModel {
+                    status: Set(TrackerCommandStatus::Expired),
+                    failure_reason: Set(Some(String::from(
+                        "not acknowledged by the tracker in time",
+                    ))),
+                    ..Default::default()
+                })
+                .filter(
+                    tracker_command::Column::Status
+                        .is_in([TrackerCommandStatus::Pending, TrackerCommandStatus::Sent]),
+                )
+                .filter(tracker_command::Column::CreatedAt.lt(Utc::now() - ttl))
+                .exec(&db)
+                .await;
+        }
+    });
+}
+
+/// starts a tokio task that every interval emits the status of the trackers that became
+/// offline since the last interval for not being seen in a while, as no event is
+/// received when that happens
+pub fn start_notify_offline_trackers_cronjob(
+    db: DatabaseConnection,
+    socket: SocketIo,
+    interval: Duration,
+) {
+    println!("[CRON] notifying offline trackers every {interval:?}");
+
+    let offline_after = chrono::Duration::seconds(TRACKER_OFFLINE_AFTER_SECS);
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        let mut last_run = Utc::now();
+
+        loop {
+            interval.tick().await;
+
+            let now = Utc::now();
+
+            let trackers = vehicle_tracker::Entity::find()
+                .filter(vehicle_tracker::Column::LastSeenAt.gte(last_run - offline_after))
+                .filter(vehicle_tracker::Column::LastSeenAt.lt(now - offline_after))
+                .all(&db)
+                .await;
+
+            match trackers {
+                Ok(trackers) => {
+                    last_run = now;
+
+                    // trackers whose connection was closed were not online when last
+                    // seen, so they were already notified as offline when disconnected
+                    let was_online = |tracker: &&vehicle_tracker::Model| {
+                        tracker.last_seen_at.is_some_and(|last_seen_at| {
+                            tracker.connectivity(last_seen_at) == TrackerConnectivity::Online
+                        })
+                    };
+
+                    trackers
+                        .iter()
+                        .filter(was_online)
+                        .for_each(|tracker| status::emit_status(&socket, tracker, now));
+                }
+                Err(e) => println!("[CRON] failed to get offline trackers: {e}"),
+            }
+        }
+    });
+}
@@ -1,7 +1,7 @@
 use crate::modules::common::responses::{internal_error_res, SimpleError};
 use convert_case::{Case, Casing};
 use http::StatusCode;
-use sea_orm::{DbErr, RuntimeErr, SqlxError};
+use sea_orm::{DbErr, RuntimeErr, SqlxError, TransactionError};
 
 /// Wrapper for seaorm errors.
 ///
@@ -16,6 +16,15 @@ impl From<DbErr> for DbError {
     }
 }
 
+impl From<TransactionError<DbErr>> for DbError {
+    fn from(err: TransactionError<DbErr>) -> Self {
+        match err {
+            TransactionError::Connection(e) => DbError(e),
+            TransactionError::Transaction(e) => DbError(e),
+        }
+    }
+}
+
 impl From<DbError> for (StatusCode, SimpleError) {
     fn from(err: DbError) -> Self {
         match err.0 {
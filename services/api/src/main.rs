@@ -37,6 +37,12 @@ pub async fn main() {
 
     cronjobs::start_clear_sessions_cronjob(db.clone(), Duration::from_secs(5 * 60));
 
+    cronjobs::start_expire_tracker_commands_cronjob(
+        db.clone(),
+        Duration::from_secs(60),
+        Duration::from_secs(10 * 60),
+    );
+
     let rmq = Arc::new(rabbitmq::Rmq::new(&cfg.rmq_uri).await);
     let rmq_reconnect_ref = rmq.clone();
     let rmq_shutdown_ref = rmq.clone();
@@ -51,7 +51,12 @@ pub struct Pagination {
     PaginatedVehicle = PaginationResult<entity::vehicle::Model>,
     PaginatedSimCard = PaginationResult<entity::sim_card::Model>,
     PaginatedAccessLevel = PaginationResult<access_level::dto::AccessLevelDto>,
-    PaginatedVehicleTracker = PaginationResult<entity::vehicle_tracker::Model>
+    PaginatedVehicleTracker = PaginationResult<entity::vehicle_tracker::Model>,
+    PaginatedTrackerCommand = PaginationResult<entity::tracker_command::Model>,
+    PaginatedTrackerAlarm = PaginationResult<entity::tracker_alarm::Model>,
+    PaginatedGeofence = PaginationResult<entity::geofence::Model>,
+    PaginatedGeofenceEvent = PaginationResult<entity::geofence_event::Model>,
+    PaginatedPositionFilterDecision = PaginationResult<entity::position_filter_decision::Model>
 )]
 pub struct PaginationResult<T: for<'_s> ToSchema<'_s>> {
     /// 1 Indexed Page number
@@ -81,7 +86,7 @@ pub struct SingleImageDto {
 }
 
 /// Simple enum to order a query by ascending or descending order
-#[derive(Debug, ToSchema)]
+#[derive(Debug, ToSchema, Clone, Copy, PartialEq)]
 pub enum AscOrDescOrder {
     Asc,
     Desc,
@@ -0,0 +1,234 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use shared::constants::GeofenceShape;
+use std::collections::HashSet;
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
+
+/// Maximum amount of points of a polygon geofence
+const MAX_POLYGON_POINTS: usize = 1000;
+
+/// Maximum radius in meters of a circle geofence
+const MAX_CIRCLE_RADIUS: f64 = 100_000.0;
+
+#[derive(Serialize, Deserialize, ToSchema, Clone, Copy, PartialEq)]
+pub struct CoordinateDto {
+    /// latitude (90 to -90) in decimal degrees
+    pub lat: f64,
+
+    /// longitude (180 to -180) in decimal degrees
+    pub lng: f64,
+}
+
+impl CoordinateDto {
+    fn is_valid(&self) -> bool {
+        (-90.0..=90.0).contains(&self.lat) && (-180.0..=180.0).contains(&self.lng)
+    }
+
+    /// GeoJSON position, longitude first
+    fn to_position(self) -> serde_json::Value {
+        json!([self.lng, self.lat])
+    }
+}
+
+/// The area of a geofence, one of:
+///
+/// - `{ "type": "polygon", "points": [{ "lat": -23.55, "lng": -46.63 }, ...] }`
+/// - `{ "type": "circle", "center": { "lat": -23.55, "lng": -46.63 }, "radius": 500 }`
+#[derive(Serialize, Deserialize, ToSchema, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum GeofenceShapeDto {
+    Polygon {
+        /// vertices of the polygon, at least 3
+        points: Vec<CoordinateDto>,
+    },
+    Circle {
+        center: CoordinateDto,
+
+        /// radius in meters
+        radius: f64,
+    },
+}
+
+impl GeofenceShapeDto {
+    pub fn shape(&self) -> GeofenceShape {
+        match self {
+            Self::Polygon { .. } => GeofenceShape::Polygon,
+            Self::Circle { .. } => GeofenceShape::Circle,
+        }
+    }
+
+    pub fn radius(&self) -> Option<f64> {
+        match self {
+            Self::Polygon { .. } => None,
+            Self::Circle { radius, .. } => Some(*radius),
+        }
+    }
+
+    /// GeoJSON geometry of the shape, circles being the point at their center
+    pub fn geojson(&self) -> serde_json::Value {
+        match self {
+            Self::Polygon { points } => {
+                let mut ring: Vec<serde_json::Value> =
+                    points.iter().map(|p| p.to_position()).collect();
+
+                // GeoJSON polygon rings must end on their first position
+                if points.first() != points.last() {
+                    ring.push(points[0].to_position());
+                }
+
+                json!({ "type": "Polygon", "coordinates": [ring] })
+            }
+            Self::Circle { center, .. } => {
+                json!({ "type": "Point", "coordinates": center.to_position() })
+            }
+        }
+    }
+}
+
+/// Twice the signed area of the polygon in squared degrees, by the shoelace formula,
+/// zero when every vertex is on the same line
+fn polygon_area(points: &[CoordinateDto]) -> f64 {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.lng * b.lat - b.lng * a.lat)
+        .sum()
+}
+
+fn is_valid_geofence_shape(shape: &GeofenceShapeDto) -> Result<(), ValidationError> {
+    match shape {
+        GeofenceShapeDto::Polygon { points } => {
+            if points.len() < 3 || points.len() > MAX_POLYGON_POINTS {
+                return Err(ValidationError::new("polygon must have 3 to 1000 points"));
+            }
+
+            if !points.iter().all(CoordinateDto::is_valid) {
+                return Err(ValidationError::new("invalid polygon point coordinates"));
+            }
+
+            let distinct_points: HashSet<(u64, u64)> = points
+                .iter()
+                .map(|p| (p.lat.to_bits(), p.lng.to_bits()))
+                .collect();
+
+            if distinct_points.len() < 3 {
+                return Err(ValidationError::new(
+                    "polygon must have at least 3 distinct points",
+                ));
+            }
+
+            if polygon_area(points) == 0.0 {
+                return Err(ValidationError::new("polygon points cannot be on a line"));
+            }
+
+            Ok(())
+        }
+        GeofenceShapeDto::Circle { center, radius } => {
+            if !(1.0..=MAX_CIRCLE_RADIUS).contains(radius) {
+                return Err(ValidationError::new(
+                    "circle radius must be from 1 to 100000 meters",
+                ));
+            }
+
+            if !center.is_valid() {
+                return Err(ValidationError::new("invalid circle center coordinates"));
+            }
+
+            Ok(())
+        }
+    }
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct ListGeofencesDto {
+    /// Search by name
+    pub name: Option<String>,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CreateGeofenceDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: String,
+
+    pub description: Option<String>,
+
+    #[validate(custom = "is_valid_geofence_shape")]
+    pub shape: GeofenceShapeDto,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateGeofenceDto {
+    #[validate(length(min = 1, max = 255))]
+    pub name: Option<String>,
+
+    #[serde(default, with = "::serde_with::rust::double_option")]
+    pub description: Option<Option<String>>,
+
+    #[validate(custom = "is_valid_geofence_shape")]
+    pub shape: Option<GeofenceShapeDto>,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SetGeofenceVehiclesDto {
+    /// ids of the vehicles to monitor on the geofence, replacing the current ones
+    #[validate(length(max = 1000))]
+    pub vehicle_ids: Vec<i32>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{is_valid_geofence_shape, CoordinateDto, GeofenceShapeDto};
+
+    fn polygon(points: &[(f64, f64)]) -> GeofenceShapeDto {
+        GeofenceShapeDto::Polygon {
+            points: points
+                .iter()
+                .map(|(lat, lng)| CoordinateDto {
+                    lat: *lat,
+                    lng: *lng,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn accepts_open_and_closed_polygons() {
+        let open = polygon(&[(-23.5, -46.6), (-23.5, -46.5), (-23.4, -46.5)]);
+        let closed = polygon(&[
+            (-23.5, -46.6),
+            (-23.5, -46.5),
+            (-23.4, -46.5),
+            (-23.5, -46.6),
+        ]);
+
+        assert!(is_valid_geofence_shape(&open).is_ok());
+        assert!(is_valid_geofence_shape(&closed).is_ok());
+    }
+
+    #[test]
+    fn rejects_polygons_without_3_distinct_points() {
+        let closed_line = polygon(&[(-23.5, -46.6), (-23.5, -46.5), (-23.5, -46.6)]);
+        let repeated = polygon(&[
+            (-23.5, -46.6),
+            (-23.5, -46.5),
+            (-23.5, -46.5),
+            (-23.5, -46.6),
+        ]);
+
+        assert!(is_valid_geofence_shape(&closed_line).is_err());
+        assert!(is_valid_geofence_shape(&repeated).is_err());
+    }
+
+    #[test]
+    fn rejects_polygons_on_a_line() {
+        let line = polygon(&[(-23.5, -46.6), (-23.5, -46.5), (-23.5, -46.4)]);
+
+        assert!(is_valid_geofence_shape(&line).is_err());
+    }
+}
@@ -0,0 +1,431 @@
+use super::dto::{CreateGeofenceDto, ListGeofencesDto, SetGeofenceVehiclesDto, UpdateGeofenceDto};
+use crate::{
+    database::{
+        error::DbError,
+        helpers::{paginated_query_to_pagination_result, set_if_some},
+    },
+    modules::{
+        auth::{self, middleware::AclLayer},
+        common::{
+            dto::{Pagination, PaginationResult},
+            extractors::{
+                DbConnection, OrgBoundEntityFromPathId, OrganizationId, ValidatedJson,
+                ValidatedQuery,
+            },
+            responses::SimpleError,
+        },
+    },
+    server::controller::AppState,
+};
+use axum::{
+    routing::{delete, get, post, put},
+    Json, Router,
+};
+use http::StatusCode;
+use migration::{extension::postgres::PgExpr, Expr, OnConflict};
+use sea_orm::{
+    ActiveModelTrait, ColumnTrait, DbErr, EntityTrait, ModelTrait, PaginatorTrait, QueryFilter,
+    QueryOrder, QueryTrait, Set, TransactionTrait,
+};
+use shared::constants::Permission;
+use shared::entity::{geofence, geofence_event, vehicle, vehicle_geofence};
+
+pub fn create_router(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/", get(list_geofences))
+        //
+        .route(
+            "/",
+            post(create_geofence).route_layer(AclLayer::single(Permission::CreateGeofence)),
+        )
+        //
+        .route("/:geofence_id", get(geofence_by_id))
+        //
+        .route(
+            "/:geofence_id",
+            put(update_geofence).route_layer(AclLayer::single(Permission::UpdateGeofence)),
+        )
+        //
+        .route(
+            "/:geofence_id",
+            delete(delete_geofence).route_layer(AclLayer::single(Permission::DeleteGeofence)),
+        )
+        //
+        .route("/:geofence_id/vehicles", get(list_geofence_vehicles))
+        //
+        .route(
+            "/:geofence_id/vehicles",
+            put(set_geofence_vehicles).route_layer(AclLayer::single(Permission::UpdateGeofence)),
+        )
+        //
+        .route("/:geofence_id/events", get(list_geofence_events))
+        //
+        .route_layer(axum::middleware::from_fn_with_state(
+            state,
+            auth::middleware::require_user,
+        ))
+}
+
+/// Lists the geofences that belong to the same org as the request user
+#[utoipa::path(
+    get,
+    tag = "geofence",
+    path = "/geofence",
+    security(("session_id" = [])),
+    params(
+        Pagination,
+        ListGeofencesDto
+    ),
+    responses(
+        (
+            status = OK,
+            description = "paginated list of geofences",
+            content_type = "application/json",
+            body = PaginatedGeofence,
+        ),
+    ),
+)]
+pub async fn list_geofences(
+    ValidatedQuery(pagination): ValidatedQuery<Pagination>,
+    ValidatedQuery(filter): ValidatedQuery<ListGeofencesDto>,
+    OrganizationId(org_id): OrganizationId,
+    DbConnection(db): DbConnection,
+) -> Result<Json<PaginationResult<geofence::Model>>, (StatusCode, SimpleError)> {
+    let db_query = geofence::Entity::find()
+        .filter(geofence::Column::OrganizationId.eq(org_id))
+        .apply_if(filter.name, |query, name| {
+            if !name.is_empty() {
+                let col = Expr::col((geofence::Entity, geofence::Column::Name));
+                query.filter(col.ilike(format!("%{}%", name)))
+            } else {
+                query
+            }
+        })
+        .order_by_asc(geofence::Column::Id)
+        .paginate(&db, pagination.page_size);
+
+    let result = paginated_query_to_pagination_result(db_query, pagination).await?;
+
+    Ok(Json(result))
+}
+
+/// Creates a new geofence
+///
+/// Required permissions: CREATE_GEOFENCE
+#[utoipa::path(
+    post,
+    tag = "geofence",
+    path = "/geofence",
+    security(("session_id" = [])),
+    request_body(content = CreateGeofenceDto, content_type = "application/json"),
+    responses(
+        (
+            status = OK,
+            description = "the created geofence",
+            content_type = "application/json",
+            body = entity::geofence::Model,
+        ),
+        (
+            status = BAD_REQUEST,
+            description = "invalid dto error message",
+            body = SimpleError,
+        ),
+    ),
+)]
+pub async fn create_geofence(
+    OrganizationId(org_id): OrganizationId,
+    DbConnection(db): DbConnection,
+    ValidatedJson(dto): ValidatedJson<CreateGeofenceDto>,
+) -> Result<Json<geofence::Model>, (StatusCode, SimpleError)> {
+    let created_geofence = geofence::ActiveModel {
+        name: Set(dto.name),
+        description: Set(dto.description),
+        shape: Set(dto.shape.shape()),
+        geojson: Set(dto.shape.geojson()),
+        radius: Set(dto.shape.radius()),
+        organization_id: Set(org_id),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .map_err(DbError::from)?;
+
+    Ok(Json(created_geofence))
+}
+
+/// Get a geofence by id
+#[utoipa::path(
+    get,
+    tag = "geofence",
+    path = "/geofence/{geofence_id}",
+    security(("session_id" = [])),
+    params(
+        ("geofence_id" = u128, Path, description = "id of the geofence to get"),
+    ),
+    responses(
+        (
+            status = OK,
+            content_type = "application/json",
+            body = entity::geofence::Model,
+        ),
+    ),
+)]
+pub async fn geofence_by_id(
+    OrgBoundEntityFromPathId(geofence): OrgBoundEntityFromPathId<geofence::Entity>,
+) -> Result<Json<geofence::Model>, (StatusCode, SimpleError)> {
+    Ok(Json(geofence))
+}
+
+/// Update a geofence, changing its shape restarts the detection of
+/// the vehicles being inside of it from their next position
+///
+/// Required permissions: UPDATE_GEOFENCE
+#[utoipa::path(
+    put,
+    tag = "geofence",
+    path = "/geofence/{geofence_id}",
+    security(("session_id" = [])),
+    params(
+        ("geofence_id" = u128, Path, description = "id of the geofence to update"),
+    ),
+    request_body(content = UpdateGeofenceDto, content_type = "application/json"),
+    responses(
+        (
+            status = OK,
+            content_type = "application/json",
+            body = entity::geofence::Model,
+        ),
+    ),
+)]
+pub async fn update_geofence(
+    DbConnection(db): DbConnection,
+    OrgBoundEntityFromPathId(geofence): OrgBoundEntityFromPathId<geofence::Entity>,
+    ValidatedJson(dto): ValidatedJson<UpdateGeofenceDto>,
+) -> Result<Json<geofence::Model>, (StatusCode, SimpleError)> {
+    let geofence_id = geofence.id;
+    let mut g: geofence::ActiveModel = geofence.into();
+
+    g.name = set_if_some(dto.name);
+    g.description = set_if_some(dto.description);
+
+    if let Some(shape) = &dto.shape {
+        g.shape = Set(shape.shape());
+        g.geojson = Set(shape.geojson());
+        g.radius = Set(shape.radius());
+    }
+
+    let updated_geofence = db
+        .transaction::<_, geofence::Model, DbErr>(|tx| {
+            Box::pin(async move {
+                let updated_geofence = g.update(tx).await?;
+
+                if dto.shape.is_some() {
+                    vehicle_geofence::Entity::update_many()
+                        .col_expr(
+                            vehicle_geofence::Column::Inside,
+                            Expr::value::<Option<bool>>(None),
+                        )
+                        .filter(vehicle_geofence::Column::GeofenceId.eq(geofence_id))
+                        .exec(tx)
+                        .await?;
+                }
+
+                Ok(updated_geofence)
+            })
+        })
+        .await
+        .map_err(DbError::from)?;
+
+    Ok(Json(updated_geofence))
+}
+
+/// Deletes a geofence along with its events
+///
+/// Required permissions: DELETE_GEOFENCE
+#[utoipa::path(
+    delete,
+    tag = "geofence",
+    path = "/geofence/{geofence_id}",
+    security(("session_id" = [])),
+    params(
+        ("geofence_id" = u128, Path, description = "id of the geofence to delete"),
+    ),
+    responses(
+        (
+            status = OK,
+            body = String,
+            content_type = "application/json",
+            description = "success message",
+            example = json!("geofence deleted successfully"),
+        ),
+    ),
+)]
+pub async fn delete_geofence(
+    DbConnection(db): DbConnection,
+    OrgBoundEntityFromPathId(geofence): OrgBoundEntityFromPathId<geofence::Entity>,
+) -> Result<Json<String>, (StatusCode, SimpleError)> {
+    geofence.delete(&db).await.map_err(DbError::from)?;
+
+    Ok(Json(String::from("geofence deleted successfully")))
+}
+
+/// Lists the vehicles monitored on a geofence
+#[utoipa::path(
+    get,
+    tag = "geofence",
+    path = "/geofence/{geofence_id}/vehicles",
+    security(("session_id" = [])),
+    params(
+        ("geofence_id" = u128, Path, description = "id of the geofence"),
+    ),
+    responses(
+        (
+            status = OK,
+            content_type = "application/json",
+            body = Vec<entity::vehicle::Model>,
+        ),
+    ),
+)]
+pub async fn list_geofence_vehicles(
+    DbConnection(db): DbConnection,
+    OrgBoundEntityFromPathId(geofence): OrgBoundEntityFromPathId<geofence::Entity>,
+) -> Result<Json<Vec<vehicle::Model>>, (StatusCode, SimpleError)> {
+    let vehicles = geofence
+        .find_related(vehicle::Entity)
+        .order_by_asc(vehicle::Column::Id)
+        .all(&db)
+        .await
+        .map_err(DbError::from)?;
+
+    Ok(Json(vehicles))
+}
+
+/// Sets the vehicles monitored on a geofence, vehicles that were already monitored
+/// keep their state, so entering or leaving the geofence is detected as usual
+///
+/// Required permissions: UPDATE_GEOFENCE
+#[utoipa::path(
+    put,
+    tag = "geofence",
+    path = "/geofence/{geofence_id}/vehicles",
+    security(("session_id" = [])),
+    params(
+        ("geofence_id" = u128, Path, description = "id of the geofence"),
+    ),
+    request_body(content = SetGeofenceVehiclesDto, content_type = "application/json"),
+    responses(
+        (
+            status = OK,
+            description = "the vehicles monitored on the geofence",
+            content_type = "application/json",
+            body = Vec<entity::vehicle::Model>,
+        ),
+        (
+            status = BAD_REQUEST,
+            description = "some vehicle does not exist or belong to another organization",
+            body = SimpleError,
+        ),
+    ),
+)]
+pub async fn set_geofence_vehicles(
+    DbConnection(db): DbConnection,
+    OrganizationId(org_id): OrganizationId,
+    OrgBoundEntityFromPathId(geofence): OrgBoundEntityFromPathId<geofence::Entity>,
+    ValidatedJson(dto): ValidatedJson<SetGeofenceVehiclesDto>,
+) -> Result<Json<Vec<vehicle::Model>>, (StatusCode, SimpleError)> {
+    let vehicles = vehicle::Entity::find()
+        .filter(vehicle::Column::Id.is_in(dto.vehicle_ids.clone()))
+        .filter(vehicle::Column::OrganizationId.eq(org_id))
+        .order_by_asc(vehicle::Column::Id)
+        .all(&db)
+        .await
+        .map_err(DbError::from)?;
+
+    if dto
+        .vehicle_ids
+        .iter()
+        .any(|id| !vehicles.iter().any(|v| v.id == *id))
+    {
+        let err_msg = "vehicle does not exist or does not belong to the request user organization";
+        return Err((StatusCode::BAD_REQUEST, SimpleError::from(err_msg)));
+    }
+
+    let geofence_id = geofence.id;
+    let vehicle_ids: Vec<i32> = vehicles.iter().map(|v| v.id).collect();
+
+    db.transaction::<_, (), DbErr>(|tx| {
+        Box::pin(async move {
+            vehicle_geofence::Entity::delete_many()
+                .filter(vehicle_geofence::Column::GeofenceId.eq(geofence_id))
+                .filter(vehicle_geofence::Column::VehicleId.is_not_in(vehicle_ids.clone()))
+                .exec(tx)
+                .await?;
+
+            if vehicle_ids.is_empty() {
+                return Ok(());
+            }
+
+            let assignments = vehicle_ids
+                .iter()
+                .map(|vehicle_id| vehicle_geofence::ActiveModel {
+                    vehicle_id: Set(*vehicle_id),
+                    geofence_id: Set(geofence_id),
+                    inside: Set(None),
+                    checked_at: Set(None),
+                });
+
+            vehicle_geofence::Entity::insert_many(assignments)
+                .on_conflict(
+                    OnConflict::columns([
+                        vehicle_geofence::Column::VehicleId,
+                        vehicle_geofence::Column::GeofenceId,
+                    ])
+                    .do_nothing()
+                    .to_owned(),
+                )
+                .do_nothing()
+                .exec(tx)
+                .await?;
+
+            Ok(())
+        })
+    })
+    .await
+    .map_err(DbError::from)?;
+
+    Ok(Json(vehicles))
+}
+
+/// Lists the vehicles entering and leaving a geofence, from newest to oldest
+#[utoipa::path(
+    get,
+    tag = "geofence",
+    path = "/geofence/{geofence_id}/events",
+    security(("session_id" = [])),
+    params(
+        Pagination,
+        ("geofence_id" = u128, Path, description = "id of the geofence"),
+    ),
+    responses(
+        (
+            status = OK,
+            description = "paginated list of geofence events",
+            content_type = "application/json",
+            body = PaginatedGeofenceEvent,
+        ),
+    ),
+)]
+pub async fn list_geofence_events(
+    ValidatedQuery(pagination): ValidatedQuery<Pagination>,
+    DbConnection(db): DbConnection,
+    OrgBoundEntityFromPathId(geofence): OrgBoundEntityFromPathId<geofence::Entity>,
+) -> Result<Json<PaginationResult<geofence_event::Model>>, (StatusCode, SimpleError)> {
+    let db_query = geofence_event::Entity::find()
+        .filter(geofence_event::Column::GeofenceId.eq(geofence.id))
+        .order_by_desc(geofence_event::Column::Id)
+        .paginate(&db, pagination.page_size);
+
+    let result = paginated_query_to_pagination_result(db_query, pagination).await?;
+
+    Ok(Json(result))
+}
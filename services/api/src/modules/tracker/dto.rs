@@ -1,10 +1,16 @@
-use chrono::{DateTime, Utc};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use chrono::{DateTime, NaiveDateTime, Utc};
 use serde::{Deserialize, Serialize};
-use shared::constants::TrackerModel;
+use shared::{
+    constants::{TrackerConnectivity, TrackerModel},
+    dto::decoder::command::Command,
+    entity::vehicle_tracker,
+};
 use utoipa::{IntoParams, ToSchema};
 use validator::{Validate, ValidationError};
 
 use crate::modules::common::dto::AscOrDescOrder;
+use crate::modules::tracking::dto::TelemetryDto;
 
 fn is_supported_tracker_model(model: &str) -> Result<(), ValidationError> {
     let allowed_models = TrackerModel::to_string_vec();
@@ -16,6 +22,26 @@ fn is_supported_tracker_model(model: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+/// Maximum length of the content of custom commands, bigger commands
+/// would not fit the packets of most tracker protocols
+const MAX_CUSTOM_COMMAND_LEN: usize = 200;
+
+fn is_valid_tracker_command(command: &Command) -> Result<(), ValidationError> {
+    match command {
+        Command::PositionInterval { seconds: 0 } => Err(ValidationError::new(
+            "position interval must be at least 1 second",
+        )),
+        Command::Custom { content }
+            if content.is_empty() || content.len() > MAX_CUSTOM_COMMAND_LEN =>
+        {
+            Err(ValidationError::new(
+                "invalid custom command content length",
+            ))
+        }
+        _ => Ok(()),
+    }
+}
+
 #[derive(Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "camelCase")]
 pub struct CreateTrackerDto {
@@ -48,6 +74,19 @@ pub struct ListTrackersDto {
     /// If the trackers should be filtered if they are associated
     /// to a vehicle or not, `None` means `any`
     pub with_associated_vehicle: Option<bool>,
+
+    /// Filter trackers by their connectivity, `None` means `any`
+    pub connectivity: Option<TrackerConnectivity>,
+}
+
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackerDto {
+    #[serde(flatten)]
+    pub tracker: vehicle_tracker::Model,
+
+    /// the tracker connectivity, calculated from when it was last seen
+    pub connectivity: TrackerConnectivity,
 }
 
 #[derive(Deserialize, ToSchema, Validate)]
@@ -64,6 +103,37 @@ pub struct SetTrackerVehicleDto {
     pub vehicle_id: Option<Option<i32>>,
 }
 
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct SendTrackerCommandDto {
+    /// The command to send to the tracker, one of:
+    ///
+    /// - `{ "type": "engine_stop" }`
+    /// - `{ "type": "engine_resume" }`
+    /// - `{ "type": "position_interval", "seconds": 30 }`
+    /// - `{ "type": "reboot" }`
+    /// - `{ "type": "custom", "content": "S20,1,1" }` a protocol specific command
+    #[schema(value_type = Object)]
+    #[validate(custom = "is_valid_tracker_command")]
+    pub command: Command,
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+pub struct ListTrackerAlarmsDto {
+    /// If the alarms should be filtered by being acknowledged or not, `None` means `any`
+    pub acknowledged: Option<bool>,
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct AcknowledgeTrackerAlarmDto {
+    /// Note about the alarm, eg: what was done about it
+    #[validate(length(max = 500))]
+    pub note: Option<String>,
+}
+
 #[derive(Deserialize, IntoParams, Validate)]
 #[serde(rename_all = "camelCase")]
 #[into_params(parameter_in = Query)]
@@ -72,8 +142,21 @@ pub struct DeleteTrackerDto {
     pub delete_associated_sim_cards: Option<bool>,
 }
 
+fn is_valid_positions_sampling(dto: &GetTrackerPositionsDto) -> Result<(), ValidationError> {
+    let is_sampled = dto.simplify.is_some() || dto.bucket.is_some();
+
+    if is_sampled && (dto.after.is_none() || dto.before.is_none()) {
+        return Err(ValidationError::new(
+            "after and before are required to simplify or bucket positions",
+        ));
+    }
+
+    Ok(())
+}
+
 #[derive(Deserialize, ToSchema, Validate)]
 #[serde(rename_all = "camelCase")]
+#[validate(schema(function = "is_valid_positions_sampling"))]
 pub struct GetTrackerPositionsDto {
     /// List positions after a timestamp
     pub after: Option<DateTime<Utc>>,
@@ -87,6 +170,181 @@ pub struct GetTrackerPositionsDto {
 
     #[serde(default)]
     pub order: AscOrDescOrder,
+
+    /// Continue listing from the `nextCursor` or `previousCursor` of a previous response,
+    /// the other fields must be the same as the request of the previous response
+    pub cursor: Option<String>,
+
+    /// Simplify the route with a tolerance in meters, skipping positions that do not
+    /// change the route shape by more than it, requires `after` and `before`
+    #[validate(range(min = 0.1, max = 10000.0))]
+    pub simplify: Option<f64>,
+
+    /// Average the positions on time buckets of this many seconds, listing one position
+    /// per bucket, requires `after` and `before`
+    #[validate(range(min = 1, max = 86400))]
+    pub bucket: Option<i32>,
+}
+
+/// Where a location list continues, the location list is ordered by time and a
+/// tracker cannot have two locations at the same time, so the time of the location
+/// where the list stopped is enough to continue it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LocationCursor {
+    pub time: DateTime<Utc>,
+
+    /// tracker of the listed locations, so a cursor is not used on another tracker
+    pub tracker_id: i32,
+
+    /// if the list continues forward, after the location on the list order,
+    /// or backward, before the location
+    pub forward: bool,
+}
+
+impl LocationCursor {
+    /// Encodes the cursor as a opaque URL safe string
+    pub fn encode(&self) -> String {
+        let direction = if self.forward { "n" } else { "p" };
+        let raw = format!(
+            "{}:{}:{}",
+            self.time.timestamp_micros(),
+            self.tracker_id,
+            direction
+        );
+
+        URL_SAFE_NO_PAD.encode(raw)
+    }
+
+    pub fn decode(cursor: &str) -> Option<LocationCursor> {
+        let raw = String::from_utf8(URL_SAFE_NO_PAD.decode(cursor).ok()?).ok()?;
+
+        let [time, tracker_id, direction]: [&str; 3] =
+            raw.split(':').collect::<Vec<&str>>().try_into().ok()?;
+
+        let forward = match direction {
+            "n" => true,
+            "p" => false,
+            _ => return None,
+        };
+
+        Some(LocationCursor {
+            time: NaiveDateTime::from_timestamp_micros(time.parse().ok()?)?.and_utc(),
+            tracker_id: tracker_id.parse().ok()?,
+            forward,
+        })
+    }
+}
+
+/// How the page of a location list is queried, from the order of the list
+/// and the cursor the page continues from, if any
+#[derive(Debug, Clone, Copy)]
+pub struct LocationPageQuery {
+    pub tracker_id: i32,
+
+    pub cursor: Option<LocationCursor>,
+
+    /// maximum amount of locations of the page
+    pub limit: u64,
+
+    /// if the page is after the cursor on the list order, or before it
+    pub forward: bool,
+
+    /// if the locations are queried in ascending time order, backward
+    /// pages are queried on the opposite order and reversed afterwards
+    pub query_asc: bool,
+}
+
+/// The locations of a page of a location list and the cursors around it
+pub struct LocationPage<T> {
+    pub locations: Vec<T>,
+
+    pub next_cursor: Option<String>,
+
+    pub previous_cursor: Option<String>,
+}
+
+impl LocationPageQuery {
+    /// The page of the locations of a tracker requested by the dto, `None` if
+    /// its cursor cannot be decoded or is a cursor of another tracker
+    pub fn new(tracker_id: i32, dto: &GetTrackerPositionsDto) -> Option<LocationPageQuery> {
+        let cursor = match &dto.cursor {
+            Some(cursor) => {
+                Some(LocationCursor::decode(cursor).filter(|c| c.tracker_id == tracker_id)?)
+            }
+            None => None,
+        };
+
+        let is_asc = dto.order == AscOrDescOrder::Asc;
+        let forward = cursor.is_none_or(|c| c.forward);
+
+        Some(LocationPageQuery {
+            tracker_id,
+            cursor,
+            limit: dto.limit.unwrap_or(15),
+            forward,
+            query_asc: is_asc == forward,
+        })
+    }
+
+    /// Amount of locations to query, one more than the limit to know if there are more locations
+    pub fn query_limit(&self) -> u64 {
+        self.limit + 1
+    }
+
+    /// The page of the queried locations, `rows` being the locations queried after or before
+    /// the cursor on the `query_asc` order, limited to `query_limit`
+    pub fn page<T>(&self, mut rows: Vec<T>, time: impl Fn(&T) -> DateTime<Utc>) -> LocationPage<T> {
+        let has_more = rows.len() as u64 > self.limit;
+        rows.truncate(self.limit as usize);
+
+        if !self.forward {
+            rows.reverse();
+        }
+
+        let cursor_at = |row: Option<&T>, forward: bool| {
+            row.map(|row| {
+                LocationCursor {
+                    time: time(row),
+                    tracker_id: self.tracker_id,
+                    forward,
+                }
+                .encode()
+            })
+        };
+
+        // a page reached from a cursor always has locations on the direction it came from
+        let (next_cursor, previous_cursor) = match self.forward {
+            true => (
+                cursor_at(rows.last(), true).filter(|_| has_more),
+                cursor_at(rows.first(), false).filter(|_| self.cursor.is_some()),
+            ),
+            false => (
+                cursor_at(rows.last(), true),
+                cursor_at(rows.first(), false).filter(|_| has_more),
+            ),
+        };
+
+        LocationPage {
+            locations: rows,
+            next_cursor,
+            previous_cursor,
+        }
+    }
+}
+
+/// A page of a location list
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackerLocationListDto {
+    pub locations: Vec<TrackerLocationDto>,
+
+    /// Cursor to list the locations after the last location of the page,
+    /// `null` if there are no more locations
+    pub next_cursor: Option<String>,
+
+    /// Cursor to list the locations before the first location of the page,
+    /// `null` if there are no previous locations or the page is the first one
+    pub previous_cursor: Option<String>,
 }
 
 #[derive(Serialize, ToSchema)]
@@ -95,11 +353,17 @@ pub struct TrackerLocationDto {
     pub time: DateTime<Utc>,
 
     pub point: Point,
+
+    #[serde(flatten)]
+    pub telemetry: TelemetryDto,
 }
 
+/// A location point, stored with the latitude as the X coordinate
 #[derive(Serialize, ToSchema)]
 pub struct Point {
+    /// latitude
     pub x: f64,
+    /// longitude
     pub y: f64,
 }
 
@@ -111,3 +375,243 @@ impl From<geo_types::Point<f64>> for Point {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{GetTrackerPositionsDto, LocationCursor, LocationPageQuery};
+    use crate::modules::common::dto::AscOrDescOrder;
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+
+    const TRACKER_ID: i32 = 7;
+
+    fn at(second: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap() + Duration::seconds(second)
+    }
+
+    fn dto(order: AscOrDescOrder, cursor: Option<String>) -> GetTrackerPositionsDto {
+        GetTrackerPositionsDto {
+            after: None,
+            before: None,
+            limit: Some(3),
+            order,
+            cursor,
+            simplify: None,
+            bucket: None,
+        }
+    }
+
+    /// Lists the page of `locations` as the database would for the query
+    fn list(locations: &[DateTime<Utc>], query: &LocationPageQuery) -> Vec<DateTime<Utc>> {
+        let mut rows: Vec<DateTime<Utc>> = locations
+            .iter()
+            .copied()
+            .filter(|time| match (query.cursor, query.query_asc) {
+                (Some(c), true) => *time > c.time,
+                (Some(c), false) => *time < c.time,
+                (None, _) => true,
+            })
+            .collect();
+
+        rows.sort();
+
+        if !query.query_asc {
+            rows.reverse();
+        }
+
+        rows.truncate(query.query_limit() as usize);
+        rows
+    }
+
+    type Pages = Vec<Vec<DateTime<Utc>>>;
+
+    /// Walks every page of the list by the next cursors, then back by the previous
+    /// cursors, returning the locations of the pages on each direction
+    fn walk(locations: &[DateTime<Utc>], order: AscOrDescOrder) -> (Pages, Pages) {
+        let page_of = |cursor: Option<String>| {
+            let query = LocationPageQuery::new(TRACKER_ID, &dto(order, cursor)).unwrap();
+            query.page(list(locations, &query), |time| *time)
+        };
+
+        let mut forward = vec![];
+        let mut page = page_of(None);
+
+        assert_eq!(page.previous_cursor, None);
+
+        loop {
+            forward.push(page.locations.clone());
+
+            match page.next_cursor.clone() {
+                Some(cursor) => page = page_of(Some(cursor)),
+                None => break,
+            }
+        }
+
+        let mut backward = vec![page.locations.clone()];
+
+        while let Some(cursor) = page.previous_cursor.clone() {
+            page = page_of(Some(cursor));
+            backward.push(page.locations.clone());
+        }
+
+        backward.reverse();
+
+        (forward, backward)
+    }
+
+    #[test]
+    fn encodes_and_decodes_cursors() {
+        for forward in [true, false] {
+            let cursor = LocationCursor {
+                time: Utc.timestamp_micros(1_709_294_400_123_456).unwrap(),
+                tracker_id: TRACKER_ID,
+                forward,
+            };
+
+            assert_eq!(LocationCursor::decode(&cursor.encode()), Some(cursor));
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_cursors() {
+        let encode = |raw: &str| URL_SAFE_NO_PAD.encode(raw);
+
+        let cursors = [
+            String::from("not base64!"),
+            encode("1709294400123456:7"),
+            encode("1709294400123456:7:n:1"),
+            encode("1709294400123456:7:x"),
+            encode("yesterday:7:n"),
+            encode("1709294400123456:seven:n"),
+            encode(&format!("{}:7:n", i64::MAX)),
+        ];
+
+        for cursor in cursors {
+            assert_eq!(LocationCursor::decode(&cursor), None, "{cursor}");
+
+            let page = LocationPageQuery::new(TRACKER_ID, &dto(AscOrDescOrder::Asc, Some(cursor)));
+            assert!(page.is_none());
+        }
+    }
+
+    #[test]
+    fn rejects_cursors_of_other_trackers() {
+        let cursor = LocationCursor {
+            time: at(0),
+            tracker_id: TRACKER_ID + 1,
+            forward: true,
+        };
+
+        let dto = dto(AscOrDescOrder::Asc, Some(cursor.encode()));
+
+        assert!(LocationPageQuery::new(TRACKER_ID, &dto).is_none());
+        assert!(LocationPageQuery::new(TRACKER_ID + 1, &dto).is_some());
+    }
+
+    #[test]
+    fn queries_backward_pages_on_the_opposite_order() {
+        let cursor = |forward| {
+            Some(
+                LocationCursor {
+                    time: at(0),
+                    tracker_id: TRACKER_ID,
+                    forward,
+                }
+                .encode(),
+            )
+        };
+
+        let query_asc = |order, cursor| {
+            LocationPageQuery::new(TRACKER_ID, &dto(order, cursor))
+                .unwrap()
+                .query_asc
+        };
+
+        assert!(query_asc(AscOrDescOrder::Asc, None));
+        assert!(query_asc(AscOrDescOrder::Asc, cursor(true)));
+        assert!(!query_asc(AscOrDescOrder::Asc, cursor(false)));
+        assert!(!query_asc(AscOrDescOrder::Desc, None));
+        assert!(!query_asc(AscOrDescOrder::Desc, cursor(true)));
+        assert!(query_asc(AscOrDescOrder::Desc, cursor(false)));
+    }
+
+    #[test]
+    fn pages_ascending_lists_both_ways() {
+        let locations: Vec<DateTime<Utc>> = (0..8).map(at).collect();
+
+        let (forward, backward) = walk(&locations, AscOrDescOrder::Asc);
+
+        let expected = vec![
+            vec![at(0), at(1), at(2)],
+            vec![at(3), at(4), at(5)],
+            vec![at(6), at(7)],
+        ];
+
+        assert_eq!(forward, expected);
+        assert_eq!(backward, expected);
+    }
+
+    #[test]
+    fn pages_descending_lists_both_ways() {
+        let locations: Vec<DateTime<Utc>> = (0..8).map(at).collect();
+
+        let (forward, backward) = walk(&locations, AscOrDescOrder::Desc);
+
+        let expected = vec![
+            vec![at(7), at(6), at(5)],
+            vec![at(4), at(3), at(2)],
+            vec![at(1), at(0)],
+        ];
+
+        assert_eq!(forward, expected);
+        assert_eq!(backward, expected);
+    }
+
+    #[test]
+    fn pages_lists_ending_on_a_page_boundary() {
+        let locations: Vec<DateTime<Utc>> = (0..6).map(at).collect();
+
+        let (forward, _) = walk(&locations, AscOrDescOrder::Asc);
+
+        assert_eq!(
+            forward,
+            vec![vec![at(0), at(1), at(2)], vec![at(3), at(4), at(5)]]
+        );
+
+        let (forward, backward) = walk(&[], AscOrDescOrder::Asc);
+
+        assert_eq!(forward, vec![Vec::<DateTime<Utc>>::new()]);
+        assert_eq!(backward, forward);
+    }
+
+    #[test]
+    fn backward_pages_point_forward_to_the_page_they_came_from() {
+        let locations: Vec<DateTime<Utc>> = (0..8).map(at).collect();
+
+        let cursor = LocationCursor {
+            time: at(3),
+            tracker_id: TRACKER_ID,
+            forward: false,
+        };
+
+        let query =
+            LocationPageQuery::new(TRACKER_ID, &dto(AscOrDescOrder::Asc, Some(cursor.encode())))
+                .unwrap();
+
+        let page = query.page(list(&locations, &query), |time| *time);
+
+        assert_eq!(page.locations, vec![at(0), at(1), at(2)]);
+        assert_eq!(page.previous_cursor, None);
+
+        let next = page.next_cursor.and_then(|c| LocationCursor::decode(&c));
+
+        assert_eq!(
+            next,
+            Some(LocationCursor {
+                time: at(2),
+                tracker_id: TRACKER_ID,
+                forward: true,
+            })
+        );
+    }
+}
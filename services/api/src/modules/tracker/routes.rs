@@ -1,11 +1,15 @@
 use super::dto::{
-    self, CreateTrackerDto, DeleteTrackerDto, GetTrackerPositionsDto, ListTrackersDto,
+    self, AcknowledgeTrackerAlarmDto, CreateTrackerDto, DeleteTrackerDto, GetTrackerPositionsDto,
+    ListTrackerAlarmsDto, ListTrackersDto, LocationPageQuery, SendTrackerCommandDto, TrackerDto,
     UpdateTrackerDto,
 };
 use crate::{
     database::{self, error::DbError, helpers::set_if_some},
     modules::{
-        auth::{self, middleware::AclLayer},
+        auth::{
+            self,
+            middleware::{AclLayer, RequestUser},
+        },
         common::{
             dto::{Pagination, PaginationResult},
             extractors::{
@@ -15,15 +19,21 @@ use crate::{
             responses::{internal_error_res, SimpleError},
         },
         globals::TRACKER_ID_CACHE,
+        tracking::{
+            dto::ExportLocationsDto,
+            export, trip,
+            utils::{location_history_query, LocationOwner, LocationRow, LocationSampling},
+        },
     },
     server::controller::AppState,
 };
 use axum::{
-    extract::{Path, Query},
+    extract::{Path, Query, State},
+    response::Response,
     routing::{delete, get, post, put},
-    Json, Router,
+    Extension, Json, Router,
 };
-use chrono::{DateTime, Utc};
+use chrono::Utc;
 use http::StatusCode;
 use migration::Expr;
 use sea_orm::sea_query::extension::postgres::PgExpr;
@@ -31,14 +41,16 @@ use sea_orm::{
     ActiveModelTrait, ColumnTrait, EntityTrait, PaginatorTrait, QueryFilter, QueryOrder,
     QuerySelect, QueryTrait, Set, TryIntoModel,
 };
-use sea_query::{Cond, PostgresQueryBuilder, Query as SeaQuery};
+use sea_query::{Cond, Order, PostgresQueryBuilder, Query as SeaQuery};
 use sea_query_binder::SqlxBinder;
 use shared::entity::{
-    sim_card, traits::QueryableByIdAndOrgId, vehicle_tracker, vehicle_tracker_last_location,
+    position_filter_decision, sim_card, tracker_alarm, tracker_command,
+    traits::QueryableByIdAndOrgId, vehicle_tracker, vehicle_tracker_last_location,
     vehicle_tracker_location,
 };
 use shared::{
-    constants::{Permission, TrackerModel},
+    constants::{Permission, TrackerCommandStatus, TrackerModel},
+    dto::decoder::command::TrackerCommand,
     entity::vehicle,
 };
 use std::str::FromStr;
@@ -71,9 +83,30 @@ pub fn create_router(state: AppState) -> Router<AppState> {
         )
         //
         .route("/:tracker_id/get-location-list", post(get_location_list))
+        .route(
+            "/:tracker_id/export-locations",
+            get(export_tracker_locations),
+        )
         .route("/:tracker_id/last-location", get(get_tracker_location))
         .route("/:tracker_id/sim-cards", get(list_tracker_sim_cards))
         //
+        .route(
+            "/:tracker_id/command",
+            post(send_tracker_command).layer(AclLayer::single(Permission::SendTrackerCommand)),
+        )
+        .route("/:tracker_id/command", get(list_tracker_commands))
+        //
+        .route("/:tracker_id/alarms", get(list_tracker_alarms))
+        .route(
+            "/:tracker_id/alarms/:alarm_id/acknowledge",
+            post(acknowledge_tracker_alarm)
+                .layer(AclLayer::single(Permission::AcknowledgeTrackerAlarm)),
+        )
+        .route(
+            "/:tracker_id/filtered-positions",
+            get(list_tracker_filtered_positions),
+        )
+        //
         .layer(axum::middleware::from_fn_with_state(
             state,
             auth::middleware::require_user,
@@ -102,14 +135,19 @@ async fn delete_tracker_imei_from_cache(imei: String) {
         (
             status = OK,
             content_type = "application/json",
-            body = entity::vehicle::Model,
+            body = TrackerDto,
         ),
     ),
 )]
 pub async fn get_tracker(
     OrgBoundEntityFromPathId(tracker): OrgBoundEntityFromPathId<vehicle_tracker::Entity>,
-) -> Result<Json<vehicle_tracker::Model>, (StatusCode, SimpleError)> {
-    Ok(Json(tracker))
+) -> Result<Json<TrackerDto>, (StatusCode, SimpleError)> {
+    let connectivity = tracker.connectivity(Utc::now());
+
+    Ok(Json(TrackerDto {
+        tracker,
+        connectivity,
+    }))
 }
 
 /// Update a tracker
@@ -219,6 +257,8 @@ pub async fn delete_tracker(
         .await
         .map_err(DbError::from)?;
 
+    trip::forget_tracker(tracker.id).await;
+
     let span = Span::current();
 
     tokio::spawn(delete_tracker_imei_from_cache(tracker.imei).instrument(span));
@@ -260,6 +300,9 @@ pub async fn list_tracker_sim_cards(
 }
 
 /// Get a list of tracker locations
+///
+/// to walk through long location histories, use the response cursors to get
+/// the next or previous page of locations
 #[utoipa::path(
     post,
     tag = "tracker",
@@ -272,58 +315,68 @@ pub async fn list_tracker_sim_cards(
     responses(
         (
             status = OK,
-            description = "tracker location",
-            body = Vec<TrackerLocationDto>,
+            description = "tracker locations page",
+            body = TrackerLocationListDto,
             content_type = "application/json",
         ),
+        (
+            status = BAD_REQUEST,
+            description = "invalid cursor",
+            body = SimpleError,
+        ),
     ),
 )]
 pub async fn get_location_list(
     OrgBoundEntityFromPathId(tracker): OrgBoundEntityFromPathId<vehicle_tracker::Entity>,
     DbConnection(db): DbConnection,
     ValidatedJson(search_query): ValidatedJson<GetTrackerPositionsDto>,
-) -> Result<Json<Vec<dto::TrackerLocationDto>>, (StatusCode, SimpleError)> {
-    let (q, args) = SeaQuery::select()
-        .column(vehicle_tracker_location::Column::Time)
-        .column(vehicle_tracker_location::Column::Point)
-        .from(vehicle_tracker_location::Entity)
-        .cond_where(
-            Cond::all()
-                .add(Expr::col(vehicle_tracker_location::Column::VehicleTrackerId).eq(tracker.id))
-                .add_option(
-                    search_query
-                        .after
-                        .map(|a| Expr::col(vehicle_tracker_location::Column::Time).gt(a)),
-                )
-                .add_option(
-                    search_query
-                        .before
-                        .map(|b| Expr::col(vehicle_tracker_location::Column::Time).lt(b)),
-                ),
-        )
+) -> Result<Json<dto::TrackerLocationListDto>, (StatusCode, SimpleError)> {
+    let page = location_page_query(tracker.id, &search_query)?;
+
+    let time_col = || Expr::col(vehicle_tracker_location::Column::Time);
+
+    let range = Cond::all()
+        .add_option(search_query.after.map(|a| time_col().gt(a)))
+        .add_option(search_query.before.map(|b| time_col().lt(b)));
+
+    let sampling = LocationSampling {
+        simplify: search_query.simplify,
+        bucket: search_query.bucket,
+    };
+
+    let (q, args) = location_history_query(LocationOwner::Tracker(tracker.id), range, sampling)
+        .and_where_option(page.cursor.map(|c| match page.query_asc {
+            true => time_col().gt(c.time),
+            false => time_col().lt(c.time),
+        }))
         .order_by(
             vehicle_tracker_location::Column::Time,
-            search_query.order.into(),
+            if page.query_asc {
+                Order::Asc
+            } else {
+                Order::Desc
+            },
         )
-        .limit(search_query.limit.unwrap_or(15))
+        .limit(page.query_limit())
         .to_owned()
         .build_sqlx(PostgresQueryBuilder);
 
-    let rows: Vec<(
-        DateTime<Utc>,
-        geozero::wkb::Decode<geo_types::Geometry<f64>>,
-    )> = sqlx::query_as_with(&q, args)
+    let rows: Vec<LocationRow> = sqlx::query_as_with(&q, args)
         .fetch_all(db.get_postgres_connection_pool())
         .await
         .map_err(|_| internal_error_res())?;
 
-    let positions: Vec<dto::TrackerLocationDto> = rows
+    let page = page.page(rows, |row| row.time);
+
+    let locations: Vec<dto::TrackerLocationDto> = page
+        .locations
         .iter()
         .filter_map(|row| {
-            if let Some(geo_types::Geometry::Point(point)) = row.1.geometry {
+            if let Some(geo_types::Geometry::Point(point)) = row.point.geometry {
                 let loc = dto::TrackerLocationDto {
                     point: point.into(),
-                    time: row.0,
+                    time: row.time,
+                    telemetry: row.telemetry(),
                 };
 
                 return Some(loc);
@@ -333,7 +386,57 @@ pub async fn get_location_list(
         })
         .collect();
 
-    Ok(Json(positions))
+    Ok(Json(dto::TrackerLocationListDto {
+        locations,
+        next_cursor: page.next_cursor,
+        previous_cursor: page.previous_cursor,
+    }))
+}
+
+/// The page of a location list requested by the dto, a cursor that cannot
+/// be decoded or is of another tracker is a bad request
+fn location_page_query(
+    tracker_id: i32,
+    dto: &GetTrackerPositionsDto,
+) -> Result<LocationPageQuery, (StatusCode, SimpleError)> {
+    LocationPageQuery::new(tracker_id, dto)
+        .ok_or((StatusCode::BAD_REQUEST, SimpleError::from("invalid cursor")))
+}
+
+/// Export the locations of a tracker within a time range as a file,
+/// the file is streamed so exports of long ranges start right away
+#[utoipa::path(
+    get,
+    tag = "tracker",
+    path = "/tracker/{tracker_id}/export-locations",
+    security(("session_id" = [])),
+    params(
+        ExportLocationsDto,
+        ("tracker_id" = u128, Path, description = "id of the tracker"),
+    ),
+    responses(
+        (
+            status = OK,
+            description = "the locations in the requested format, as a attachment",
+            body = String,
+            content_type = ["application/gpx+xml", "application/vnd.google-earth.kml+xml", "application/geo+json", "text/csv"],
+        ),
+        (
+            status = BAD_REQUEST,
+            description = "invalid dto error message",
+            body = SimpleError,
+        ),
+    ),
+)]
+pub async fn export_tracker_locations(
+    OrgBoundEntityFromPathId(tracker): OrgBoundEntityFromPathId<vehicle_tracker::Entity>,
+    DbConnection(db): DbConnection,
+    ValidatedQuery(dto): ValidatedQuery<ExportLocationsDto>,
+) -> Response {
+    let name = format!("tracker {}", tracker.imei);
+    let filename = format!("tracker-{}-locations", tracker.id);
+
+    export::export_locations(&db, LocationOwner::Tracker(tracker.id), name, filename, dto)
 }
 
 /// Get the most recent tracker location
@@ -360,8 +463,15 @@ pub async fn get_tracker_location(
 ) -> Result<Json<Option<dto::TrackerLocationDto>>, (StatusCode, SimpleError)> {
     let (q, args) =
         SeaQuery::select()
-            .column(vehicle_tracker_last_location::Column::Time)
-            .column(vehicle_tracker_last_location::Column::Point)
+            .columns([
+                vehicle_tracker_last_location::Column::VehicleTrackerId,
+                vehicle_tracker_last_location::Column::Time,
+                vehicle_tracker_last_location::Column::Point,
+                vehicle_tracker_last_location::Column::Speed,
+                vehicle_tracker_last_location::Column::Heading,
+                vehicle_tracker_last_location::Column::Ignition,
+                vehicle_tracker_last_location::Column::Status,
+            ])
             .from(vehicle_tracker_last_location::Entity)
             .cond_where(Cond::all().add(
                 Expr::col(vehicle_tracker_last_location::Column::VehicleTrackerId).eq(tracker_id),
@@ -369,19 +479,17 @@ pub async fn get_tracker_location(
             .to_owned()
             .build_sqlx(PostgresQueryBuilder);
 
-    let row: Option<(
-        DateTime<Utc>,
-        geozero::wkb::Decode<geo_types::Geometry<f64>>,
-    )> = sqlx::query_as_with(&q, args)
+    let row: Option<LocationRow> = sqlx::query_as_with(&q, args)
         .fetch_optional(db.get_postgres_connection_pool())
         .await
         .map_err(|_| internal_error_res())?;
 
-    if let Some(time_and_loc) = row {
-        if let Some(geo_types::Geometry::Point(point)) = time_and_loc.1.geometry {
+    if let Some(row) = row {
+        if let Some(geo_types::Geometry::Point(point)) = row.point.geometry {
             let loc = dto::TrackerLocationDto {
                 point: point.into(),
-                time: time_and_loc.0,
+                time: row.time,
+                telemetry: row.telemetry(),
             };
 
             return Ok(Json(Some(loc)));
@@ -462,6 +570,8 @@ pub async fn set_tracker_vehicle(
         .await
         .map_err(DbError::from)?;
 
+    trip::forget_tracker(tracker.id).await;
+
     Ok(Json(String::from("tracker vehicle set successfully")))
 }
 
@@ -581,6 +691,12 @@ pub async fn list_trackers(
                 query.filter(vehicle_tracker::Column::VehicleId.is_null())
             }
         })
+        .apply_if(filter.connectivity, |query, connectivity| {
+            query.filter(vehicle_tracker::Entity::connectivity_condition(
+                connectivity,
+                Utc::now(),
+            ))
+        })
         .apply_if(filter.imei, |query, imei| {
             if !imei.is_empty() {
                 let col = Expr::col((vehicle_tracker::Entity, vehicle_tracker::Column::Imei));
@@ -598,3 +714,274 @@ pub async fn list_trackers(
 
     Ok(Json(result))
 }
+
+/// Sends a command to a tracker through the decoder service, such as cutting
+/// off the vehicle engine, the command is stored as pending and its status is
+/// updated once the decoder sends it and once the tracker acknowledges it
+///
+/// Required permissions: SEND_TRACKER_COMMAND
+#[utoipa::path(
+    post,
+    tag = "tracker",
+    path = "/tracker/{tracker_id}/command",
+    security(("session_id" = [])),
+    params(
+        ("tracker_id" = u128, Path, description = "id of the tracker to send the command to"),
+    ),
+    request_body(content = SendTrackerCommandDto, content_type = "application/json"),
+    responses(
+        (
+            status = OK,
+            description = "the created command",
+            content_type = "application/json",
+            body = entity::tracker_command::Model,
+        ),
+    ),
+)]
+#[tracing::instrument(skip_all)]
+pub async fn send_tracker_command(
+    Extension(req_user): Extension<RequestUser>,
+    State(state): State<AppState>,
+    DbConnection(db): DbConnection,
+    OrgBoundEntityFromPathId(tracker): OrgBoundEntityFromPathId<vehicle_tracker::Entity>,
+    ValidatedJson(dto): ValidatedJson<SendTrackerCommandDto>,
+) -> Result<Json<tracker_command::Model>, (StatusCode, SimpleError)> {
+    let command = serde_json::to_value(&dto.command).or(Err(internal_error_res()))?;
+
+    let created_command = tracker_command::ActiveModel {
+        command: Set(command),
+        vehicle_tracker_id: Set(tracker.id),
+        vehicle_id: Set(tracker.vehicle_id),
+        user_id: Set(Some(req_user.0.id)),
+        organization_id: Set(tracker.organization_id),
+        ..Default::default()
+    }
+    .insert(&db)
+    .await
+    .map_err(DbError::from)?;
+
+    let publish_result = state
+        .decoder_service
+        .send_command(&TrackerCommand {
+            id: created_command.id,
+            imei: tracker.imei,
+            protocol: tracker.model.get_info().protocol.to_string(),
+            command: dto.command,
+        })
+        .await;
+
+    if publish_result.is_err() {
+        let mut failed_command: tracker_command::ActiveModel = created_command.into();
+
+        failed_command.status = Set(TrackerCommandStatus::Failed);
+        failed_command.failure_reason = Set(Some(String::from(
+            "failed to publish command to the decoder service",
+        )));
+
+        let failed_command = failed_command.update(&db).await.map_err(DbError::from)?;
+
+        return Ok(Json(failed_command));
+    }
+
+    Ok(Json(created_command))
+}
+
+/// Lists the commands sent to a tracker, from newest to oldest
+#[utoipa::path(
+    get,
+    tag = "tracker",
+    path = "/tracker/{tracker_id}/command",
+    security(("session_id" = [])),
+    params(
+        Pagination,
+        ("tracker_id" = u128, Path, description = "id of the tracker"),
+    ),
+    responses(
+        (
+            status = OK,
+            description = "paginated list of tracker commands",
+            content_type = "application/json",
+            body = PaginatedTrackerCommand,
+        ),
+    ),
+)]
+pub async fn list_tracker_commands(
+    ValidatedQuery(pagination): ValidatedQuery<Pagination>,
+    DbConnection(db): DbConnection,
+    OrgBoundEntityFromPathId(tracker): OrgBoundEntityFromPathId<vehicle_tracker::Entity>,
+) -> Result<Json<PaginationResult<tracker_command::Model>>, (StatusCode, SimpleError)> {
+    let db_query = tracker_command::Entity::find()
+        .filter(tracker_command::Column::VehicleTrackerId.eq(tracker.id))
+        .order_by_desc(tracker_command::Column::Id)
+        .paginate(&db, pagination.page_size);
+
+    let result =
+        database::helpers::paginated_query_to_pagination_result(db_query, pagination).await?;
+
+    Ok(Json(result))
+}
+
+/// Lists the alarms raised by a tracker, from newest to oldest
+#[utoipa::path(
+    get,
+    tag = "tracker",
+    path = "/tracker/{tracker_id}/alarms",
+    security(("session_id" = [])),
+    params(
+        Pagination,
+        ListTrackerAlarmsDto,
+        ("tracker_id" = u128, Path, description = "id of the tracker"),
+    ),
+    responses(
+        (
+            status = OK,
+            description = "paginated list of tracker alarms",
+            content_type = "application/json",
+            body = PaginatedTrackerAlarm,
+        ),
+    ),
+)]
+pub async fn list_tracker_alarms(
+    ValidatedQuery(pagination): ValidatedQuery<Pagination>,
+    ValidatedQuery(filter): ValidatedQuery<ListTrackerAlarmsDto>,
+    DbConnection(db): DbConnection,
+    OrgBoundEntityFromPathId(tracker): OrgBoundEntityFromPathId<vehicle_tracker::Entity>,
+) -> Result<Json<PaginationResult<tracker_alarm::Model>>, (StatusCode, SimpleError)> {
+    let db_query = tracker_alarm::Entity::find()
+        .filter(tracker_alarm::Column::VehicleTrackerId.eq(tracker.id))
+        .apply_if(filter.acknowledged, |query, acknowledged| {
+            if acknowledged {
+                query.filter(tracker_alarm::Column::AcknowledgedAt.is_not_null())
+            } else {
+                query.filter(tracker_alarm::Column::AcknowledgedAt.is_null())
+            }
+        })
+        .order_by_desc(tracker_alarm::Column::Id)
+        .paginate(&db, pagination.page_size);
+
+    let result =
+        database::helpers::paginated_query_to_pagination_result(db_query, pagination).await?;
+
+    Ok(Json(result))
+}
+
+/// Acknowledges a tracker alarm, recording who acknowledged it
+///
+/// Required permissions: ACKNOWLEDGE_TRACKER_ALARM
+#[utoipa::path(
+    post,
+    tag = "tracker",
+    path = "/tracker/{tracker_id}/alarms/{alarm_id}/acknowledge",
+    security(("session_id" = [])),
+    params(
+        ("tracker_id" = u128, Path, description = "id of the tracker"),
+        ("alarm_id" = u128, Path, description = "id of the alarm to acknowledge"),
+    ),
+    request_body(content = AcknowledgeTrackerAlarmDto, content_type = "application/json"),
+    responses(
+        (
+            status = OK,
+            description = "the acknowledged alarm",
+            content_type = "application/json",
+            body = entity::tracker_alarm::Model,
+        ),
+        (
+            status = BAD_REQUEST,
+            description = "the alarm was already acknowledged",
+            body = SimpleError,
+        ),
+    ),
+)]
+pub async fn acknowledge_tracker_alarm(
+    Path((tracker_id, alarm_id)): Path<(i32, i32)>,
+    OrganizationId(org_id): OrganizationId,
+    Extension(req_user): Extension<RequestUser>,
+    DbConnection(db): DbConnection,
+    ValidatedJson(dto): ValidatedJson<AcknowledgeTrackerAlarmDto>,
+) -> Result<Json<tracker_alarm::Model>, (StatusCode, SimpleError)> {
+    let alarm = tracker_alarm::Entity::find_by_id(alarm_id)
+        .filter(tracker_alarm::Column::VehicleTrackerId.eq(tracker_id))
+        .filter(tracker_alarm::Column::OrganizationId.eq(org_id))
+        .one(&db)
+        .await
+        .map_err(DbError::from)?
+        .ok_or((StatusCode::NOT_FOUND, SimpleError::entity_not_found()))?;
+
+    if alarm.acknowledged_at.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            SimpleError::from("alarm already acknowledged"),
+        ));
+    }
+
+    let mut alarm: tracker_alarm::ActiveModel = alarm.into();
+
+    alarm.acknowledged_at = Set(Some(Utc::now()));
+    alarm.acknowledged_by_user_id = Set(Some(req_user.0.id));
+    alarm.acknowledgement_note = Set(dto.note);
+
+    let acknowledged_alarm = alarm.update(&db).await.map_err(DbError::from)?;
+
+    Ok(Json(acknowledged_alarm))
+}
+
+/// Lists the positions of a tracker rejected by the position quality filter, from newest
+/// to oldest, with the check each position failed, to debug the filter limits
+#[utoipa::path(
+    get,
+    tag = "tracker",
+    path = "/tracker/{tracker_id}/filtered-positions",
+    security(("session_id" = [])),
+    params(
+        Pagination,
+        ("tracker_id" = u128, Path, description = "id of the tracker"),
+    ),
+    responses(
+        (
+            status = OK,
+            description = "paginated list of filtered tracker positions",
+            content_type = "application/json",
+            body = PaginatedPositionFilterDecision,
+        ),
+    ),
+)]
+pub async fn list_tracker_filtered_positions(
+    ValidatedQuery(pagination): ValidatedQuery<Pagination>,
+    DbConnection(db): DbConnection,
+    OrgBoundEntityFromPathId(tracker): OrgBoundEntityFromPathId<vehicle_tracker::Entity>,
+) -> Result<Json<PaginationResult<position_filter_decision::Model>>, (StatusCode, SimpleError)> {
+    let db_query = position_filter_decision::Entity::find()
+        .filter(position_filter_decision::Column::VehicleTrackerId.eq(tracker.id))
+        .order_by_desc(position_filter_decision::Column::Id)
+        .paginate(&db, pagination.page_size);
+
+    let result =
+        database::helpers::paginated_query_to_pagination_result(db_query, pagination).await?;
+
+    Ok(Json(result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::location_page_query;
+    use crate::modules::{common::dto::AscOrDescOrder, tracker::dto::GetTrackerPositionsDto};
+    use http::StatusCode;
+
+    #[test]
+    fn invalid_cursors_are_bad_requests() {
+        let dto = |cursor: Option<&str>| GetTrackerPositionsDto {
+            after: None,
+            before: None,
+            limit: None,
+            order: AscOrDescOrder::Desc,
+            cursor: cursor.map(String::from),
+            simplify: None,
+            bucket: None,
+        };
+
+        let err = location_page_query(1, &dto(Some("tampered"))).unwrap_err();
+        assert_eq!(err.0, StatusCode::BAD_REQUEST);
+
+        assert!(location_page_query(1, &dto(None)).is_ok());
+    }
+}
@@ -1,5 +1,9 @@
-use super::decoder::h02;
-use crate::{modules::globals::TRACKER_ID_CACHE, rabbitmq::Rmq};
+use super::batch::{BatchWriter, PositionWriter};
+use super::decoder;
+use super::filter::FilterDecision;
+use super::registry::{EventContext, EventRegistry};
+use crate::{config::app_config, modules::globals::TRACKER_ID_CACHE, rabbitmq::Rmq};
+use futures_util::FutureExt;
 use lapin::{message::Delivery, options::BasicConsumeOptions, types::FieldTable};
 use sea_orm::DatabaseConnection;
 use socketioxide::SocketIo;
@@ -7,10 +11,17 @@ use std::{sync::Arc, time::Duration};
 use tracing::{error, warn, Instrument};
 
 /// handler for tracker events recieved from the decoder microservice through a
-/// RabbitMQ delivery, this mainly passes the message to the appropriate function
-/// based on the `protocol`, `event_type` and the `imei` on the delivery routing key
+/// RabbitMQ delivery, this mainly passes the message to the handlers registered
+/// for the `protocol` and `event_type` on the delivery routing key
 #[tracing::instrument(skip_all)]
-async fn on_tracker_event(delivery: Delivery, db: &DatabaseConnection, socket: &SocketIo) {
+async fn on_tracker_event(
+    delivery: Delivery,
+    registry: &EventRegistry,
+    db: &DatabaseConnection,
+    socket: &SocketIo,
+    positions: &PositionWriter,
+    filter_decisions: &BatchWriter<FilterDecision>,
+) {
     let routing_key = delivery.routing_key.to_string();
 
     // tracking events routing keys have the following pattern
@@ -30,17 +41,8 @@ async fn on_tracker_event(delivery: Delivery, db: &DatabaseConnection, socket: &
         return;
     }
 
-    // it might seem dumb to rejoin protocol and event_type
-    // again but it was needed to separate by '.' in three parts
-    // to check if the routing key was valid
-    let protocol_and_event = protocol.to_owned() + "." + event_type;
-
-    // for now we only support the h02 protocol and the location message
-    // when this grows we should move this to a decoder struct that maps
-    // the combination of protocol and event_type to a struct that implements
-    // serializable
-    if protocol_and_event != "h02.location" {
-        error!("unsupported protocol and/or event {protocol_and_event}");
+    if !registry.is_supported(protocol, event_type) {
+        error!("unsupported protocol and/or event {protocol}.{event_type}");
         return;
     }
 
@@ -56,7 +58,19 @@ async fn on_tracker_event(delivery: Delivery, db: &DatabaseConnection, socket: &
         }
     };
 
-    let _ = h02::handle_location(&delivery, socket, tracker_id, db).await;
+    let ctx = EventContext {
+        protocol: protocol.to_string(),
+        event_type: event_type.to_string(),
+        imei: imei.to_string(),
+        tracker_id,
+        socket: socket.clone(),
+        db: db.clone(),
+        positions: positions.clone(),
+        filter_decisions: filter_decisions.clone(),
+        position_accepted: Arc::default(),
+    };
+
+    registry.dispatch(delivery.data.as_slice(), ctx).await;
 }
 
 /// Starts a RabbitMQ consumer that listens for any tracker event
@@ -65,6 +79,11 @@ async fn on_tracker_event(delivery: Delivery, db: &DatabaseConnection, socket: &
 /// this is supossed to run for the entirety of the program, so
 /// it attempts to reconnect infinitely if the connection ends and
 /// thus so does the consumer.
+///
+/// positions are not stored as they are recieved, they are queued to be stored
+/// in batches by a `PositionWriter`, see `AppConfig::position_batch_size`, as
+/// are the decisions of the position filter, geofences and trips are updated
+/// once each batch of positions is stored
 pub fn start_positions_consumer(rmq: Arc<Rmq>, socket_io: SocketIo, db: DatabaseConnection) {
     tokio::task::spawn(async move {
         // Important: use automatic acknowledgement mode because we will recieve a
@@ -74,8 +93,27 @@ pub fn start_positions_consumer(rmq: Arc<Rmq>, socket_io: SocketIo, db: Database
             ..Default::default()
         };
 
+        let registry = decoder::event_registry();
+
+        let batch_size = app_config().position_batch_size;
+        let batch_interval = Duration::from_millis(app_config().position_batch_interval_ms);
+
+        let positions =
+            PositionWriter::start_with_handler(db.clone(), batch_size, batch_interval, {
+                let (db, socket_io) = (db.clone(), socket_io.clone());
+
+                Box::new(move |positions| {
+                    let (db, socket_io) = (db.clone(), socket_io.clone());
+                    decoder::position::handle_stored_positions(positions, db, socket_io).boxed()
+                })
+            });
+        let filter_decisions = BatchWriter::start(db.clone(), batch_size, batch_interval);
+
+        let registry_ref = &registry;
         let db_ref = &db;
         let socket_ref = &socket_io;
+        let positions_ref = &positions;
+        let filter_decisions_ref = &filter_decisions;
 
         loop {
             tokio::time::sleep(Duration::from_secs(5)).await;
@@ -91,9 +129,16 @@ pub fn start_positions_consumer(rmq: Arc<Rmq>, socket_io: SocketIo, db: Database
                         let (span, delivery) =
                             shared::tracer::correlate_trace_from_delivery(delivery);
 
-                        on_tracker_event(delivery, db_ref, socket_ref)
-                            .instrument(span)
-                            .await
+                        on_tracker_event(
+                            delivery,
+                            registry_ref,
+                            db_ref,
+                            socket_ref,
+                            positions_ref,
+                            filter_decisions_ref,
+                        )
+                        .instrument(span)
+                        .await
                     },
                 )
                 .await;
@@ -0,0 +1,294 @@
+use super::dto::PositionDto;
+use futures_util::future::BoxFuture;
+use geozero::wkb;
+use sea_orm::DatabaseConnection;
+use sqlx::{Postgres, QueryBuilder};
+use std::{collections::VecDeque, time::Duration};
+use tokio::{sync::mpsc, time::Instant};
+use tracing::{error, warn};
+
+/// Maximum amount of bind parameters of a postgres statement
+const MAX_PARAMS: usize = 65535;
+
+/// Attempts to store a batch when the database cannot be reached
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Time to wait before retrying a batch, multiplied by the amount of failed attempts
+const RETRY_DELAY: Duration = Duration::from_millis(500);
+
+/// Amount of stored batches waiting to be handled before the writer waits for the handler
+const STORED_QUEUE_SIZE: usize = 64;
+
+/// Handles the rows of each batch once it is stored, see `BatchWriter::start_with_handler`
+pub type StoredHandler<T> = Box<dyn Fn(Vec<T>) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// A row stored in batches by a `BatchWriter`
+pub trait BatchRow: Send + Sync + Sized + 'static {
+    /// name of the rows on logs
+    const NAME: &'static str;
+
+    /// amount of bind parameters of each row, which limits the size of the batches
+    const PARAMS: usize;
+
+    /// The insert of the rows with a single statement, on the order of `rows`
+    fn insert_query(rows: &[Self]) -> QueryBuilder<'_, Postgres>;
+
+    /// The row on logs, such as when it is rejected by the database
+    fn describe(&self) -> String;
+}
+
+/// Handle to queue rows to be stored by the `BatchWriter` task, cloning
+/// it is cheap and every clone queues to the same task
+pub struct BatchWriter<T> {
+    tx: mpsc::Sender<T>,
+}
+
+/// Stores the positions of the trackers, see `AppConfig::position_batch_size`,
+/// the stored positions are handled by `decoder::position::handle_stored_positions`
+pub type PositionWriter = BatchWriter<PositionDto>;
+
+impl<T> Clone for BatchWriter<T> {
+    fn clone(&self) -> Self {
+        Self {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<T: BatchRow> BatchWriter<T> {
+    /// Starts the task that stores the queued rows, rows are written with a single
+    /// multi row insert once `batch_size` rows are queued or `interval` has passed
+    /// since the first row of the batch was queued, whatever happens first.
+    ///
+    /// batches are written one at a time in the order rows were queued, so the
+    /// positions of each tracker are stored in the order they were received, this
+    /// also means queueing waits for the writes once the queue is full.
+    pub fn start(db: DatabaseConnection, batch_size: usize, interval: Duration) -> BatchWriter<T> {
+        Self::spawn(db, batch_size, interval, None)
+    }
+
+    /// Starts the writer like `start`, passing the rows of each batch to `handler` once the
+    /// batch is stored, work that needs the rows stored or is slow to do for each row can be
+    /// done there, as it does not delay queueing rows nor storing the next batches.
+    ///
+    /// batches are handled one at a time on their own task, in the order they are stored,
+    /// rows dropped by the database are handled as well.
+    pub fn start_with_handler(
+        db: DatabaseConnection,
+        batch_size: usize,
+        interval: Duration,
+        handler: StoredHandler<T>,
+    ) -> BatchWriter<T> {
+        Self::spawn(db, batch_size, interval, Some(handler))
+    }
+
+    fn spawn(
+        db: DatabaseConnection,
+        batch_size: usize,
+        interval: Duration,
+        handler: Option<StoredHandler<T>>,
+    ) -> BatchWriter<T> {
+        let batch_size = batch_size.clamp(1, MAX_PARAMS / T::PARAMS);
+
+        let (tx, rx) = mpsc::channel(batch_size * 4);
+
+        let stored_tx = handler.map(|handler| {
+            let (stored_tx, stored_rx) = mpsc::channel(STORED_QUEUE_SIZE);
+            tokio::task::spawn(handle_batches(stored_rx, handler));
+            stored_tx
+        });
+
+        tokio::task::spawn(write_batches(db, rx, stored_tx, batch_size, interval));
+
+        BatchWriter { tx }
+    }
+
+    /// Queues the row to be stored on the next batch
+    pub async fn write(&self, row: T) {
+        if let Err(e) = self.tx.send(row).await {
+            error!(
+                "failed to queue {}, {} writer stopped",
+                e.0.describe(),
+                T::NAME
+            );
+        }
+    }
+}
+
+async fn write_batches<T: BatchRow>(
+    db: DatabaseConnection,
+    mut rx: mpsc::Receiver<T>,
+    stored_tx: Option<mpsc::Sender<Vec<T>>>,
+    batch_size: usize,
+    interval: Duration,
+) {
+    let mut batch = Vec::with_capacity(batch_size);
+
+    while let Some(row) = rx.recv().await {
+        let deadline = Instant::now() + interval;
+
+        batch.push(row);
+
+        while batch.len() < batch_size {
+            match tokio::time::timeout_at(deadline, rx.recv()).await {
+                Ok(Some(row)) => batch.push(row),
+                Ok(None) | Err(_) => break,
+            }
+        }
+
+        store_batch(&db, &batch).await;
+
+        match &stored_tx {
+            Some(stored_tx) => {
+                let stored = std::mem::replace(&mut batch, Vec::with_capacity(batch_size));
+
+                if stored_tx.send(stored).await.is_err() {
+                    error!("failed to handle stored {}, handler stopped", T::NAME);
+                }
+            }
+            None => batch.clear(),
+        }
+    }
+}
+
+async fn handle_batches<T: BatchRow>(mut rx: mpsc::Receiver<Vec<T>>, handler: StoredHandler<T>) {
+    while let Some(batch) = rx.recv().await {
+        handler(batch).await;
+    }
+}
+
+/// Stores the batch, retrying it if the database cannot be reached, and splitting it in halves
+/// if the database rejects it, so a invalid row is dropped alone instead of with its batch,
+/// the parts are stored in order, to keep the order of the positions of each tracker
+async fn store_batch<T: BatchRow>(db: &DatabaseConnection, batch: &[T]) {
+    let mut pending = VecDeque::from([batch]);
+
+    while let Some(rows) = pending.pop_front() {
+        let mut attempt = 1;
+
+        loop {
+            let err = match insert_rows(db, rows).await {
+                Ok(()) => break,
+                Err(err) => err,
+            };
+
+            match err {
+                sqlx::Error::Database(_) if rows.len() > 1 => {
+                    let (first, second) = rows.split_at(rows.len() / 2);
+
+                    pending.push_front(second);
+                    pending.push_front(first);
+                    break;
+                }
+                sqlx::Error::Database(e) => {
+                    error!(
+                        "dropped {} rejected by the database: {e}",
+                        rows[0].describe()
+                    );
+                    break;
+                }
+                e if attempt < MAX_ATTEMPTS => {
+                    warn!(
+                        "failed to store {} {} on attempt {attempt}, retrying: {e}",
+                        rows.len(),
+                        T::NAME
+                    );
+
+                    tokio::time::sleep(RETRY_DELAY * attempt).await;
+                    attempt += 1;
+                }
+                e => {
+                    error!(
+                        "dropped {} {} after {attempt} attempts: {e}",
+                        rows.len(),
+                        T::NAME
+                    );
+                    break;
+                }
+            }
+        }
+    }
+}
+
+/// Stores the rows with a single insert
+async fn insert_rows<T: BatchRow>(db: &DatabaseConnection, rows: &[T]) -> Result<(), sqlx::Error> {
+    if rows.is_empty() {
+        return Ok(());
+    }
+
+    T::insert_query(rows)
+        .build()
+        .execute(db.get_postgres_connection_pool())
+        .await?;
+
+    Ok(())
+}
+
+/// Positions are inserted in the order of the batch, so the last location trigger sees the
+/// positions of each tracker in that order, positions already stored are ignored, such as
+/// positions sent again by the tracker or positions of a batch that was stored although its
+/// insert seemed to fail
+impl BatchRow for PositionDto {
+    const NAME: &'static str = "positions";
+
+    const PARAMS: usize = 7;
+
+    fn insert_query(positions: &[Self]) -> QueryBuilder<'_, Postgres> {
+        let mut query: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO vehicle_tracker_location (time, vehicle_tracker_id, point, speed, heading, ignition, status) ",
+        );
+
+        query.push_values(positions, |mut row, position| {
+            let point: geo_types::Geometry<f64> =
+                geo_types::Point::new(position.lat, position.lng).into();
+
+            row.push_bind(position.timestamp)
+                .push_bind(position.tracker_id)
+                .push("ST_SetSRID(")
+                .push_bind_unseparated(wkb::Encode(point))
+                .push_unseparated(", 4326)")
+                .push_bind(position.telemetry.speed)
+                .push_bind(position.telemetry.heading)
+                .push_bind(position.telemetry.ignition)
+                .push_bind(position.telemetry.status.clone());
+        });
+
+        query.push(" ON CONFLICT (time, vehicle_tracker_id) DO NOTHING");
+        query
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "position of tracker {} at {}",
+            self.tracker_id, self.timestamp
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BatchRow;
+    use crate::modules::tracking::dto::{PositionDto, TelemetryDto};
+    use chrono::Utc;
+
+    #[test]
+    fn ignores_positions_already_stored() {
+        let position = PositionDto {
+            lat: -23.5,
+            lng: -46.6,
+            timestamp: Utc::now(),
+            tracker_id: 1,
+            telemetry: TelemetryDto::default(),
+        };
+
+        let positions = [position.clone(), position];
+        let query = PositionDto::insert_query(&positions);
+
+        assert_eq!(
+            query.sql(),
+            "INSERT INTO vehicle_tracker_location (time, vehicle_tracker_id, point, speed, heading, ignition, status) \
+             VALUES ($1, $2, ST_SetSRID($3, 4326), $4, $5, $6, $7), ($8, $9, ST_SetSRID($10, 4326), $11, $12, $13, $14) \
+             ON CONFLICT (time, vehicle_tracker_id) DO NOTHING"
+        );
+    }
+}
@@ -0,0 +1,145 @@
+use super::super::{
+    filter,
+    registry::{EventContext, EventRegistry},
+};
+use chrono::{DateTime, Utc};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, QuerySelect, Set};
+use shared::{
+    constants::TrackerAlarmType,
+    dto::decoder::h02,
+    entity::{tracker_alarm, vehicle_tracker, vehicle_tracker_last_location},
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::OnceLock,
+};
+use tokio::sync::Mutex;
+use tracing::error;
+
+/// The alarms active on the last status sent by each tracker, to detect when
+/// a alarm is raised instead of storing it on every event while it is active
+static ACTIVE_ALARMS: OnceLock<Mutex<HashMap<i32, HashSet<TrackerAlarmType>>>> = OnceLock::new();
+
+fn active_alarms() -> &'static Mutex<HashMap<i32, HashSet<TrackerAlarmType>>> {
+    ACTIVE_ALARMS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Registers the alarm detection for the protocols whose events carry status flags,
+/// this must be registered before the protocol location handlers, so the status of
+/// the last location is read before it is replaced, see `previously_active_alarms`,
+/// the status of locations rejected by the position filter is ignored
+pub fn register(registry: &mut EventRegistry) {
+    registry
+        .on("h02", "location", |msg: h02::LocationMsg, ctx| async move {
+            let position = super::h02::position_of(&msg, ctx.tracker_id);
+
+            if filter::is_accepted(&position, &ctx).await {
+                handle_status(msg.status, msg.timestamp, ctx).await;
+            }
+        })
+        .on("h02", "lbs", |msg: h02::LbsMsg, ctx| {
+            handle_status(msg.status, msg.timestamp, ctx)
+        })
+        .on("h02", "link_status", |msg: h02::LinkMsg, ctx| {
+            handle_status(msg.status, msg.timestamp, ctx)
+        });
+}
+
+/// The alarms active on the status of the last location of the tracker, used when the
+/// alarms of the tracker are not cached, such as after the API restarts, so alarms that
+/// were already active are not raised again
+async fn previously_active_alarms(
+    tracker_id: i32,
+    db: &DatabaseConnection,
+) -> HashSet<TrackerAlarmType> {
+    let last_status = vehicle_tracker_last_location::Entity::find_by_id(tracker_id)
+        .select_only()
+        .column(vehicle_tracker_last_location::Column::Status)
+        .into_tuple::<Option<serde_json::Value>>()
+        .one(db)
+        .await;
+
+    match last_status {
+        Ok(Some(Some(status))) => serde_json::from_value::<h02::Status>(status)
+            .map(|status| status.alarms().into_iter().collect())
+            .unwrap_or_default(),
+        Ok(_) => HashSet::new(),
+        Err(e) => {
+            error!("failed to get last status of tracker {tracker_id}: {e}");
+            HashSet::new()
+        }
+    }
+}
+
+/// Stores and emits the alarms raised on the tracker status, that is, the alarms
+/// active on the status that were not active on the previous status of the tracker
+#[tracing::instrument(skip_all)]
+pub async fn handle_status(status: h02::Status, time: DateTime<Utc>, ctx: EventContext) {
+    let active: HashSet<TrackerAlarmType> = status.alarms().into_iter().collect();
+
+    let is_cached = active_alarms().lock().await.contains_key(&ctx.tracker_id);
+
+    let previously_active = match is_cached {
+        true => None,
+        false => Some(previously_active_alarms(ctx.tracker_id, &ctx.db).await),
+    };
+
+    let raised: Vec<TrackerAlarmType> = {
+        let mut alarms = active_alarms().lock().await;
+
+        let previous = match previously_active {
+            Some(previous) => previous,
+            None => alarms.get(&ctx.tracker_id).cloned().unwrap_or_default(),
+        };
+
+        let raised = active.difference(&previous).copied().collect();
+
+        alarms.insert(ctx.tracker_id, active);
+
+        raised
+    };
+
+    if raised.is_empty() {
+        return;
+    }
+
+    let tracker = match vehicle_tracker::Entity::find_by_id(ctx.tracker_id)
+        .one(&ctx.db)
+        .await
+    {
+        Ok(Some(tracker)) => tracker,
+        Ok(None) => return,
+        Err(e) => {
+            error!("failed to get tracker {}: {e}", ctx.tracker_id);
+            return;
+        }
+    };
+
+    for alarm_type in raised {
+        let alarm = tracker_alarm::ActiveModel {
+            time: Set(time),
+            alarm_type: Set(alarm_type),
+            vehicle_tracker_id: Set(tracker.id),
+            vehicle_id: Set(tracker.vehicle_id),
+            organization_id: Set(tracker.organization_id),
+            ..Default::default()
+        }
+        .insert(&ctx.db)
+        .await;
+
+        match alarm {
+            Ok(alarm) => {
+                let _ = ctx
+                    .socket
+                    .of("/tracking")
+                    .expect("/tracking socket io namespace not available")
+                    .within(tracker.id.to_string())
+                    .emit("alarm", alarm);
+            }
+            Err(e) => error!(
+                "failed to store {alarm_type} alarm of tracker {}: {e}",
+                tracker.id
+            ),
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use super::super::registry::{EventContext, EventRegistry, ANY_PROTOCOL};
+use chrono::Utc;
+use sea_orm::{
+    ActiveValue::Set, ColumnTrait, Condition, DatabaseConnection, DbErr, EntityTrait, QueryFilter,
+    QueryOrder, Select,
+};
+use shared::{
+    constants::TrackerCommandStatus,
+    dto::decoder::command::{CommandFailedMsg, CommandSentMsg},
+    entity::tracker_command,
+};
+use tracing::{error, warn};
+
+/// Registers the handlers of the command events published by the decoder itself, acknowledgements
+/// are sent by the trackers on each protocol format, so they are registered by the protocol modules
+pub fn register(registry: &mut EventRegistry) {
+    registry
+        .on(ANY_PROTOCOL, "command_sent", handle_command_sent)
+        .on(ANY_PROTOCOL, "command_failed", handle_command_failed);
+}
+
+/// Updates the command of the tracker with the `command_id` if its status is one of `from`
+async fn update_command(
+    db: &DatabaseConnection,
+    tracker_id: i32,
+    command_id: i32,
+    from: Vec<TrackerCommandStatus>,
+    changes: tracker_command::ActiveModel,
+) -> Result<u64, DbErr> {
+    let result = tracker_command::Entity::update_many()
+        .set(changes)
+        .filter(tracker_command::Column::Id.eq(command_id))
+        .filter(tracker_command::Column::VehicleTrackerId.eq(tracker_id))
+        .filter(tracker_command::Column::Status.is_in(from))
+        .exec(db)
+        .await?;
+
+    Ok(result.rows_affected)
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn handle_command_sent(msg: CommandSentMsg, ctx: EventContext) {
+    let changes = tracker_command::ActiveModel {
+        status: Set(TrackerCommandStatus::Sent),
+        sent_at: Set(Some(Utc::now())),
+        ..Default::default()
+    };
+
+    let from = vec![TrackerCommandStatus::Pending];
+
+    if let Err(e) = update_command(&ctx.db, ctx.tracker_id, msg.id, from, changes).await {
+        error!("failed to set command {} as sent: {e}", msg.id);
+    }
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn handle_command_failed(msg: CommandFailedMsg, ctx: EventContext) {
+    let changes = tracker_command::ActiveModel {
+        status: Set(TrackerCommandStatus::Failed),
+        failure_reason: Set(Some(msg.reason)),
+        ..Default::default()
+    };
+
+    let from = vec![TrackerCommandStatus::Pending];
+
+    if let Err(e) = update_command(&ctx.db, ctx.tracker_id, msg.id, from, changes).await {
+        error!("failed to set command {} as failed: {e}", msg.id);
+    }
+}
+
+/// Finds the oldest command sent to the tracker that was not acknowledged yet, commands sent
+/// but expired before the tracker answered are included, otherwise a late acknowledgement
+/// would be matched to a newer command
+fn oldest_unacknowledged_command(tracker_id: i32) -> Select<tracker_command::Entity> {
+    tracker_command::Entity::find()
+        .filter(tracker_command::Column::VehicleTrackerId.eq(tracker_id))
+        .filter(
+            Condition::any()
+                .add(tracker_command::Column::Status.eq(TrackerCommandStatus::Sent))
+                .add(
+                    Condition::all()
+                        .add(tracker_command::Column::Status.eq(TrackerCommandStatus::Expired))
+                        .add(tracker_command::Column::SentAt.is_not_null()),
+                ),
+        )
+        .order_by_asc(tracker_command::Column::Id)
+}
+
+/// Sets a command as acknowledged by the tracker, GT06 trackers echo back the `command_id`
+/// while other protocols do not identify the command, in that case the oldest sent
+/// command of the tracker is the one acknowledged, since trackers answer in order.
+///
+/// expired commands can still be acknowledged, as the tracker did execute them.
+#[tracing::instrument(skip_all)]
+pub async fn handle_command_ack(command_id: Option<i32>, response: String, ctx: EventContext) {
+    let EventContext { tracker_id, db, .. } = ctx;
+
+    let command_id = match command_id {
+        Some(id) => id,
+        None => {
+            let oldest_sent_command = oldest_unacknowledged_command(tracker_id).one(&db).await;
+
+            match oldest_sent_command {
+                Ok(Some(command)) => command.id,
+                Ok(None) => {
+                    warn!("acknowledgement of unknown command: {response}");
+                    return;
+                }
+                Err(e) => {
+                    error!("failed to find acknowledged command: {e}");
+                    return;
+                }
+            }
+        }
+    };
+
+    let changes = tracker_command::ActiveModel {
+        status: Set(TrackerCommandStatus::Acknowledged),
+        acknowledged_at: Set(Some(Utc::now())),
+        response: Set(Some(response)),
+        ..Default::default()
+    };
+
+    let from = vec![
+        TrackerCommandStatus::Pending,
+        TrackerCommandStatus::Sent,
+        TrackerCommandStatus::Expired,
+    ];
+
+    if let Err(e) = update_command(&db, tracker_id, command_id, from, changes).await {
+        error!("failed to set command {command_id} as acknowledged: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::oldest_unacknowledged_command;
+    use sea_orm::{DbBackend, QueryTrait};
+
+    #[test]
+    fn matches_acknowledgements_to_the_oldest_sent_or_expired_command() {
+        let sql = oldest_unacknowledged_command(1)
+            .build(DbBackend::Postgres)
+            .to_string();
+
+        assert!(
+            sql.contains(concat!(
+                r#"("tracker_command"."status" = (CAST('SENT' AS tracker_command_status)) OR "#,
+                r#"("tracker_command"."status" = (CAST('EXPIRED' AS tracker_command_status)) "#,
+                r#"AND "tracker_command"."sent_at" IS NOT NULL))"#
+            )),
+            "{sql}"
+        );
+        assert!(
+            sql.ends_with(r#"ORDER BY "tracker_command"."id" ASC"#),
+            "{sql}"
+        );
+    }
+}
@@ -0,0 +1,94 @@
+use super::super::dto::PositionDto;
+use sea_orm::{DatabaseConnection, DbBackend, EntityTrait, Statement};
+use shared::entity::geofence_event;
+use socketioxide::SocketIo;
+use tracing::error;
+
+/// Circles are checked by the geodesic distance to their center, since their radius
+/// is in meters and the geofence geometries are in longitude and latitude degrees.
+///
+/// `checked_at` is set on every newer position, even without a transition, so
+/// a position older than the last one checked never flips `inside`
+const GEOFENCE_TRANSITIONS_QUERY: &str = r#"
+WITH "position" AS (
+    SELECT ST_SetSRID(ST_MakePoint($1, $2), 4326) AS "point"
+), "checked" AS (
+    SELECT
+        vg."vehicle_id",
+        vg."geofence_id",
+        vg."inside" AS "was_inside",
+        g."organization_id",
+        CASE g."shape"
+            WHEN 'CIRCLE' THEN ST_DWithin(g."geometry"::geography, p."point"::geography, g."radius")
+            ELSE ST_Contains(g."geometry", p."point")
+        END AS "is_inside"
+    FROM "vehicle_tracker" vt
+    INNER JOIN "vehicle_geofence" vg ON vg."vehicle_id" = vt."vehicle_id"
+    INNER JOIN "geofence" g ON g."id" = vg."geofence_id"
+    CROSS JOIN "position" p
+    WHERE vt."id" = $3
+), "changed" AS (
+    UPDATE "vehicle_geofence" vg
+    SET "inside" = c."is_inside", "checked_at" = $4
+    FROM "checked" c
+    WHERE vg."vehicle_id" = c."vehicle_id"
+    AND vg."geofence_id" = c."geofence_id"
+    AND (vg."checked_at" IS NULL OR $4 > vg."checked_at")
+    RETURNING c.*
+)
+INSERT INTO "geofence_event" (
+    "time", "event_type", "geofence_id", "vehicle_id", "vehicle_tracker_id", "organization_id"
+)
+SELECT
+    $4,
+    (CASE WHEN "is_inside" THEN 'ENTER' ELSE 'EXIT' END)::geofence_event_type,
+    "geofence_id",
+    "vehicle_id",
+    $3,
+    "organization_id"
+FROM "changed"
+WHERE "was_inside" IS DISTINCT FROM "is_inside"
+AND "was_inside" IS NOT NULL
+RETURNING *
+"#;
+
+/// Updates if the vehicle of the tracker is inside each geofence it is monitored on, storing
+/// and emitting a event whenever it enters or leaves one, the first position checked against
+/// a geofence only sets if the vehicle is inside it, as there is no previous position
+#[tracing::instrument(skip_all)]
+pub async fn check_geofences(position: &PositionDto, db: &DatabaseConnection, socket: &SocketIo) {
+    let statement = Statement::from_sql_and_values(
+        DbBackend::Postgres,
+        GEOFENCE_TRANSITIONS_QUERY,
+        [
+            position.lng.into(),
+            position.lat.into(),
+            position.tracker_id.into(),
+            position.timestamp.into(),
+        ],
+    );
+
+    let events = geofence_event::Entity::find()
+        .from_raw_sql(statement)
+        .all(db)
+        .await;
+
+    let events = match events {
+        Ok(events) => events,
+        Err(e) => {
+            error!(
+                "failed to check geofences of tracker {}: {e}",
+                position.tracker_id
+            );
+            return;
+        }
+    };
+
+    for event in events {
+        let _ = socket
+            .of("/tracking")
+            .expect("/tracking socket io namespace not available")
+            .within(position.tracker_id.to_string())
+            .emit("geofence", event);
+    }
+}
@@ -0,0 +1,40 @@
+use super::super::registry::{EventContext, EventRegistry};
+use crate::modules::tracking::dto::{PositionDto, TelemetryDto};
+use shared::dto::decoder::gt06::{CommandAckMsg, LocationMsg};
+use tracing::warn;
+
+pub fn register(registry: &mut EventRegistry) {
+    registry.on("gt06", "location", handle_location).on(
+        "gt06",
+        "command_ack",
+        |msg: CommandAckMsg, ctx| {
+            super::command::handle_command_ack(Some(msg.server_flag as i32), msg.content, ctx)
+        },
+    );
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn handle_location(decoded: LocationMsg, ctx: EventContext) {
+    // without a GPS fix GT06 trackers send their last known coordinates
+    if !decoded.positioned {
+        warn!("ignoring GT06 location without GPS fix");
+        return;
+    }
+
+    let telemetry = TelemetryDto {
+        speed: Some(decoded.speed),
+        heading: Some(decoded.direction),
+        ignition: decoded.acc,
+        status: None,
+    };
+
+    let position = PositionDto {
+        lat: decoded.lat,
+        lng: decoded.lng,
+        timestamp: decoded.timestamp,
+        tracker_id: ctx.tracker_id,
+        telemetry,
+    };
+
+    super::position::handle_position(position, ctx).await;
+}
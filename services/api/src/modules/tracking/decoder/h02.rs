@@ -1,46 +1,39 @@
-use super::super::utils;
-use crate::modules::tracking::dto::PositionDto;
-use lapin::message::Delivery;
-use sea_orm::DatabaseConnection;
-use socketioxide::SocketIo;
-use tracing::error;
+use super::super::registry::{EventContext, EventRegistry};
+use crate::modules::tracking::dto::{PositionDto, TelemetryDto};
+use shared::dto::decoder::h02::{CommandAckMsg, LocationMsg};
 
-#[tracing::instrument(skip_all)]
-pub async fn handle_location(
-    delivery: &Delivery,
-    socket: &SocketIo,
-    tracker_id: i32,
-    db: &DatabaseConnection,
-) {
-    let parse_result: Result<shared::dto::decoder::h02::LocationMsg, serde_json::Error> =
-        serde_json::from_slice(delivery.data.as_slice());
-
-    match parse_result {
-        Ok(decoded) => {
-            let _ = utils::insert_vehicle_tracker_location(
-                db,
-                decoded.timestamp,
-                tracker_id,
-                decoded.lat,
-                decoded.lng,
-            )
-            .await;
+pub fn register(registry: &mut EventRegistry) {
+    registry.on("h02", "location", handle_location).on(
+        "h02",
+        "command_ack",
+        |msg: CommandAckMsg, ctx| {
+            let response = [vec![msg.command], msg.args].concat().join(",");
+            super::command::handle_command_ack(None, response, ctx)
+        },
+    );
+}
 
-            let position = PositionDto {
-                lat: decoded.lat,
-                lng: decoded.lng,
-                timestamp: decoded.timestamp,
-                tracker_id,
-            };
+/// The position of a h02 location
+pub fn position_of(decoded: &LocationMsg, tracker_id: i32) -> PositionDto {
+    let telemetry = TelemetryDto {
+        speed: Some(decoded.speed),
+        heading: Some(decoded.direction),
+        ignition: Some(decoded.status.acc),
+        status: serde_json::to_value(&decoded.status).ok(),
+    };
 
-            let _ = socket
-                .of("/tracking")
-                .expect("/tracking socket io namespace not available")
-                .within(tracker_id.to_string())
-                .emit("position", position);
-        }
-        Err(e) => {
-            error!("failed to parse H02 location: {e}");
-        }
+    PositionDto {
+        lat: decoded.lat,
+        lng: decoded.lng,
+        timestamp: decoded.timestamp,
+        tracker_id,
+        telemetry,
     }
 }
+
+#[tracing::instrument(skip_all)]
+pub async fn handle_location(decoded: LocationMsg, ctx: EventContext) {
+    let position = position_of(&decoded, ctx.tracker_id);
+
+    super::position::handle_position(position, ctx).await;
+}
@@ -1 +1,25 @@
+use super::registry::EventRegistry;
+
+pub mod alarm;
+pub mod command;
+pub mod geofence;
+pub mod gt06;
 pub mod h02;
+pub mod position;
+pub mod status;
+pub mod teltonika;
+
+/// Registry with the handlers of every tracker event supported by the API
+pub fn event_registry() -> EventRegistry {
+    let mut registry = EventRegistry::new();
+
+    // before the protocol handlers, so it runs before the location is stored
+    alarm::register(&mut registry);
+    h02::register(&mut registry);
+    gt06::register(&mut registry);
+    teltonika::register(&mut registry);
+    command::register(&mut registry);
+    status::register(&mut registry);
+
+    registry
+}
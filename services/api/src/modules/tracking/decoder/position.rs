@@ -0,0 +1,45 @@
+use super::super::{dto::PositionDto, filter, registry::EventContext, trip};
+use sea_orm::DatabaseConnection;
+use socketioxide::SocketIo;
+use tracing::error;
+
+/// Handles a position decoded from a location of any protocol, emitting it to the users
+/// listening to the tracker and queueing it to be stored, positions rejected by the position
+/// filter are not handled.
+///
+/// the position is emitted as soon as it is recieved, while storing it is only queued,
+/// as positions are stored in batches, see `PositionWriter`
+pub async fn handle_position(position: PositionDto, ctx: EventContext) {
+    if !filter::is_accepted(&position, &ctx).await {
+        return;
+    }
+
+    let _ = ctx
+        .socket
+        .of("/tracking")
+        .expect("/tracking socket io namespace not available")
+        .within(ctx.tracker_id.to_string())
+        .emit("position", &position);
+
+    ctx.positions.write(position).await;
+}
+
+/// Checks the geofences of the tracker vehicle and segments its positions into trips for
+/// each position of a batch stored by the `PositionWriter`, so handling the positions does
+/// not wait for these queries, positions are handled in the order they were stored.
+pub async fn handle_stored_positions(
+    positions: Vec<PositionDto>,
+    db: DatabaseConnection,
+    socket: SocketIo,
+) {
+    for position in positions {
+        super::geofence::check_geofences(&position, &db, &socket).await;
+
+        if let Err(e) = trip::update_trips(&db, &position).await {
+            error!(
+                "failed to update trips of tracker {}: {e}",
+                position.tracker_id
+            );
+        }
+    }
+}
@@ -0,0 +1,178 @@
+use super::super::{
+    filter,
+    registry::{EventContext, EventRegistry, ANY_PROTOCOL},
+};
+use crate::modules::tracking::dto::TrackerStatusDto;
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, Set};
+use serde::de::IgnoredAny;
+use shared::{
+    dto::decoder::session::{ConnectedMsg, DisconnectedMsg},
+    entity::vehicle_tracker,
+};
+use socketioxide::SocketIo;
+use std::{collections::HashMap, sync::OnceLock};
+use tokio::sync::Mutex;
+use tracing::error;
+
+/// Minimum interval between updates of the `last_seen_at` of a tracker, trackers
+/// send many events per minute and the connectivity does not need such precision
+const LAST_SEEN_UPDATE_INTERVAL_SECS: i64 = 30;
+
+/// When the `last_seen_at` of each tracker was last updated by this API instance
+static LAST_SEEN_UPDATES: OnceLock<Mutex<HashMap<i32, DateTime<Utc>>>> = OnceLock::new();
+
+fn last_seen_updates() -> &'static Mutex<HashMap<i32, DateTime<Utc>>> {
+    LAST_SEEN_UPDATES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Registers the connectivity handlers, any location or heartbeat means the tracker was seen,
+/// except locations rejected by the position filter, as their time may not be trusted
+pub fn register(registry: &mut EventRegistry) {
+    registry
+        .on(ANY_PROTOCOL, "location", |_: IgnoredAny, ctx| async move {
+            if !filter::is_rejected(&ctx) {
+                handle_seen(ctx).await;
+            }
+        })
+        .on(ANY_PROTOCOL, "heartbeat", |_: IgnoredAny, ctx| {
+            handle_seen(ctx)
+        })
+        .on(ANY_PROTOCOL, "connected", handle_connected)
+        .on(ANY_PROTOCOL, "disconnected", handle_disconnected);
+}
+
+/// Emits the tracker connectivity to the users listening to the tracker
+pub fn emit_status(socket: &SocketIo, tracker: &vehicle_tracker::Model, now: DateTime<Utc>) {
+    let status = TrackerStatusDto {
+        tracker_id: tracker.id,
+        connectivity: tracker.connectivity(now),
+        last_seen_at: tracker.last_seen_at,
+    };
+
+    let _ = socket
+        .of("/tracking")
+        .expect("/tracking socket io namespace not available")
+        .within(tracker.id.to_string())
+        .emit("status", status);
+}
+
+/// Updates the tracker with `update`, emitting its status if its connectivity changed
+async fn update_tracker(
+    tracker_id: i32,
+    socket: &SocketIo,
+    db: &DatabaseConnection,
+    update: impl FnOnce(&vehicle_tracker::Model, &mut vehicle_tracker::ActiveModel) -> bool,
+) {
+    let now = Utc::now();
+
+    let tracker = match vehicle_tracker::Entity::find_by_id(tracker_id)
+        .one(db)
+        .await
+    {
+        Ok(Some(tracker)) => tracker,
+        Ok(None) => return,
+        Err(e) => {
+            error!("failed to get tracker {tracker_id}: {e}");
+            return;
+        }
+    };
+
+    let previous_connectivity = tracker.connectivity(now);
+
+    let mut active_model: vehicle_tracker::ActiveModel = tracker.clone().into();
+
+    if !update(&tracker, &mut active_model) {
+        return;
+    }
+
+    match active_model.update(db).await {
+        Ok(updated) if updated.connectivity(now) != previous_connectivity => {
+            emit_status(socket, &updated, now)
+        }
+        Ok(_) => {}
+        Err(e) => error!("failed to update tracker {tracker_id} connectivity: {e}"),
+    }
+}
+
+/// Updates when the tracker was last seen, on any event sent by the tracker itself
+#[tracing::instrument(skip_all)]
+pub async fn handle_seen(ctx: EventContext) {
+    let EventContext {
+        tracker_id,
+        socket,
+        db,
+        ..
+    } = ctx;
+
+    let now = Utc::now();
+
+    {
+        let mut updates = last_seen_updates().lock().await;
+
+        let updated_recently = updates.get(&tracker_id).is_some_and(|updated_at| {
+            now - *updated_at < Duration::seconds(LAST_SEEN_UPDATE_INTERVAL_SECS)
+        });
+
+        if updated_recently {
+            return;
+        }
+
+        updates.insert(tracker_id, now);
+    }
+
+    update_tracker(tracker_id, &socket, &db, |_, tracker| {
+        tracker.last_seen_at = Set(Some(now));
+        true
+    })
+    .await;
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn handle_connected(msg: ConnectedMsg, ctx: EventContext) {
+    let EventContext {
+        tracker_id,
+        socket,
+        db,
+        ..
+    } = ctx;
+
+    let now = Utc::now();
+
+    last_seen_updates().lock().await.insert(tracker_id, now);
+
+    update_tracker(tracker_id, &socket, &db, |_, tracker| {
+        tracker.last_seen_at = Set(Some(now));
+        tracker.last_connected_at = Set(Some(msg.connected_at));
+        true
+    })
+    .await;
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn handle_disconnected(msg: DisconnectedMsg, ctx: EventContext) {
+    let EventContext {
+        tracker_id,
+        socket,
+        db,
+        ..
+    } = ctx;
+
+    // so the next event of the tracker updates when it was last seen
+    last_seen_updates().lock().await.remove(&tracker_id);
+
+    update_tracker(tracker_id, &socket, &db, |current, tracker| {
+        // trackers usually open a new connection before the old one is closed,
+        // so the old connection being closed does not mean the tracker is offline
+        let is_stale_connection = current
+            .last_connected_at
+            .is_some_and(|connected_at| connected_at > msg.connected_at);
+
+        if !is_stale_connection {
+            tracker.last_disconnected_at = Set(Some(msg.timestamp));
+        }
+
+        !is_stale_connection
+    })
+    .await;
+}
@@ -0,0 +1,43 @@
+use super::super::registry::{EventContext, EventRegistry};
+use crate::modules::tracking::dto::{PositionDto, TelemetryDto};
+use shared::dto::decoder::teltonika::{CommandAckMsg, IoValue, LocationMsg};
+use tracing::warn;
+
+pub fn register(registry: &mut EventRegistry) {
+    registry.on("teltonika", "location", handle_location).on(
+        "teltonika",
+        "command_ack",
+        |msg: CommandAckMsg, ctx| super::command::handle_command_ack(None, msg.response, ctx),
+    );
+}
+
+#[tracing::instrument(skip_all)]
+pub async fn handle_location(decoded: LocationMsg, ctx: EventContext) {
+    // without a GPS fix teltonika trackers send zeroed or last known coordinates
+    if !decoded.positioned {
+        warn!("ignoring teltonika location without GPS fix");
+        return;
+    }
+
+    let ignition = match decoded.io.get("ignition") {
+        Some(IoValue::Number(value)) => Some(*value != 0),
+        _ => None,
+    };
+
+    let telemetry = TelemetryDto {
+        speed: Some(decoded.speed),
+        heading: Some(decoded.direction),
+        ignition,
+        status: serde_json::to_value(&decoded.io).ok(),
+    };
+
+    let position = PositionDto {
+        lat: decoded.lat,
+        lng: decoded.lng,
+        timestamp: decoded.timestamp,
+        tracker_id: ctx.tracker_id,
+        telemetry,
+    };
+
+    super::position::handle_position(position, ctx).await;
+}
@@ -1,15 +1,48 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
-use utoipa::ToSchema;
-use validator::Validate;
+use shared::constants::TrackerConnectivity;
+use utoipa::{IntoParams, ToSchema};
+use validator::{Validate, ValidationError};
 
-#[derive(Serialize, ToSchema)]
+/// Maximum amount of days of locations that can be exported at once
+const MAX_EXPORT_RANGE_DAYS: i64 = 366;
+
+#[derive(Serialize, ToSchema, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PositionDto {
     pub lat: f64,
     pub lng: f64,
     pub timestamp: DateTime<Utc>,
     pub tracker_id: i32,
+    #[serde(flatten)]
+    pub telemetry: TelemetryDto,
+}
+
+/// Telemetry sent by the tracker along with a location, not all protocols send every field
+#[derive(Serialize, ToSchema, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryDto {
+    /// speed in km/h
+    pub speed: Option<f64>,
+
+    /// direction in degrees, 0 being north and 90 east
+    pub heading: Option<i32>,
+
+    /// if the vehicle ignition (ACC) is on
+    pub ignition: Option<bool>,
+
+    /// protocol specific status, eg: H02 status flags or teltonika IO elements
+    #[schema(value_type = Option<Object>)]
+    pub status: Option<serde_json::Value>,
+}
+
+/// Connectivity of a tracker, emitted when it changes
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackerStatusDto {
+    pub tracker_id: i32,
+    pub connectivity: TrackerConnectivity,
+    pub last_seen_at: Option<DateTime<Utc>>,
 }
 
 /// SocketIO connection payload
@@ -25,3 +58,59 @@ pub struct GetTrackersLastPositionsDto {
     #[validate(length(min = 1, max = 20))]
     pub ids: Vec<i32>,
 }
+
+/// File format of a location history export
+#[derive(Deserialize, ToSchema, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    /// GPX 1.1 track
+    Gpx,
+
+    /// KML document with a placemark per location
+    Kml,
+
+    /// GeoJSON FeatureCollection with a point feature per location
+    Geojson,
+
+    /// GeoJSON feature with a LineString of the locations
+    GeojsonLine,
+
+    /// CSV with a header row
+    Csv,
+}
+
+fn is_valid_export_range(dto: &ExportLocationsDto) -> Result<(), ValidationError> {
+    if dto.to <= dto.from {
+        return Err(ValidationError::new("to must be after from"));
+    }
+
+    if dto.to - dto.from > Duration::days(MAX_EXPORT_RANGE_DAYS) {
+        return Err(ValidationError::new("range must be of at most 366 days"));
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+#[validate(schema(function = "is_valid_export_range"))]
+pub struct ExportLocationsDto {
+    /// Export locations from a timestamp, inclusive
+    pub from: DateTime<Utc>,
+
+    /// Export locations up to a timestamp, exclusive
+    pub to: DateTime<Utc>,
+
+    pub format: ExportFormat,
+
+    /// Simplify the route with a tolerance in meters, skipping locations that
+    /// do not change the route shape by more than it
+    #[validate(range(min = 0.1, max = 10000.0))]
+    pub simplify: Option<f64>,
+
+    /// Average the locations on time buckets of this many seconds,
+    /// exporting one location per bucket
+    #[validate(range(min = 1, max = 86400))]
+    pub bucket: Option<i32>,
+}
@@ -0,0 +1,323 @@
+use super::{
+    dto::{ExportFormat, ExportLocationsDto},
+    utils::{location_history_query, LocationOwner, LocationRow, LocationSampling},
+};
+use axum::{
+    body::Body,
+    response::{IntoResponse, Response},
+};
+use chrono::SecondsFormat;
+use futures_util::StreamExt;
+use http::header;
+use sea_orm::DatabaseConnection;
+use sea_query::{Cond, Expr, Order, PostgresQueryBuilder};
+use sea_query_binder::SqlxBinder;
+use serde_json::json;
+use shared::entity::vehicle_tracker_location;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::error;
+
+/// Amount of formatted locations buffered while the client is not reading the export,
+/// bounding the memory used by a export regardless of how many locations it has
+const EXPORT_BUFFER_SIZE: usize = 256;
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl ExportFormat {
+    fn content_type(&self) -> &'static str {
+        match self {
+            Self::Gpx => "application/gpx+xml",
+            Self::Kml => "application/vnd.google-earth.kml+xml",
+            Self::Geojson | Self::GeojsonLine => "application/geo+json",
+            Self::Csv => "text/csv",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            Self::Gpx => "gpx",
+            Self::Kml => "kml",
+            Self::Geojson | Self::GeojsonLine => "geojson",
+            Self::Csv => "csv",
+        }
+    }
+
+    fn header(&self, name: &str) -> String {
+        match self {
+            Self::Gpx => format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<gpx version=\"1.1\" creator=\"rastercar\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n<trk><name>{}</name><trkseg>\n",
+                escape_xml(name)
+            ),
+            Self::Kml => format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<kml xmlns=\"http://www.opengis.net/kml/2.2\"><Document><name>{}</name>\n",
+                escape_xml(name)
+            ),
+            Self::Geojson => String::from("{\"type\":\"FeatureCollection\",\"features\":[\n"),
+            Self::GeojsonLine => format!(
+                "{{\"type\":\"Feature\",\"properties\":{{\"name\":{}}},\"geometry\":{{\"type\":\"LineString\",\"coordinates\":[\n",
+                json!(name)
+            ),
+            Self::Csv => String::from("time,lat,lng,speed,heading,ignition\n"),
+        }
+    }
+
+    /// The location formatted as a part of the export, `is_first` is needed
+    /// to separate the elements of JSON arrays
+    fn row(&self, row: &LocationRow, lat: f64, lng: f64, is_first: bool) -> String {
+        let time = row.time.to_rfc3339_opts(SecondsFormat::Secs, true);
+        let separator = if is_first { "" } else { "," };
+
+        match self {
+            Self::Gpx => format!("<trkpt lat=\"{lat}\" lon=\"{lng}\"><time>{time}</time></trkpt>\n"),
+            Self::Kml => format!(
+                "<Placemark><TimeStamp><when>{time}</when></TimeStamp><Point><coordinates>{lng},{lat}</coordinates></Point></Placemark>\n"
+            ),
+            Self::Geojson => {
+                let feature = json!({
+                    "type": "Feature",
+                    "geometry": { "type": "Point", "coordinates": [lng, lat] },
+                    "properties": {
+                        "time": time,
+                        "speed": row.speed,
+                        "heading": row.heading,
+                        "ignition": row.ignition,
+                    },
+                });
+
+                format!("{separator}{feature}\n")
+            }
+            Self::GeojsonLine => format!("{separator}[{lng},{lat}]\n"),
+            Self::Csv => {
+                let optional = |value: Option<String>| value.unwrap_or_default();
+
+                format!(
+                    "{time},{lat},{lng},{},{},{}\n",
+                    optional(row.speed.map(|v| v.to_string())),
+                    optional(row.heading.map(|v| v.to_string())),
+                    optional(row.ignition.map(|v| v.to_string())),
+                )
+            }
+        }
+    }
+
+    fn footer(&self) -> &'static str {
+        match self {
+            Self::Gpx => "</trkseg></trk>\n</gpx>\n",
+            Self::Kml => "</Document></kml>\n",
+            Self::Geojson => "]}\n",
+            Self::GeojsonLine => "]}}\n",
+            Self::Csv => "",
+        }
+    }
+}
+
+/// Streams the locations of the `owner` within a time range as a file download, rows
+/// are formatted as they are read from the database, so exports of months of locations
+/// are not loaded into memory, `name` is the name of the track on formats that have it.
+///
+/// since the response starts before every location is read, a database error while
+/// streaming aborts the response instead of returning a error status code.
+pub fn export_locations(
+    db: &DatabaseConnection,
+    owner: LocationOwner,
+    name: String,
+    filename: String,
+    dto: ExportLocationsDto,
+) -> Response {
+    let format = dto.format;
+
+    let range = Cond::all()
+        .add(Expr::col(vehicle_tracker_location::Column::Time).gte(dto.from))
+        .add(Expr::col(vehicle_tracker_location::Column::Time).lt(dto.to));
+
+    let sampling = LocationSampling {
+        simplify: dto.simplify,
+        bucket: dto.bucket,
+    };
+
+    let (q, args) = location_history_query(owner, range, sampling)
+        .order_by(vehicle_tracker_location::Column::Time, Order::Asc)
+        .to_owned()
+        .build_sqlx(PostgresQueryBuilder);
+
+    let pool = db.get_postgres_connection_pool().clone();
+
+    let (tx, rx) = mpsc::channel::<Result<String, sqlx::Error>>(EXPORT_BUFFER_SIZE);
+
+    tokio::spawn(async move {
+        if tx.send(Ok(format.header(&name))).await.is_err() {
+            return;
+        }
+
+        let mut rows = sqlx::query_as_with::<_, LocationRow, _>(&q, args).fetch(&pool);
+        let mut is_first = true;
+
+        while let Some(row) = rows.next().await {
+            let chunk = match row {
+                Ok(row) => match row.lat_lng() {
+                    Some((lat, lng)) => {
+                        let chunk = format.row(&row, lat, lng, is_first);
+                        is_first = false;
+                        Ok(chunk)
+                    }
+                    None => continue,
+                },
+                Err(e) => {
+                    error!("failed to export locations of {owner}: {e}");
+                    Err(e)
+                }
+            };
+
+            let is_err = chunk.is_err();
+
+            // the client stopped reading the export
+            if tx.send(chunk).await.is_err() || is_err {
+                return;
+            }
+        }
+
+        let _ = tx.send(Ok(format.footer().to_string())).await;
+    });
+
+    let headers = [
+        (header::CONTENT_TYPE, format.content_type().to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{filename}.{}\"", format.extension()),
+        ),
+    ];
+
+    (headers, Body::from_stream(ReceiverStream::new(rx))).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::modules::tracking::{dto::ExportFormat, utils::LocationRow};
+    use chrono::{TimeZone, Utc};
+    use geozero::wkb;
+    use serde_json::Value;
+
+    fn row(second: u32, lat: f64, lng: f64) -> LocationRow {
+        let point = geo_types::Point::new(lat, lng).into();
+
+        LocationRow {
+            vehicle_tracker_id: 1,
+            time: Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, second).unwrap(),
+            point: wkb::Decode {
+                geometry: Some(point),
+            },
+            speed: Some(40.5),
+            heading: Some(90),
+            ignition: Some(true),
+            status: None,
+        }
+    }
+
+    fn exported(format: ExportFormat, rows: &[LocationRow]) -> String {
+        let mut export = format.header("ABC-1234 <car>");
+
+        for (i, row) in rows.iter().enumerate() {
+            let (lat, lng) = row.lat_lng().unwrap();
+            export.push_str(&format.row(row, lat, lng, i == 0));
+        }
+
+        export.push_str(format.footer());
+        export
+    }
+
+    fn rows() -> Vec<LocationRow> {
+        vec![row(0, -23.5, -46.6), row(30, -23.4, -46.5)]
+    }
+
+    #[test]
+    fn reads_the_latitude_from_the_x_coordinate() {
+        assert_eq!(row(0, -23.5, -46.6).lat_lng(), Some((-23.5, -46.6)));
+    }
+
+    #[test]
+    fn exports_geojson_feature_collections() {
+        let export: Value =
+            serde_json::from_str(&exported(ExportFormat::Geojson, &rows())).unwrap();
+
+        let features = export["features"].as_array().unwrap();
+
+        assert_eq!(export["type"], "FeatureCollection");
+        assert_eq!(features.len(), 2);
+        assert_eq!(
+            features[0]["geometry"]["coordinates"],
+            serde_json::json!([-46.6, -23.5])
+        );
+        assert_eq!(features[0]["properties"]["time"], "2024-03-01T12:00:00Z");
+        assert_eq!(features[0]["properties"]["speed"], 40.5);
+
+        let empty: Value = serde_json::from_str(&exported(ExportFormat::Geojson, &[])).unwrap();
+
+        assert_eq!(empty["features"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn exports_geojson_line_strings() {
+        let export: Value =
+            serde_json::from_str(&exported(ExportFormat::GeojsonLine, &rows())).unwrap();
+
+        assert_eq!(export["type"], "Feature");
+        assert_eq!(export["properties"]["name"], "ABC-1234 <car>");
+        assert_eq!(export["geometry"]["type"], "LineString");
+        assert_eq!(
+            export["geometry"]["coordinates"],
+            serde_json::json!([[-46.6, -23.5], [-46.5, -23.4]])
+        );
+
+        let empty: Value = serde_json::from_str(&exported(ExportFormat::GeojsonLine, &[])).unwrap();
+
+        assert_eq!(empty["geometry"]["coordinates"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn exports_gpx_tracks() {
+        let export = exported(ExportFormat::Gpx, &rows());
+
+        assert!(export.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<gpx "));
+        assert!(export.contains("<name>ABC-1234 &lt;car&gt;</name>"));
+        assert!(export.contains(
+            "<trkpt lat=\"-23.5\" lon=\"-46.6\"><time>2024-03-01T12:00:00Z</time></trkpt>\n"
+        ));
+        assert!(export.contains(
+            "<trkpt lat=\"-23.4\" lon=\"-46.5\"><time>2024-03-01T12:00:30Z</time></trkpt>\n"
+        ));
+        assert!(export.ends_with("</trkseg></trk>\n</gpx>\n"));
+    }
+
+    #[test]
+    fn exports_kml_placemarks() {
+        let export = exported(ExportFormat::Kml, &rows());
+
+        assert!(export.contains("<name>ABC-1234 &lt;car&gt;</name>"));
+        assert!(export.contains(
+            "<Placemark><TimeStamp><when>2024-03-01T12:00:00Z</when></TimeStamp><Point><coordinates>-46.6,-23.5</coordinates></Point></Placemark>\n"
+        ));
+        assert!(export.ends_with("</Document></kml>\n"));
+    }
+
+    #[test]
+    fn exports_csv_rows() {
+        let mut rows = rows();
+        rows[1].speed = None;
+        rows[1].heading = None;
+        rows[1].ignition = None;
+
+        assert_eq!(
+            exported(ExportFormat::Csv, &rows),
+            "time,lat,lng,speed,heading,ignition\n\
+             2024-03-01T12:00:00Z,-23.5,-46.6,40.5,90,true\n\
+             2024-03-01T12:00:30Z,-23.4,-46.5,,,\n"
+        );
+    }
+}
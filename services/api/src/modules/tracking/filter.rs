@@ -0,0 +1,465 @@
+use super::{batch::BatchRow, dto::PositionDto, registry::EventContext, utils::distance_in_meters};
+use crate::config::{app_config, AppConfig};
+use chrono::{DateTime, Utc};
+use sea_orm::DatabaseConnection;
+use shared::constants::PositionFilterReason;
+use sqlx::{Postgres, QueryBuilder};
+use std::{collections::HashMap, sync::OnceLock};
+use tokio::sync::Mutex;
+use tracing::{debug, error, warn};
+
+/// Amount of consecutive positions of a tracker rejected for their speed after which
+/// the position is accepted anyway, as it is more likely that the previous position
+/// was the implausible one, such as the first position after the API restarted
+const MAX_CONSECUTIVE_SPEED_REJECTIONS: u32 = 3;
+
+/// The last position of a tracker that passed the filter, the
+/// position the next position of the tracker is checked against
+#[derive(Clone, Copy)]
+struct Fix {
+    lat: f64,
+    lng: f64,
+    timestamp: DateTime<Utc>,
+    speed_rejections: u32,
+}
+
+static LAST_FIXES: OnceLock<Mutex<HashMap<i32, Fix>>> = OnceLock::new();
+
+fn last_fixes() -> &'static Mutex<HashMap<i32, Fix>> {
+    LAST_FIXES.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Limits of the checks of the filter, a limit of 0 disables its check
+#[derive(Clone, Copy)]
+struct Limits {
+    /// km/h
+    max_speed: f64,
+    /// meters
+    min_parked_distance: f64,
+    /// seconds
+    max_clock_skew: f64,
+}
+
+impl From<&AppConfig> for Limits {
+    fn from(config: &AppConfig) -> Self {
+        Self {
+            max_speed: config.position_filter_max_speed,
+            min_parked_distance: config.position_filter_min_parked_distance,
+            max_clock_skew: config.position_filter_max_clock_skew_secs as f64,
+        }
+    }
+}
+
+/// A check of the filter the position failed
+#[derive(Debug)]
+struct Rejection {
+    reason: PositionFilterReason,
+    measured: f64,
+    threshold: f64,
+}
+
+/// A position rejected by the filter, stored as a `position_filter_decision`
+pub struct FilterDecision {
+    time: DateTime<Utc>,
+    tracker_id: i32,
+    reason: PositionFilterReason,
+    dropped: bool,
+    lat: f64,
+    lng: f64,
+    speed: Option<f64>,
+    ignition: Option<bool>,
+    measured: f64,
+    threshold: f64,
+}
+
+impl BatchRow for FilterDecision {
+    const NAME: &'static str = "position filter decisions";
+
+    const PARAMS: usize = 10;
+
+    fn insert_query(decisions: &[Self]) -> QueryBuilder<'_, Postgres> {
+        let mut query: QueryBuilder<Postgres> = QueryBuilder::new(
+            "INSERT INTO position_filter_decision (time, vehicle_tracker_id, reason, dropped, lat, lng, speed, ignition, measured, threshold) ",
+        );
+
+        query.push_values(decisions, |mut row, decision| {
+            row.push_bind(decision.time)
+                .push_bind(decision.tracker_id)
+                .push_bind(decision.reason.to_string())
+                .push_unseparated("::position_filter_reason")
+                .push_bind(decision.dropped)
+                .push_bind(decision.lat)
+                .push_bind(decision.lng)
+                .push_bind(decision.speed)
+                .push_bind(decision.ignition)
+                .push_bind(decision.measured)
+                .push_bind(decision.threshold);
+        });
+
+        query
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "position filter decision of tracker {} at {}",
+            self.tracker_id, self.time
+        )
+    }
+}
+
+/// The last location of the tracker, used when its last fix is not
+/// cached, such as after the API restarts
+async fn last_stored_fix(tracker_id: i32, db: &DatabaseConnection) -> Option<Fix> {
+    // location points are stored with the latitude as the X coordinate
+    let row = sqlx::query_as::<_, (DateTime<Utc>, f64, f64)>(
+        "SELECT time, ST_X(point), ST_Y(point) FROM vehicle_tracker_last_location WHERE vehicle_tracker_id = $1",
+    )
+    .bind(tracker_id)
+    .fetch_optional(db.get_postgres_connection_pool())
+    .await;
+
+    match row {
+        Ok(row) => row.map(|(timestamp, lat, lng)| Fix {
+            lat,
+            lng,
+            timestamp,
+            speed_rejections: 0,
+        }),
+        Err(e) => {
+            error!("failed to get last location of tracker {tracker_id}: {e}");
+            None
+        }
+    }
+}
+
+/// Runs the checks of the filter on the position, the speed and parked drift checks are
+/// skipped for out of order positions, as they are compared against the newest position
+/// of the tracker that passed the filter
+fn check(
+    position: &PositionDto,
+    last: Option<&Fix>,
+    now: DateTime<Utc>,
+    limits: &Limits,
+) -> Option<Rejection> {
+    let skew = (position.timestamp - now).num_seconds() as f64;
+
+    if limits.max_clock_skew > 0.0 && skew > limits.max_clock_skew {
+        return Some(Rejection {
+            reason: PositionFilterReason::ClockSkew,
+            measured: skew,
+            threshold: limits.max_clock_skew,
+        });
+    }
+
+    let last = last.filter(|last| position.timestamp > last.timestamp)?;
+
+    let distance = distance_in_meters((last.lat, last.lng), (position.lat, position.lng));
+    let elapsed_secs = (position.timestamp - last.timestamp).num_milliseconds() as f64 / 1000.0;
+
+    let speed = distance / elapsed_secs * 3.6;
+
+    if limits.max_speed > 0.0
+        && speed > limits.max_speed
+        && last.speed_rejections < MAX_CONSECUTIVE_SPEED_REJECTIONS
+    {
+        return Some(Rejection {
+            reason: PositionFilterReason::MaxSpeed,
+            measured: speed,
+            threshold: limits.max_speed,
+        });
+    }
+
+    if position.telemetry.ignition == Some(false) && distance < limits.min_parked_distance {
+        return Some(Rejection {
+            reason: PositionFilterReason::ParkedDrift,
+            measured: distance,
+            threshold: limits.min_parked_distance,
+        });
+    }
+
+    None
+}
+
+/// Checks if the quality of the position is good enough for it to be handled, rejecting positions
+/// with timestamps too far in the future, positions too far away from the previous position of the
+/// tracker to be reached in time and positions that barely moved while the ignition is off, such
+/// as the GPS drift of a parked vehicle, the limits of each check are set on the `AppConfig`.
+///
+/// rejected positions are queued to be stored as a `position_filter_decision`, and dropped unless
+/// the filter is set to only flag them, returning if the position should be handled.
+#[tracing::instrument(skip_all)]
+async fn filter_position(position: &PositionDto, ctx: &EventContext) -> bool {
+    let tracker_id = position.tracker_id;
+    let config = app_config();
+    let flag_only = config.position_filter_flag_only;
+
+    let is_cached = last_fixes().lock().await.contains_key(&tracker_id);
+
+    let stored = match is_cached {
+        true => None,
+        false => last_stored_fix(tracker_id, &ctx.db).await,
+    };
+
+    let rejection = {
+        let mut fixes = last_fixes().lock().await;
+
+        if let Some(fix) = stored {
+            fixes.entry(tracker_id).or_insert(fix);
+        }
+
+        let last = fixes.get(&tracker_id).copied();
+        let rejection = check(position, last.as_ref(), Utc::now(), &Limits::from(config));
+
+        let dropped = rejection.is_some() && !flag_only;
+        let is_newer = last.is_none_or(|last| position.timestamp > last.timestamp);
+
+        match (&rejection, fixes.get_mut(&tracker_id)) {
+            (Some(r), Some(fix)) if r.reason == PositionFilterReason::MaxSpeed && dropped => {
+                fix.speed_rejections += 1;
+            }
+            _ if !dropped && is_newer => {
+                fixes.insert(
+                    tracker_id,
+                    Fix {
+                        lat: position.lat,
+                        lng: position.lng,
+                        timestamp: position.timestamp,
+                        speed_rejections: 0,
+                    },
+                );
+            }
+            _ => {}
+        }
+
+        rejection
+    };
+
+    let rejection = match rejection {
+        Some(rejection) => rejection,
+        None => {
+            debug!(
+                "position of tracker {tracker_id} at {} passed the filter",
+                position.timestamp
+            );
+            return true;
+        }
+    };
+
+    let dropped = !flag_only;
+
+    warn!(
+        "position of tracker {tracker_id} at {} failed the {} check, measured {:.1} with limit {:.1}, dropped: {dropped}",
+        position.timestamp, rejection.reason, rejection.measured, rejection.threshold
+    );
+
+    ctx.filter_decisions
+        .write(FilterDecision {
+            time: position.timestamp,
+            tracker_id,
+            reason: rejection.reason,
+            dropped,
+            lat: position.lat,
+            lng: position.lng,
+            speed: position.telemetry.speed,
+            ignition: position.telemetry.ignition,
+            measured: rejection.measured,
+            threshold: rejection.threshold,
+        })
+        .await;
+
+    !dropped
+}
+
+/// If the position of a location event should be handled, the position is filtered once per
+/// event, so every handler of the event gets the same answer, see `filter_position`
+pub async fn is_accepted(position: &PositionDto, ctx: &EventContext) -> bool {
+    *ctx.position_accepted
+        .get_or_init(|| filter_position(position, ctx))
+        .await
+}
+
+/// If the position of the location event was rejected by the filter, for the handlers
+/// of the event that run after the position was filtered and do not decode it
+pub fn is_rejected(ctx: &EventContext) -> bool {
+    ctx.position_accepted.get() == Some(&false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, FilterDecision, Fix, Limits, MAX_CONSECUTIVE_SPEED_REJECTIONS};
+    use crate::modules::tracking::{
+        batch::BatchRow,
+        dto::{PositionDto, TelemetryDto},
+    };
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use shared::constants::PositionFilterReason;
+
+    /// About 111 meters of latitude
+    const DEGREES_PER_111_METERS: f64 = 0.001;
+
+    const LIMITS: Limits = Limits {
+        max_speed: 300.0,
+        min_parked_distance: 15.0,
+        max_clock_skew: 120.0,
+    };
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    fn fix(seconds_ago: i64) -> Fix {
+        Fix {
+            lat: -23.5,
+            lng: -46.6,
+            timestamp: now() - Duration::seconds(seconds_ago),
+            speed_rejections: 0,
+        }
+    }
+
+    /// A position `degrees` north of the fix, at `seconds` from now
+    fn position(degrees: f64, seconds: i64, ignition: Option<bool>) -> PositionDto {
+        PositionDto {
+            lat: -23.5 + degrees,
+            lng: -46.6,
+            timestamp: now() + Duration::seconds(seconds),
+            tracker_id: 1,
+            telemetry: TelemetryDto {
+                ignition,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn reason(position: &PositionDto, last: Option<&Fix>) -> Option<PositionFilterReason> {
+        check(position, last, now(), &LIMITS).map(|r| r.reason)
+    }
+
+    #[test]
+    fn accepts_plausible_positions() {
+        let last = fix(60);
+
+        // 1 km in a minute, 60 km/h
+        let position = position(DEGREES_PER_111_METERS * 9.0, 0, Some(true));
+
+        assert_eq!(reason(&position, Some(&last)), None);
+        assert_eq!(reason(&position, None), None);
+    }
+
+    #[test]
+    fn rejects_positions_from_the_future() {
+        let rejection = check(&position(0.0, 121, None), None, now(), &LIMITS).unwrap();
+
+        assert_eq!(rejection.reason, PositionFilterReason::ClockSkew);
+        assert_eq!(rejection.measured, 121.0);
+        assert_eq!(rejection.threshold, 120.0);
+
+        assert_eq!(reason(&position(0.0, 120, None), None), None);
+    }
+
+    #[test]
+    fn rejects_positions_too_far_to_be_reached() {
+        let last = fix(10);
+
+        // 1 km in 10 seconds, 360 km/h
+        let far = position(DEGREES_PER_111_METERS * 9.0, 0, Some(true));
+        let rejection = check(&far, Some(&last), now(), &LIMITS).unwrap();
+
+        assert_eq!(rejection.reason, PositionFilterReason::MaxSpeed);
+        assert!((rejection.measured - 360.0).abs() < 5.0, "{rejection:?}");
+        assert_eq!(rejection.threshold, 300.0);
+    }
+
+    #[test]
+    fn accepts_far_positions_after_consecutive_speed_rejections() {
+        let last = Fix {
+            speed_rejections: MAX_CONSECUTIVE_SPEED_REJECTIONS,
+            ..fix(10)
+        };
+
+        let far = position(DEGREES_PER_111_METERS * 9.0, 0, Some(true));
+
+        assert_eq!(reason(&far, Some(&last)), None);
+    }
+
+    #[test]
+    fn rejects_parked_drift() {
+        let last = fix(60);
+        let drift = position(DEGREES_PER_111_METERS / 10.0, 0, Some(false));
+
+        let rejection = check(&drift, Some(&last), now(), &LIMITS).unwrap();
+
+        assert_eq!(rejection.reason, PositionFilterReason::ParkedDrift);
+        assert!((rejection.measured - 11.1).abs() < 0.5, "{rejection:?}");
+        assert_eq!(rejection.threshold, 15.0);
+
+        // with the ignition on or unknown the vehicle may be moving slowly
+        let slow = position(DEGREES_PER_111_METERS / 10.0, 0, Some(true));
+        let unknown = position(DEGREES_PER_111_METERS / 10.0, 0, None);
+
+        assert_eq!(reason(&slow, Some(&last)), None);
+        assert_eq!(reason(&unknown, Some(&last)), None);
+
+        let towed = position(DEGREES_PER_111_METERS, 0, Some(false));
+
+        assert_eq!(reason(&towed, Some(&last)), None);
+    }
+
+    #[test]
+    fn skips_speed_and_drift_checks_of_out_of_order_positions() {
+        let last = fix(0);
+
+        let far = position(DEGREES_PER_111_METERS * 9.0, -10, Some(true));
+        let drift = position(0.0, -10, Some(false));
+        let same_time = position(0.0, 0, Some(false));
+
+        assert_eq!(reason(&far, Some(&last)), None);
+        assert_eq!(reason(&drift, Some(&last)), None);
+        assert_eq!(reason(&same_time, Some(&last)), None);
+    }
+
+    #[test]
+    fn disables_checks_with_zero_limits() {
+        let limits = Limits {
+            max_speed: 0.0,
+            min_parked_distance: 0.0,
+            max_clock_skew: 0.0,
+        };
+
+        let last = fix(10);
+
+        let future = position(0.0, 3600, None);
+        let far = position(DEGREES_PER_111_METERS * 9.0, 0, Some(true));
+        let drift = position(0.0, 0, Some(false));
+
+        assert!(check(&future, None, now(), &limits).is_none());
+        assert!(check(&far, Some(&last), now(), &limits).is_none());
+        assert!(check(&drift, Some(&last), now(), &limits).is_none());
+    }
+
+    #[test]
+    fn casts_the_reason_of_stored_decisions() {
+        let decision = FilterDecision {
+            time: now(),
+            tracker_id: 1,
+            reason: PositionFilterReason::ParkedDrift,
+            dropped: true,
+            lat: -23.5,
+            lng: -46.6,
+            speed: None,
+            ignition: Some(false),
+            measured: 3.0,
+            threshold: 15.0,
+        };
+
+        let decisions = [decision];
+        let query = FilterDecision::insert_query(&decisions);
+
+        assert!(query
+            .sql()
+            .ends_with("VALUES ($1, $2, $3::position_filter_reason, $4, $5, $6, $7, $8, $9, $10)"));
+        assert_eq!(
+            PositionFilterReason::ParkedDrift.to_string(),
+            "PARKED_DRIFT"
+        );
+    }
+}
@@ -1,6 +1,11 @@
 pub mod background;
+pub mod batch;
 pub mod cache;
 pub mod decoder;
 pub mod dto;
+pub mod export;
+pub mod filter;
+pub mod registry;
 pub mod routes;
+pub mod trip;
 pub mod utils;
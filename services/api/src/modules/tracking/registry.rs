@@ -0,0 +1,304 @@
+use super::{
+    batch::{BatchWriter, PositionWriter},
+    filter::FilterDecision,
+};
+use futures_util::future::{BoxFuture, FutureExt};
+use opentelemetry::{
+    global,
+    metrics::{Counter, Meter},
+    Context, KeyValue,
+};
+use sea_orm::DatabaseConnection;
+use serde::de::DeserializeOwned;
+use socketioxide::SocketIo;
+use std::{collections::HashMap, future::Future, sync::Arc};
+use tokio::sync::OnceCell;
+use tracing::error;
+
+/// Protocol to register handlers of events that are handled the same way
+/// regardless of the tracker protocol, such as command events
+pub const ANY_PROTOCOL: &str = "*";
+
+/// The tracker event being handled, besides the event data
+#[derive(Clone)]
+pub struct EventContext {
+    pub protocol: String,
+    pub event_type: String,
+    pub imei: String,
+    pub tracker_id: i32,
+    pub socket: SocketIo,
+    pub db: DatabaseConnection,
+    pub positions: PositionWriter,
+    pub filter_decisions: BatchWriter<FilterDecision>,
+
+    /// if the position of a location event passed the position filter, shared by
+    /// the handlers of the event, see `filter::is_accepted`
+    pub position_accepted: Arc<OnceCell<bool>>,
+}
+
+type BoxedHandler = Box<dyn Fn(&[u8], EventContext) -> BoxFuture<'static, ()> + Send + Sync>;
+
+/// Maps the `(protocol, event_type)` of tracker events published by the decoder
+/// service to their handlers, so supporting a new protocol or event type only
+/// requires registering its handlers.
+///
+/// Many handlers can be registered for the same event, they are called in the order
+/// they were registered, handlers of the event protocol before `ANY_PROTOCOL` handlers.
+///
+/// `ANY_PROTOCOL` handlers are only called for the events of known protocols, that
+/// is, protocols with at least one handler of their own, so events of unknown
+/// protocols are not supported even if there is a `ANY_PROTOCOL` handler for them.
+pub struct EventRegistry {
+    handlers: HashMap<(String, String), Vec<BoxedHandler>>,
+
+    /// events without any handler, by protocol and event type
+    unhandled_events: Counter<u64>,
+}
+
+impl EventRegistry {
+    pub fn new() -> EventRegistry {
+        EventRegistry::with_meter(&global::meter("tracker_events"))
+    }
+
+    fn with_meter(meter: &Meter) -> EventRegistry {
+        let unhandled_events = meter
+            .u64_counter("tracker_events.unhandled")
+            .with_description("tracker events without a handler")
+            .init();
+
+        EventRegistry {
+            handlers: HashMap::new(),
+            unhandled_events,
+        }
+    }
+
+    /// Registers a handler for the events of `event_type` of trackers of the `protocol`,
+    /// the event data is deserialized to `T` before calling the handler, events that
+    /// cannot be deserialized are logged and not passed to the handler.
+    pub fn on<T, F, Fut>(&mut self, protocol: &str, event_type: &str, handler: F) -> &mut Self
+    where
+        T: DeserializeOwned + Send + 'static,
+        F: Fn(T, EventContext) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let boxed: BoxedHandler =
+            Box::new(move |data, ctx| match serde_json::from_slice::<T>(data) {
+                Ok(msg) => handler(msg, ctx).boxed(),
+                Err(e) => {
+                    error!(
+                        "failed to parse {}.{} event of tracker {}: {e}",
+                        ctx.protocol, ctx.event_type, ctx.imei
+                    );
+                    async {}.boxed()
+                }
+            });
+
+        self.handlers
+            .entry((protocol.to_string(), event_type.to_string()))
+            .or_default()
+            .push(boxed);
+
+        self
+    }
+
+    /// if any handler was registered for the protocol itself
+    fn is_known_protocol(&self, protocol: &str) -> bool {
+        protocol != ANY_PROTOCOL && self.handlers.keys().any(|(p, _)| p == protocol)
+    }
+
+    fn handlers<'a>(
+        &'a self,
+        protocol: &'a str,
+        event_type: &'a str,
+    ) -> impl Iterator<Item = &'a BoxedHandler> {
+        let protocols = match self.is_known_protocol(protocol) {
+            true => vec![protocol, ANY_PROTOCOL],
+            false => vec![],
+        };
+
+        protocols
+            .into_iter()
+            .filter_map(move |p| self.handlers.get(&(p.to_string(), event_type.to_string())))
+            .flatten()
+    }
+
+    /// If there is any handler for the event, counting the event as unhandled if not
+    pub fn is_supported(&self, protocol: &str, event_type: &str) -> bool {
+        let is_supported = self.handlers(protocol, event_type).next().is_some();
+
+        if !is_supported {
+            self.unhandled_events.add(
+                &Context::current(),
+                1,
+                &[
+                    KeyValue::new("protocol", protocol.to_string()),
+                    KeyValue::new("event_type", event_type.to_string()),
+                ],
+            );
+        }
+
+        is_supported
+    }
+
+    /// Calls every handler of the event with its data
+    pub async fn dispatch(&self, data: &[u8], ctx: EventContext) {
+        for handler in self.handlers(&ctx.protocol, &ctx.event_type) {
+            handler(data, ctx.clone()).await;
+        }
+    }
+}
+
+impl Default for EventRegistry {
+    fn default() -> Self {
+        EventRegistry::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{EventContext, EventRegistry, ANY_PROTOCOL};
+    use crate::modules::tracking::batch::BatchWriter;
+    use opentelemetry::{
+        metrics::MeterProvider,
+        sdk::{
+            export::metrics::{
+                aggregation::{cumulative_temporality_selector, Sum},
+                InstrumentationLibraryReader,
+            },
+            metrics::{aggregators::SumAggregator, controllers, processors, selectors},
+        },
+        Context,
+    };
+    use sea_orm::DatabaseConnection;
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    fn registry() -> EventRegistry {
+        let mut registry = EventRegistry::new();
+
+        registry
+            .on("h02", "location", |_: serde_json::Value, _| async {})
+            .on(ANY_PROTOCOL, "location", |_: serde_json::Value, _| async {})
+            .on(ANY_PROTOCOL, "heartbeat", |_: serde_json::Value, _| async {
+            });
+
+        registry
+    }
+
+    fn context(protocol: &str, event_type: &str) -> EventContext {
+        let db = DatabaseConnection::Disconnected;
+
+        EventContext {
+            protocol: protocol.to_string(),
+            event_type: event_type.to_string(),
+            imei: String::from("867232051148352"),
+            tracker_id: 1,
+            socket: socketioxide::SocketIo::new_layer().1,
+            positions: BatchWriter::start(db.clone(), 1, Duration::from_millis(1)),
+            filter_decisions: BatchWriter::start(db.clone(), 1, Duration::from_millis(1)),
+            position_accepted: Arc::default(),
+            db,
+        }
+    }
+
+    #[test]
+    fn finds_protocol_and_any_protocol_handlers() {
+        let registry = registry();
+
+        assert_eq!(registry.handlers("h02", "location").count(), 2);
+        assert!(registry.is_supported("h02", "location"));
+    }
+
+    #[test]
+    fn falls_back_to_any_protocol_handlers_of_known_protocols() {
+        let registry = registry();
+
+        assert_eq!(registry.handlers("h02", "heartbeat").count(), 1);
+        assert!(registry.is_supported("h02", "heartbeat"));
+    }
+
+    #[test]
+    fn does_not_support_unknown_protocols_or_events() {
+        let registry = registry();
+
+        assert_eq!(registry.handlers("unknown", "location").count(), 0);
+        assert!(!registry.is_supported("unknown", "location"));
+        assert!(!registry.is_supported("h02", "unknown"));
+        assert!(!registry.is_supported(ANY_PROTOCOL, "location"));
+    }
+
+    #[test]
+    fn counts_unhandled_events() {
+        let controller = controllers::basic(processors::factory(
+            selectors::simple::inexpensive(),
+            cumulative_temporality_selector(),
+        ))
+        .build();
+
+        let mut registry = EventRegistry::with_meter(&controller.meter("test"));
+        registry.on("h02", "location", |_: serde_json::Value, _| async {});
+        registry.on(ANY_PROTOCOL, "heartbeat", |_: serde_json::Value, _| async {
+        });
+
+        registry.is_supported("h02", "location");
+        registry.is_supported("h02", "heartbeat");
+        registry.is_supported("unknown", "heartbeat");
+        registry.is_supported("h02", "unknown");
+
+        let cx = Context::new();
+        controller.collect(&cx).unwrap();
+
+        let mut unhandled = 0;
+
+        controller
+            .try_for_each(&mut |_, reader| {
+                reader.try_for_each(&cumulative_temporality_selector(), &mut |record| {
+                    if let Some(aggregator) = record.aggregator() {
+                        if let Some(sum) = aggregator.as_any().downcast_ref::<SumAggregator>() {
+                            unhandled += sum.sum()?.to_u64(record.descriptor().number_kind());
+                        }
+                    }
+
+                    Ok(())
+                })
+            })
+            .unwrap();
+
+        assert_eq!(unhandled, 2);
+    }
+
+    #[tokio::test]
+    async fn dispatches_to_handlers_in_order() {
+        let calls = Arc::new(Mutex::new(vec![]));
+        let mut registry = EventRegistry::new();
+
+        for (protocol, name) in [(ANY_PROTOCOL, "any"), ("h02", "first"), ("h02", "second")] {
+            let calls = calls.clone();
+
+            registry.on(protocol, "location", move |msg: serde_json::Value, _| {
+                calls.lock().unwrap().push((name, msg["lat"].clone()));
+                async {}
+            });
+        }
+
+        registry
+            .dispatch(br#"{"lat":-23.5}"#, context("h02", "location"))
+            .await;
+
+        // events that cannot be deserialized are not passed to the handlers
+        registry
+            .dispatch(b"not json", context("h02", "location"))
+            .await;
+
+        let calls: Vec<String> = calls
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(name, lat)| format!("{name}:{lat}"))
+            .collect();
+
+        assert_eq!(calls, vec!["first:-23.5", "second:-23.5", "any:-23.5"]);
+    }
+}
@@ -1,4 +1,5 @@
 use super::dto::{AuthPayload, GetTrackersLastPositionsDto, PositionDto};
+use super::utils::LocationRow;
 use crate::{
     modules::{
         auth::{self, jwt, service::AuthService},
@@ -10,7 +11,6 @@ use crate::{
     server::controller::AppState,
 };
 use axum::{routing::post, Json, Router};
-use chrono::{DateTime, Utc};
 use http::StatusCode;
 use sea_orm::{entity::prelude::*, QuerySelect, QueryTrait};
 use sea_query::{Cond, PostgresQueryBuilder, Query as SeaQuery};
@@ -78,9 +78,15 @@ pub async fn get_trackers_last_positions(
     };
 
     let (q, args) = SeaQuery::select()
-        .column(vehicle_tracker_last_location::Column::Time)
-        .column(vehicle_tracker_last_location::Column::Point)
-        .column(vehicle_tracker_last_location::Column::VehicleTrackerId)
+        .columns([
+            vehicle_tracker_last_location::Column::VehicleTrackerId,
+            vehicle_tracker_last_location::Column::Time,
+            vehicle_tracker_last_location::Column::Point,
+            vehicle_tracker_last_location::Column::Speed,
+            vehicle_tracker_last_location::Column::Heading,
+            vehicle_tracker_last_location::Column::Ignition,
+            vehicle_tracker_last_location::Column::Status,
+        ])
         .from(vehicle_tracker_last_location::Entity)
         .cond_where(
             Cond::all().add(
@@ -96,26 +102,17 @@ pub async fn get_trackers_last_positions(
         .await
         .map_err(|_| internal_error_res())?
         .into_iter()
-        .filter_map(
-            |row: (
-                DateTime<Utc>,
-                geozero::wkb::Decode<geo_types::Geometry<f64>>,
-                i32,
-            )| {
-                if let Some(geo_types::Geometry::Point(point)) = row.1.geometry {
-                    let loc = PositionDto {
-                        lat: point.y(),
-                        lng: point.x(),
-                        timestamp: row.0,
-                        tracker_id: row.2,
-                    };
-
-                    return Some(loc);
-                }
-
-                None
-            },
-        )
+        .filter_map(|row: LocationRow| {
+            let (lat, lng) = row.lat_lng()?;
+
+            Some(PositionDto {
+                lat,
+                lng,
+                timestamp: row.time,
+                tracker_id: row.vehicle_tracker_id,
+                telemetry: row.telemetry(),
+            })
+        })
         .collect();
 
     Ok(Json(positions))
@@ -0,0 +1,668 @@
+use super::{dto::PositionDto, utils::distance_in_meters};
+use crate::config::{app_config, AppConfig};
+use chrono::Duration;
+use sea_orm::{
+    ActiveModelBehavior, ActiveModelTrait, ColumnTrait, DatabaseConnection, DbErr, EntityTrait,
+    IntoActiveModel, ModelTrait, QueryFilter, Set,
+};
+use shared::entity::{stop, trip, vehicle_tracker};
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+    time::Instant,
+};
+use tokio::sync::Mutex;
+
+/// Minimum interval in seconds between saves of the in progress trip and stop of a vehicle, as
+/// positions that do not start or end them only change them in memory
+const PROGRESS_SAVE_INTERVAL_SECS: u64 = 60;
+
+/// Trips state of each tracker, by tracker id, so positions are segmented without reading
+/// the tracker, its in progress trip and stop from the database for each of them
+static TRACKER_TRIPS: OnceLock<Mutex<HashMap<i32, Arc<Mutex<TrackerTrips>>>>> = OnceLock::new();
+
+fn tracker_trips() -> &'static Mutex<HashMap<i32, Arc<Mutex<TrackerTrips>>>> {
+    TRACKER_TRIPS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Removes the cached trips state of a tracker, it must be called whenever the vehicle the
+/// tracker is installed on changes, so the trips of the new vehicle are loaded
+pub async fn forget_tracker(tracker_id: i32) {
+    tracker_trips().lock().await.remove(&tracker_id);
+}
+
+/// Trips state of a tracker, loaded on the first position of the tracker
+enum TrackerTrips {
+    Unloaded,
+    /// the tracker is not installed on a vehicle, so it has no trips
+    NoVehicle,
+    Loaded(VehicleTrips),
+}
+
+/// The vehicle a tracker is installed on with its in progress trip and stop, which
+/// are kept up to date in memory and only saved on transitions or periodically
+struct VehicleTrips {
+    vehicle_id: i32,
+    organization_id: i32,
+    trip: Option<trip::Model>,
+    stop: Option<stop::Model>,
+    /// when the in progress trip and stop were last saved
+    saved_at: Instant,
+}
+
+/// Limits used to segment the positions of a vehicle into trips and stops
+#[derive(Clone, Copy)]
+struct Thresholds {
+    /// minimum speed in km/h for the vehicle to be considered moving
+    min_moving_speed: f64,
+    /// minimum time the vehicle must stay still for it to be a stop
+    min_stop_duration: Duration,
+}
+
+impl From<&AppConfig> for Thresholds {
+    fn from(config: &AppConfig) -> Self {
+        Self {
+            min_moving_speed: config.trip_min_moving_speed,
+            min_stop_duration: Duration::seconds(config.trip_min_stop_duration_secs),
+        }
+    }
+}
+
+/// What happens to the in progress stop of a vehicle once it starts moving
+#[derive(Debug, PartialEq)]
+enum StopEnd {
+    /// the stop was too short, so it is deleted and the trip continues
+    Discard,
+    /// the stop was long enough, so it ends along with the trip before it
+    Finish,
+}
+
+/// What to do with the in progress trip and stop of a vehicle for a new position
+#[derive(Debug, PartialEq)]
+enum Decision {
+    /// the position is not newer than the last position of the in progress trip or stop
+    Ignore,
+    /// the vehicle is still, starting a stop if none is in progress or extending it
+    /// otherwise, the in progress trip ends once the stop is long enough
+    Stop { start_stop: bool, end_trip: bool },
+    /// the vehicle is moving, ending the in progress stop if any, then starting a
+    /// new trip or continuing the in progress one
+    Move {
+        stop: Option<StopEnd>,
+        end_trip: bool,
+        start_trip: bool,
+    },
+}
+
+/// Decides how a new position of a vehicle changes its in progress trip and stop
+fn decide(
+    trip: Option<&trip::Model>,
+    stop: Option<&stop::Model>,
+    position: &PositionDto,
+    thresholds: &Thresholds,
+) -> Decision {
+    let is_out_of_order = [trip.map(|t| t.ended_at), stop.map(|s| s.ended_at)]
+        .into_iter()
+        .flatten()
+        .any(|last_position_at| position.timestamp <= last_position_at);
+
+    if is_out_of_order {
+        return Decision::Ignore;
+    }
+
+    let speed = position.telemetry.speed.unwrap_or(0.0);
+    let is_moving =
+        speed >= thresholds.min_moving_speed && position.telemetry.ignition != Some(false);
+
+    // the stop lasts until the new position, be it the start of a new stop or not
+    let stop_started_at = stop.map_or(position.timestamp, |s| s.started_at);
+    let is_long_stop = position.timestamp - stop_started_at >= thresholds.min_stop_duration;
+
+    if !is_moving {
+        return Decision::Stop {
+            start_stop: stop.is_none(),
+            end_trip: trip.is_some() && is_long_stop,
+        };
+    }
+
+    let stop = stop.map(|_| match is_long_stop {
+        true => StopEnd::Finish,
+        false => StopEnd::Discard,
+    });
+
+    let ends_trip = stop == Some(StopEnd::Finish);
+
+    Decision::Move {
+        stop,
+        end_trip: trip.is_some() && ends_trip,
+        start_trip: trip.is_none() || ends_trip,
+    }
+}
+
+/// Segments the positions of the vehicle of a tracker into trips and stops, this is
+/// incremental, it only needs the in progress trip and stop of the vehicle, if any.
+///
+/// a vehicle that stops for less than `trip_min_stop_duration_secs` does not end its trip,
+/// once it starts moving again the in progress stop is deleted and the trip continues,
+/// positions older than the last position of the in progress trip or stop are ignored.
+///
+/// the trips state of the tracker is cached, trips and stops are only written when they
+/// start or end and every `PROGRESS_SAVE_INTERVAL_SECS`, on errors the cached state is
+/// dropped to be loaded again from the database on the next position.
+pub async fn update_trips(db: &DatabaseConnection, position: &PositionDto) -> Result<(), DbErr> {
+    let cached = tracker_trips()
+        .lock()
+        .await
+        .entry(position.tracker_id)
+        .or_insert_with(|| Arc::new(Mutex::new(TrackerTrips::Unloaded)))
+        .clone();
+
+    let mut state = cached.lock().await;
+
+    if let TrackerTrips::Unloaded = *state {
+        *state = load_trips(db, position.tracker_id).await?;
+    }
+
+    let TrackerTrips::Loaded(trips) = &mut *state else {
+        return Ok(());
+    };
+
+    let thresholds = Thresholds::from(app_config());
+    let result = apply_position(db, trips, position, &thresholds).await;
+
+    if result.is_err() {
+        *state = TrackerTrips::Unloaded;
+    }
+
+    result
+}
+
+/// Loads the vehicle of the tracker with its in progress trip and stop
+async fn load_trips(db: &DatabaseConnection, tracker_id: i32) -> Result<TrackerTrips, DbErr> {
+    let tracker = vehicle_tracker::Entity::find_by_id(tracker_id)
+        .one(db)
+        .await?;
+
+    let (vehicle_id, organization_id) = match tracker {
+        Some(vehicle_tracker::Model {
+            vehicle_id: Some(vehicle_id),
+            organization_id,
+            ..
+        }) => (vehicle_id, organization_id),
+        _ => return Ok(TrackerTrips::NoVehicle),
+    };
+
+    let trip = trip::Entity::find()
+        .filter(trip::Column::VehicleId.eq(vehicle_id))
+        .filter(trip::Column::InProgress.eq(true))
+        .one(db)
+        .await?;
+
+    let stop = stop::Entity::find()
+        .filter(stop::Column::VehicleId.eq(vehicle_id))
+        .filter(stop::Column::InProgress.eq(true))
+        .one(db)
+        .await?;
+
+    Ok(TrackerTrips::Loaded(VehicleTrips {
+        vehicle_id,
+        organization_id,
+        trip,
+        stop,
+        saved_at: Instant::now(),
+    }))
+}
+
+/// Applies the decision for a new position to the in progress trip and stop of the
+/// vehicle, writing the trips and stops that started or ended
+async fn apply_position(
+    db: &DatabaseConnection,
+    trips: &mut VehicleTrips,
+    position: &PositionDto,
+    thresholds: &Thresholds,
+) -> Result<(), DbErr> {
+    let decision = decide(
+        trips.trip.as_ref(),
+        trips.stop.as_ref(),
+        position,
+        thresholds,
+    );
+
+    match decision {
+        Decision::Ignore => return Ok(()),
+        Decision::Stop {
+            start_stop,
+            end_trip: ends_trip,
+        } => {
+            match trips.stop.as_mut() {
+                Some(stop) if !start_stop => stop.ended_at = position.timestamp,
+                _ => {
+                    let stop = stop::ActiveModel {
+                        vehicle_id: Set(trips.vehicle_id),
+                        vehicle_tracker_id: Set(Some(position.tracker_id)),
+                        organization_id: Set(trips.organization_id),
+                        in_progress: Set(true),
+                        started_at: Set(position.timestamp),
+                        ended_at: Set(position.timestamp),
+                        lat: Set(position.lat),
+                        lng: Set(position.lng),
+                        ..Default::default()
+                    }
+                    .insert(db)
+                    .await?;
+
+                    trips.stop = Some(stop);
+                }
+            }
+
+            if let (Some(stop), true) = (&trips.stop, ends_trip) {
+                if let Some(trip) = trips.trip.take() {
+                    end_trip(db, trip, stop).await?;
+                }
+            }
+        }
+        Decision::Move {
+            stop: stop_end,
+            end_trip: ends_trip,
+            start_trip,
+        } => {
+            match (trips.stop.take(), stop_end) {
+                (Some(mut stop), Some(StopEnd::Finish)) => {
+                    stop.ended_at = position.timestamp;
+
+                    if let Some(trip) = trips.trip.take().filter(|_| ends_trip) {
+                        end_trip(db, trip, &stop).await?;
+                    }
+
+                    stop.in_progress = false;
+                    save(stop::ActiveModel::from(stop), db).await?;
+                }
+                (Some(stop), Some(StopEnd::Discard)) => {
+                    stop.delete(db).await?;
+                }
+                (stop, _) => trips.stop = stop,
+            }
+
+            match trips.trip.as_mut() {
+                Some(trip) if !start_trip => continue_trip(trip, position),
+                _ => {
+                    let trip = trip::ActiveModel {
+                        vehicle_id: Set(trips.vehicle_id),
+                        vehicle_tracker_id: Set(Some(position.tracker_id)),
+                        organization_id: Set(trips.organization_id),
+                        in_progress: Set(true),
+                        started_at: Set(position.timestamp),
+                        ended_at: Set(position.timestamp),
+                        start_lat: Set(position.lat),
+                        start_lng: Set(position.lng),
+                        end_lat: Set(position.lat),
+                        end_lng: Set(position.lng),
+                        distance: Set(0.0),
+                        max_speed: Set(position.telemetry.speed),
+                        ..Default::default()
+                    }
+                    .insert(db)
+                    .await?;
+
+                    trips.trip = Some(trip);
+                }
+            }
+        }
+    }
+
+    if trips.saved_at.elapsed().as_secs() >= PROGRESS_SAVE_INTERVAL_SECS {
+        save_progress(db, trips).await?;
+    }
+
+    Ok(())
+}
+
+/// Saves every column of a trip or stop, as their changes are made in memory
+async fn save<A>(model: A, db: &DatabaseConnection) -> Result<(), DbErr>
+where
+    A: ActiveModelTrait + ActiveModelBehavior + Send,
+    <A::Entity as EntityTrait>::Model: IntoActiveModel<A>,
+{
+    model.reset_all().update(db).await?;
+
+    Ok(())
+}
+
+/// Saves the in progress trip and stop of the vehicle
+async fn save_progress(db: &DatabaseConnection, trips: &mut VehicleTrips) -> Result<(), DbErr> {
+    if let Some(trip) = &trips.trip {
+        save(trip::ActiveModel::from(trip.clone()), db).await?;
+    }
+
+    if let Some(stop) = &trips.stop {
+        save(stop::ActiveModel::from(stop.clone()), db).await?;
+    }
+
+    trips.saved_at = Instant::now();
+
+    Ok(())
+}
+
+/// Extends the trip to the position the vehicle moved to
+fn continue_trip(trip: &mut trip::Model, position: &PositionDto) {
+    trip.distance += distance_in_meters((trip.end_lat, trip.end_lng), (position.lat, position.lng));
+
+    trip.max_speed = match (trip.max_speed, position.telemetry.speed) {
+        (Some(max), Some(speed)) => Some(max.max(speed)),
+        (max, speed) => max.or(speed),
+    };
+
+    trip.ended_at = position.timestamp;
+    trip.end_lat = position.lat;
+    trip.end_lng = position.lng;
+}
+
+/// Ends the trip where and when the vehicle stopped
+async fn end_trip(
+    db: &DatabaseConnection,
+    mut trip: trip::Model,
+    stop: &stop::Model,
+) -> Result<(), DbErr> {
+    trip.distance += distance_in_meters((trip.end_lat, trip.end_lng), (stop.lat, stop.lng));
+    trip.in_progress = false;
+    trip.ended_at = stop.started_at;
+    trip.end_lat = stop.lat;
+    trip.end_lng = stop.lng;
+
+    save(trip::ActiveModel::from(trip), db).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{continue_trip, decide, Decision, StopEnd, Thresholds};
+    use crate::modules::tracking::dto::{PositionDto, TelemetryDto};
+    use chrono::{DateTime, Duration, TimeZone, Utc};
+    use shared::entity::{stop, trip};
+
+    fn thresholds() -> Thresholds {
+        Thresholds {
+            min_moving_speed: 5.0,
+            min_stop_duration: Duration::minutes(3),
+        }
+    }
+
+    fn at(minutes: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 3, 1, 12, 0, 0).unwrap() + Duration::minutes(minutes)
+    }
+
+    fn position(minutes: i64, speed: f64, ignition: Option<bool>) -> PositionDto {
+        PositionDto {
+            lat: -23.5,
+            lng: -46.6,
+            timestamp: at(minutes),
+            tracker_id: 1,
+            telemetry: TelemetryDto {
+                speed: Some(speed),
+                ignition,
+                ..Default::default()
+            },
+        }
+    }
+
+    fn trip(started_at: i64, ended_at: i64) -> trip::Model {
+        trip::Model {
+            id: 1,
+            vehicle_id: 1,
+            vehicle_tracker_id: Some(1),
+            organization_id: 1,
+            in_progress: true,
+            started_at: at(started_at),
+            ended_at: at(ended_at),
+            start_lat: -23.5,
+            start_lng: -46.6,
+            end_lat: -23.5,
+            end_lng: -46.6,
+            distance: 0.0,
+            max_speed: None,
+        }
+    }
+
+    fn stop(started_at: i64, ended_at: i64) -> stop::Model {
+        stop::Model {
+            id: 1,
+            vehicle_id: 1,
+            vehicle_tracker_id: Some(1),
+            organization_id: 1,
+            in_progress: true,
+            started_at: at(started_at),
+            ended_at: at(ended_at),
+            lat: -23.5,
+            lng: -46.6,
+        }
+    }
+
+    #[test]
+    fn starts_and_continues_trips_while_moving() {
+        let thresholds = thresholds();
+
+        assert_eq!(
+            decide(None, None, &position(0, 40.0, Some(true)), &thresholds),
+            Decision::Move {
+                stop: None,
+                end_trip: false,
+                start_trip: true
+            }
+        );
+
+        let trip = trip(0, 1);
+
+        assert_eq!(
+            decide(Some(&trip), None, &position(2, 40.0, None), &thresholds),
+            Decision::Move {
+                stop: None,
+                end_trip: false,
+                start_trip: false
+            }
+        );
+    }
+
+    #[test]
+    fn short_stops_do_not_end_the_trip() {
+        let thresholds = thresholds();
+        let trip = trip(0, 10);
+
+        assert_eq!(
+            decide(
+                Some(&trip),
+                None,
+                &position(11, 0.0, Some(true)),
+                &thresholds
+            ),
+            Decision::Stop {
+                start_stop: true,
+                end_trip: false
+            }
+        );
+
+        let stop = stop(11, 12);
+
+        assert_eq!(
+            decide(
+                Some(&trip),
+                Some(&stop),
+                &position(13, 0.0, Some(true)),
+                &thresholds
+            ),
+            Decision::Stop {
+                start_stop: false,
+                end_trip: false
+            }
+        );
+
+        assert_eq!(
+            decide(
+                Some(&trip),
+                Some(&stop),
+                &position(13, 40.0, Some(true)),
+                &thresholds
+            ),
+            Decision::Move {
+                stop: Some(StopEnd::Discard),
+                end_trip: false,
+                start_trip: false
+            }
+        );
+    }
+
+    #[test]
+    fn long_stops_end_the_trip() {
+        let thresholds = thresholds();
+        let trip = trip(0, 10);
+        let stop = stop(11, 13);
+
+        assert_eq!(
+            decide(
+                Some(&trip),
+                Some(&stop),
+                &position(14, 0.0, Some(true)),
+                &thresholds
+            ),
+            Decision::Stop {
+                start_stop: false,
+                end_trip: true
+            }
+        );
+
+        // the trip already ended with the stop, so a new one starts
+        assert_eq!(
+            decide(
+                None,
+                Some(&stop),
+                &position(20, 40.0, Some(true)),
+                &thresholds
+            ),
+            Decision::Move {
+                stop: Some(StopEnd::Finish),
+                end_trip: false,
+                start_trip: true
+            }
+        );
+
+        // the stop got long enough between positions
+        assert_eq!(
+            decide(
+                Some(&trip),
+                Some(&stop),
+                &position(20, 40.0, Some(true)),
+                &thresholds
+            ),
+            Decision::Move {
+                stop: Some(StopEnd::Finish),
+                end_trip: true,
+                start_trip: true
+            }
+        );
+    }
+
+    #[test]
+    fn vehicles_with_the_ignition_off_are_stopped() {
+        let thresholds = thresholds();
+        let trip = trip(0, 10);
+
+        assert_eq!(
+            decide(
+                Some(&trip),
+                None,
+                &position(11, 40.0, Some(false)),
+                &thresholds
+            ),
+            Decision::Stop {
+                start_stop: true,
+                end_trip: false
+            }
+        );
+    }
+
+    #[test]
+    fn ignores_out_of_order_positions() {
+        let thresholds = thresholds();
+        let trip = trip(0, 10);
+        let stop = stop(11, 12);
+
+        assert_eq!(
+            decide(
+                Some(&trip),
+                None,
+                &position(5, 40.0, Some(true)),
+                &thresholds
+            ),
+            Decision::Ignore
+        );
+        assert_eq!(
+            decide(
+                Some(&trip),
+                None,
+                &position(10, 40.0, Some(true)),
+                &thresholds
+            ),
+            Decision::Ignore
+        );
+        assert_eq!(
+            decide(
+                None,
+                Some(&stop),
+                &position(12, 0.0, Some(true)),
+                &thresholds
+            ),
+            Decision::Ignore
+        );
+    }
+
+    #[test]
+    fn uses_the_given_thresholds() {
+        let thresholds = Thresholds {
+            min_moving_speed: 50.0,
+            min_stop_duration: Duration::minutes(10),
+        };
+
+        let trip = trip(0, 10);
+        let stop = stop(11, 13);
+
+        assert_eq!(
+            decide(
+                Some(&trip),
+                None,
+                &position(11, 40.0, Some(true)),
+                &thresholds
+            ),
+            Decision::Stop {
+                start_stop: true,
+                end_trip: false
+            }
+        );
+        assert_eq!(
+            decide(
+                Some(&trip),
+                Some(&stop),
+                &position(14, 0.0, Some(true)),
+                &thresholds
+            ),
+            Decision::Stop {
+                start_stop: false,
+                end_trip: false
+            }
+        );
+    }
+
+    #[test]
+    fn continues_trips_in_memory() {
+        let mut trip = trip(0, 10);
+        trip.max_speed = Some(60.0);
+
+        let mut moved = position(11, 40.0, Some(true));
+        moved.lat = -23.501;
+
+        continue_trip(&mut trip, &moved);
+
+        assert_eq!(trip.ended_at, at(11));
+        assert_eq!((trip.end_lat, trip.end_lng), (-23.501, -46.6));
+        assert_eq!(trip.max_speed, Some(60.0));
+        assert!((trip.distance - 111.0).abs() < 1.0, "{}", trip.distance);
+        assert!(trip.in_progress);
+    }
+}
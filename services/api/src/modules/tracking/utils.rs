@@ -1,23 +1,247 @@
+use super::dto::TelemetryDto;
 use chrono::{DateTime, Utc};
 use geozero::wkb;
-use sea_orm::DatabaseConnection;
-use sqlx::postgres::PgQueryResult;
-
-pub async fn insert_vehicle_tracker_location(
-    db: &DatabaseConnection,
-    timestamp: DateTime<Utc>,
-    tracker_id: i32,
-    lat: f64,
-    lng: f64,
-) -> Result<PgQueryResult, sqlx::Error> {
-    let point: geo_types::Geometry<f64> = geo_types::Point::new(lat, lng).into();
-
-    sqlx::query(
-        "INSERT INTO vehicle_tracker_location (time, vehicle_tracker_id, point) VALUES ($1, $2, ST_SetSRID($3, 4326))",
-    )
-    .bind(timestamp)
-    .bind(tracker_id)
-    .bind(wkb::Encode(point))
-    .execute(db.get_postgres_connection_pool())
-    .await
+use sea_query::{Alias, Cond, Expr, Query as SeaQuery, SelectStatement};
+use shared::entity::vehicle_tracker_location;
+
+/// Meters per degree of latitude, to convert simplification tolerances to degrees
+const METERS_PER_DEGREE: f64 = 111_320.0;
+
+/// Whose locations a location history is of
+#[derive(Clone, Copy, Debug)]
+pub enum LocationOwner {
+    /// locations sent by the tracker, on any vehicle it was installed on
+    Tracker(i32),
+    /// locations sent while the vehicle had a tracker installed, from any of its trackers
+    Vehicle(i32),
+}
+
+impl std::fmt::Display for LocationOwner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LocationOwner::Tracker(id) => write!(f, "tracker {id}"),
+            LocationOwner::Vehicle(id) => write!(f, "vehicle {id}"),
+        }
+    }
+}
+
+/// Options to reduce the amount of locations of a location history, such as
+/// to draw long histories on a map with only as many points as needed
+#[derive(Clone, Copy, Default)]
+pub struct LocationSampling {
+    /// Douglas-Peucker tolerance in meters, locations that do not change the
+    /// shape of the route by more than the tolerance are skipped
+    pub simplify: Option<f64>,
+
+    /// Seconds of the time buckets to average the locations on, one location per bucket
+    pub bucket: Option<i32>,
+}
+
+/// A row of `vehicle_tracker_location` or `vehicle_tracker_last_location`
+#[derive(sqlx::FromRow)]
+pub struct LocationRow {
+    pub vehicle_tracker_id: i32,
+    pub time: DateTime<Utc>,
+    pub point: wkb::Decode<geo_types::Geometry<f64>>,
+    pub speed: Option<f64>,
+    pub heading: Option<i32>,
+    pub ignition: Option<bool>,
+    pub status: Option<serde_json::Value>,
+}
+
+impl LocationRow {
+    /// The `(lat, lng)` of the location, `None` if its geometry is not a point
+    ///
+    /// IMPORTANT: location points are stored with the latitude as the X coordinate
+    pub fn lat_lng(&self) -> Option<(f64, f64)> {
+        match self.point.geometry {
+            Some(geo_types::Geometry::Point(point)) => Some((point.x(), point.y())),
+            _ => None,
+        }
+    }
+
+    pub fn telemetry(&self) -> TelemetryDto {
+        TelemetryDto {
+            speed: self.speed,
+            heading: self.heading,
+            ignition: self.ignition,
+            status: self.status.clone(),
+        }
+    }
+}
+
+/// Mean radius of the earth in meters
+const EARTH_RADIUS: f64 = 6_371_008.8;
+
+/// Great circle distance in meters between two `(lat, lng)` coordinates, using the haversine formula
+pub fn distance_in_meters(from: (f64, f64), to: (f64, f64)) -> f64 {
+    let (from_lat, from_lng) = (from.0.to_radians(), from.1.to_radians());
+    let (to_lat, to_lng) = (to.0.to_radians(), to.1.to_radians());
+
+    let a = ((to_lat - from_lat) / 2.0).sin().powi(2)
+        + from_lat.cos() * to_lat.cos() * ((to_lng - from_lng) / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS * a.sqrt().asin()
+}
+
+/// Query of the locations of the `owner` within the `range` conditions on their time, selecting the
+/// columns of `LocationRow` from the `location` alias, so conditions, ordering and limits can be
+/// added to it.
+///
+/// bucketed locations are the average of the positions and speeds of the bucket, with the heading
+/// and status of its last location, simplification is applied over the whole range, so locations
+/// kept on a page of the range do not depend on the page
+pub fn location_history_query(
+    owner: LocationOwner,
+    range: Cond,
+    sampling: LocationSampling,
+) -> SelectStatement {
+    let owned = match owner {
+        LocationOwner::Tracker(id) => {
+            Expr::col(vehicle_tracker_location::Column::VehicleTrackerId).eq(id)
+        }
+        LocationOwner::Vehicle(id) => Expr::col(vehicle_tracker_location::Column::VehicleId).eq(id),
+    };
+
+    let range = Cond::all().add(owned).add(range);
+
+    let source = match sampling.bucket {
+        Some(bucket) => {
+            // the bucket size is a integer, so it is inlined instead of bound, as the grouped
+            // expression must be the same as the selected one, and each bind is a new parameter
+            let time_bucket = || {
+                Expr::cust(format!(
+                    r#"time_bucket(make_interval(secs => {bucket}), "time")"#
+                ))
+            };
+
+            SeaQuery::select()
+                .column(vehicle_tracker_location::Column::VehicleTrackerId)
+                .expr_as(time_bucket(), Alias::new("time"))
+                .expr_as(
+                    Expr::cust(
+                        r#"ST_SetSRID(ST_MakePoint(avg(ST_X("point")), avg(ST_Y("point"))), 4326)"#,
+                    ),
+                    Alias::new("point"),
+                )
+                .expr_as(Expr::cust(r#"avg("speed")"#), Alias::new("speed"))
+                .expr_as(
+                    Expr::cust(r#"last("heading", "time")"#),
+                    Alias::new("heading"),
+                )
+                .expr_as(Expr::cust(r#"bool_or("ignition")"#), Alias::new("ignition"))
+                .expr_as(
+                    Expr::cust(r#"last("status", "time")"#),
+                    Alias::new("status"),
+                )
+                .from(vehicle_tracker_location::Entity)
+                .cond_where(range)
+                .group_by_col(vehicle_tracker_location::Column::VehicleTrackerId)
+                .add_group_by([time_bucket()])
+                .to_owned()
+        }
+        None => SeaQuery::select()
+            .columns([
+                vehicle_tracker_location::Column::VehicleTrackerId,
+                vehicle_tracker_location::Column::Time,
+                vehicle_tracker_location::Column::Point,
+                vehicle_tracker_location::Column::Speed,
+                vehicle_tracker_location::Column::Heading,
+                vehicle_tracker_location::Column::Ignition,
+                vehicle_tracker_location::Column::Status,
+            ])
+            .from(vehicle_tracker_location::Entity)
+            .cond_where(range)
+            .to_owned(),
+    };
+
+    let mut query = SeaQuery::select()
+        .columns([
+            vehicle_tracker_location::Column::VehicleTrackerId,
+            vehicle_tracker_location::Column::Time,
+            vehicle_tracker_location::Column::Point,
+            vehicle_tracker_location::Column::Speed,
+            vehicle_tracker_location::Column::Heading,
+            vehicle_tracker_location::Column::Ignition,
+            vehicle_tracker_location::Column::Status,
+        ])
+        .from_subquery(source.clone(), Alias::new("location"))
+        .to_owned();
+
+    if let Some(meters) = sampling.simplify {
+        // the time of each location is kept as the M coordinate of the route vertices,
+        // since the simplified route only has the vertices of the locations to keep
+        let kept_times = SeaQuery::select()
+            .expr(Expr::cust_with_values(
+                r#"to_timestamp(ST_M((ST_DumpPoints(ST_Simplify(ST_MakeLine(ST_MakePointM(ST_X("point"), ST_Y("point"), extract(epoch FROM "time")) ORDER BY "time"), $1, true))).geom))"#,
+                [meters / METERS_PER_DEGREE],
+            ))
+            .from_subquery(source, Alias::new("route"))
+            .to_owned();
+
+        query.and_where(Expr::col(vehicle_tracker_location::Column::Time).in_subquery(kept_times));
+    }
+
+    query
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{location_history_query, LocationOwner, LocationSampling};
+    use sea_query::{Cond, PostgresQueryBuilder};
+    use sea_query_binder::SqlxBinder;
+
+    #[test]
+    fn groups_buckets_by_the_selected_expression() {
+        let sampling = LocationSampling {
+            simplify: None,
+            bucket: Some(300),
+        };
+
+        let (sql, _) = location_history_query(LocationOwner::Tracker(1), Cond::all(), sampling)
+            .build_sqlx(PostgresQueryBuilder);
+
+        let bucket = r#"time_bucket(make_interval(secs => 300), "time")"#;
+
+        assert_eq!(sql.matches(bucket).count(), 2, "{sql}");
+        assert!(sql.contains(&format!(r#"{bucket} AS "time""#)), "{sql}");
+        assert!(
+            sql.ends_with(&format!(
+                r#"GROUP BY "vehicle_tracker_id", {bucket}) AS "location""#
+            )),
+            "{sql}"
+        );
+        assert!(!sql.contains("make_interval(secs => $"), "{sql}");
+    }
+
+    #[test]
+    fn binds_the_simplify_tolerance() {
+        let sampling = LocationSampling {
+            simplify: Some(111.32),
+            bucket: Some(60),
+        };
+
+        let (sql, values) =
+            location_history_query(LocationOwner::Tracker(1), Cond::all(), sampling)
+                .build_sqlx(PostgresQueryBuilder);
+
+        assert!(sql.contains("ST_Simplify(ST_MakeLine("), "{sql}");
+        assert!(sql.contains(r#""vehicle_tracker_id" = $"#), "{sql}");
+
+        // the tracker id of the location and route subqueries, and the tolerance
+        assert_eq!(values.0 .0.len(), 3, "{sql}");
+    }
+
+    #[test]
+    fn selects_vehicle_locations_from_any_tracker() {
+        let (sql, _) = location_history_query(
+            LocationOwner::Vehicle(1),
+            Cond::all(),
+            LocationSampling::default(),
+        )
+        .build_sqlx(PostgresQueryBuilder);
+
+        assert!(sql.contains(r#"WHERE "vehicle_id" = $1"#), "{sql}");
+        assert!(!sql.contains(r#""vehicle_tracker_id" = $"#), "{sql}");
+    }
 }
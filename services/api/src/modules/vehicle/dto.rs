@@ -1,9 +1,17 @@
 use crate::modules::common::validators::REGEX_IS_MERCOSUL_OR_BR_VEHICLE_PLATE;
 use axum::body::Bytes;
 use axum_typed_multipart::{FieldData, TryFromMultipart};
-use serde::Deserialize;
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use shared::entity;
 use utoipa::{IntoParams, ToSchema};
-use validator::Validate;
+use validator::{Validate, ValidationError};
+
+/// Maximum amount of days of trips that can be listed at once
+const MAX_TRIPS_RANGE_DAYS: i64 = 31;
+
+/// Maximum amount of days that can be summarized at once
+const MAX_SUMMARY_RANGE_DAYS: i64 = 366;
 
 #[derive(Deserialize, IntoParams, Validate)]
 #[serde(rename_all = "camelCase")]
@@ -74,3 +82,219 @@ pub struct UpdateVehicleDto {
     #[serde(default, with = "::serde_with::rust::double_option")]
     pub fabrication_year: Option<Option<i16>>,
 }
+
+fn is_valid_trips_range(dto: &ListVehicleTripsDto) -> Result<(), ValidationError> {
+    if dto.to <= dto.from {
+        return Err(ValidationError::new("to must be after from"));
+    }
+
+    if dto.to - dto.from > Duration::days(MAX_TRIPS_RANGE_DAYS) {
+        return Err(ValidationError::new("range must be of at most 31 days"));
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+#[validate(schema(function = "is_valid_trips_range"))]
+pub struct ListVehicleTripsDto {
+    /// List trips and stops that ended after a timestamp
+    pub from: DateTime<Utc>,
+
+    /// List trips and stops that started before a timestamp
+    pub to: DateTime<Utc>,
+}
+
+/// The trips of a vehicle and the stops between them, oldest first
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VehicleTripsDto {
+    pub trips: Vec<entity::trip::Model>,
+
+    pub stops: Vec<entity::stop::Model>,
+}
+
+fn is_valid_summary_range(dto: &GetVehicleSummaryDto) -> Result<(), ValidationError> {
+    if dto.to < dto.from {
+        return Err(ValidationError::new("to must not be before from"));
+    }
+
+    if dto.to - dto.from >= Duration::days(MAX_SUMMARY_RANGE_DAYS) {
+        return Err(ValidationError::new("range must be of at most 366 days"));
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize, IntoParams, Validate)]
+#[serde(rename_all = "camelCase")]
+#[into_params(parameter_in = Query)]
+#[validate(schema(function = "is_valid_summary_range"))]
+pub struct GetVehicleSummaryDto {
+    /// First day to summarize, days are in UTC
+    pub from: NaiveDate,
+
+    /// Last day to summarize, inclusive
+    pub to: NaiveDate,
+}
+
+/// Driving summary of a vehicle on a UTC day, from the locations of its trackers
+#[derive(Serialize, ToSchema, sqlx::FromRow)]
+#[serde(rename_all = "camelCase")]
+pub struct DailySummaryDto {
+    pub day: DateTime<Utc>,
+
+    /// distance driven in meters
+    pub distance: f64,
+
+    /// highest speed in km/h
+    pub max_speed: Option<f64>,
+
+    /// average speed of the locations in km/h
+    pub avg_speed: Option<f64>,
+
+    /// time with the ignition on in seconds
+    pub ignition_seconds: i64,
+
+    /// amount of locations sent by the tracker
+    pub positions: i64,
+}
+
+/// Driving summary of a vehicle on a range of days
+#[derive(Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VehicleSummaryDto {
+    pub from: NaiveDate,
+
+    pub to: NaiveDate,
+
+    /// distance driven in meters
+    pub distance: f64,
+
+    /// highest speed in km/h
+    pub max_speed: Option<f64>,
+
+    /// average speed of the locations in km/h
+    pub avg_speed: Option<f64>,
+
+    /// time with the ignition on in seconds
+    pub ignition_seconds: i64,
+
+    /// amount of locations sent by the tracker
+    pub positions: i64,
+
+    /// current odometer of the vehicle in meters
+    pub odometer: f64,
+}
+
+impl VehicleSummaryDto {
+    pub fn from_days(dto: &GetVehicleSummaryDto, days: &[DailySummaryDto], odometer: f64) -> Self {
+        let positions: i64 = days.iter().map(|d| d.positions).sum();
+
+        // the average of each day is weighted by its amount of locations
+        let speed_sum: f64 = days
+            .iter()
+            .filter_map(|d| d.avg_speed.map(|avg| avg * d.positions as f64))
+            .sum();
+
+        let speed_positions: i64 = days
+            .iter()
+            .filter(|d| d.avg_speed.is_some())
+            .map(|d| d.positions)
+            .sum();
+
+        VehicleSummaryDto {
+            from: dto.from,
+            to: dto.to,
+            distance: days.iter().map(|d| d.distance).sum(),
+            max_speed: days.iter().filter_map(|d| d.max_speed).reduce(f64::max),
+            avg_speed: (speed_positions > 0).then(|| speed_sum / speed_positions as f64),
+            ignition_seconds: days.iter().map(|d| d.ignition_seconds).sum(),
+            positions,
+            odometer,
+        }
+    }
+}
+
+#[derive(Deserialize, ToSchema, Validate)]
+#[serde(rename_all = "camelCase")]
+pub struct CalibrateOdometerDto {
+    /// odometer of the vehicle in meters, as shown on its dashboard
+    #[validate(range(min = 0.0, max = 100000000.0))]
+    pub odometer: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DailySummaryDto, GetVehicleSummaryDto, VehicleSummaryDto};
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    fn day(day: u32, distance: f64, speeds: Option<(f64, f64)>, positions: i64) -> DailySummaryDto {
+        DailySummaryDto {
+            day: Utc.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap(),
+            distance,
+            max_speed: speeds.map(|(max, _)| max),
+            avg_speed: speeds.map(|(_, avg)| avg),
+            ignition_seconds: positions * 10,
+            positions,
+        }
+    }
+
+    fn range() -> GetVehicleSummaryDto {
+        GetVehicleSummaryDto {
+            from: NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            to: NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+        }
+    }
+
+    #[test]
+    fn sums_the_days() {
+        let days = [
+            day(1, 1000.0, Some((80.0, 40.0)), 10),
+            day(2, 500.5, Some((120.0, 60.0)), 30),
+        ];
+
+        let summary = VehicleSummaryDto::from_days(&range(), &days, 12345.0);
+
+        assert_eq!(summary.from, range().from);
+        assert_eq!(summary.to, range().to);
+        assert_eq!(summary.distance, 1500.5);
+        assert_eq!(summary.max_speed, Some(120.0));
+        assert_eq!(summary.ignition_seconds, 400);
+        assert_eq!(summary.positions, 40);
+        assert_eq!(summary.odometer, 12345.0);
+    }
+
+    #[test]
+    fn weights_the_average_speed_by_the_amount_of_locations() {
+        let days = [
+            day(1, 0.0, Some((80.0, 40.0)), 10),
+            day(2, 0.0, Some((120.0, 60.0)), 30),
+            day(3, 0.0, None, 60),
+        ];
+
+        let summary = VehicleSummaryDto::from_days(&range(), &days, 0.0);
+
+        assert_eq!(summary.avg_speed, Some(55.0));
+        assert_eq!(summary.positions, 100);
+    }
+
+    #[test]
+    fn summarizes_ranges_without_speeds_or_days() {
+        let summary = VehicleSummaryDto::from_days(&range(), &[day(1, 0.0, None, 5)], 0.0);
+
+        assert_eq!(summary.max_speed, None);
+        assert_eq!(summary.avg_speed, None);
+
+        let summary = VehicleSummaryDto::from_days(&range(), &[], 10.0);
+
+        assert_eq!(summary.distance, 0.0);
+        assert_eq!(summary.max_speed, None);
+        assert_eq!(summary.avg_speed, None);
+        assert_eq!(summary.ignition_seconds, 0);
+        assert_eq!(summary.positions, 0);
+        assert_eq!(summary.odometer, 10.0);
+    }
+}
@@ -1,5 +1,6 @@
-use super::dto::CreateVehicleDto;
+use super::dto::{CreateVehicleDto, DailySummaryDto};
 use crate::database::error::DbError;
+use chrono::{DateTime, NaiveDate, Utc};
 use sea_orm::{ActiveModelTrait, DatabaseConnection, Set};
 use shared::entity::vehicle;
 
@@ -23,3 +24,40 @@ pub async fn create_vehicle(
 
     Ok(vehicle.insert(conn).await?)
 }
+
+/// Daily summaries of the locations sent while a tracker was installed on the vehicle, from
+/// the `vehicle_tracker_daily_summary` continuous aggregate, summing the days of every tracker
+/// the vehicle had, days without locations are not included
+pub async fn get_daily_summaries(
+    conn: &DatabaseConnection,
+    vehicle_id: i32,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> Result<Vec<DailySummaryDto>, sqlx::Error> {
+    let from: DateTime<Utc> = from.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    let to: DateTime<Utc> = to.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+
+    // the average speed of each tracker is weighted by its amount of locations
+    sqlx::query_as::<_, DailySummaryDto>(
+        r#"
+        SELECT
+            "day",
+            sum("distance") AS "distance",
+            max("max_speed") AS "max_speed",
+            sum("avg_speed" * "positions") / nullif(sum("positions") FILTER (WHERE "avg_speed" IS NOT NULL), 0) AS "avg_speed",
+            sum("ignition_seconds")::bigint AS "ignition_seconds",
+            sum("positions")::bigint AS "positions"
+        FROM "vehicle_tracker_daily_summary"
+        WHERE "vehicle_id" = $1
+        AND "day" >= $2
+        AND "day" <= $3
+        GROUP BY "day"
+        ORDER BY "day" ASC
+        "#,
+    )
+    .bind(vehicle_id)
+    .bind(from)
+    .bind(to)
+    .fetch_all(conn.get_postgres_connection_pool())
+    .await
+}
@@ -1,4 +1,7 @@
-use super::dto::{CreateVehicleDto, ListVehiclesDto, UpdateVehicleDto};
+use super::dto::{
+    CalibrateOdometerDto, CreateVehicleDto, DailySummaryDto, GetVehicleSummaryDto,
+    ListVehicleTripsDto, ListVehiclesDto, UpdateVehicleDto, VehicleSummaryDto, VehicleTripsDto,
+};
 use crate::{
     database::{
         error::DbError,
@@ -13,14 +16,16 @@ use crate::{
                 ValidatedMultipart, ValidatedQuery,
             },
             multipart_form_data,
-            responses::{internal_error_msg, SimpleError},
+            responses::{internal_error_msg, internal_error_res, SimpleError},
         },
+        tracking::{dto::ExportLocationsDto, export, utils::LocationOwner},
         vehicle::repository,
     },
     server::controller::AppState,
     services::s3::S3Key,
 };
 use axum::extract::{Path, State};
+use axum::response::Response;
 use axum::{
     routing::{delete, get, post, put},
     Json, Router,
@@ -30,10 +35,10 @@ use http::StatusCode;
 use migration::{extension::postgres::PgExpr, Expr};
 use sea_orm::{
     ActiveModelTrait, ColumnTrait, EntityTrait, ModelTrait, PaginatorTrait, QueryFilter,
-    QueryOrder, QueryTrait,
+    QueryOrder, QueryTrait, Set,
 };
 use shared::constants::Permission;
-use shared::entity::{vehicle, vehicle_tracker};
+use shared::entity::{stop, trip, vehicle, vehicle_tracker};
 
 pub fn create_router(state: AppState) -> Router<AppState> {
     Router::new()
@@ -58,6 +63,26 @@ pub fn create_router(state: AppState) -> Router<AppState> {
         //
         .route("/:vehicle_id/tracker", get(get_vehicle_tracker))
         //
+        .route("/:vehicle_id/trips", get(list_vehicle_trips))
+        //
+        .route(
+            "/:vehicle_id/export-locations",
+            get(export_vehicle_locations),
+        )
+        //
+        .route("/:vehicle_id/summary", get(get_vehicle_summary))
+        //
+        .route(
+            "/:vehicle_id/summary/daily",
+            get(list_vehicle_daily_summaries),
+        )
+        //
+        .route(
+            "/:vehicle_id/odometer",
+            put(calibrate_vehicle_odometer)
+                .route_layer(AclLayer::single(Permission::UpdateVehicle)),
+        )
+        //
         .route(
             "/:vehicle_id/photo",
             put(update_vehicle_photo).route_layer(AclLayer::single(Permission::UpdateVehicle)),
@@ -97,6 +122,199 @@ pub async fn vehicle_by_id(
     Ok(Json(v))
 }
 
+/// Lists the trips of a vehicle and the stops between them within a time range,
+/// trips and stops are detected from the positions sent by the vehicle trackers
+#[utoipa::path(
+    get,
+    tag = "vehicle",
+    path = "/vehicle/{vehicle_id}/trips",
+    security(("session_id" = [])),
+    params(
+        ListVehicleTripsDto,
+        ("vehicle_id" = u128, Path, description = "id of the vehicle to list the trips"),
+    ),
+    responses(
+        (
+            status = OK,
+            content_type = "application/json",
+            body = VehicleTripsDto,
+        ),
+        (
+            status = BAD_REQUEST,
+            description = "invalid dto error message",
+            body = SimpleError,
+        ),
+    ),
+)]
+pub async fn list_vehicle_trips(
+    ValidatedQuery(range): ValidatedQuery<ListVehicleTripsDto>,
+    DbConnection(db): DbConnection,
+    OrgBoundEntityFromPathId(v): OrgBoundEntityFromPathId<vehicle::Entity>,
+) -> Result<Json<VehicleTripsDto>, (StatusCode, SimpleError)> {
+    let trips = trip::Entity::find()
+        .filter(trip::Column::VehicleId.eq(v.id))
+        .filter(trip::Column::StartedAt.lte(range.to))
+        .filter(trip::Column::EndedAt.gte(range.from))
+        .order_by_asc(trip::Column::StartedAt)
+        .all(&db)
+        .await
+        .map_err(DbError::from)?;
+
+    let stops = stop::Entity::find()
+        .filter(stop::Column::VehicleId.eq(v.id))
+        .filter(stop::Column::StartedAt.lte(range.to))
+        .filter(stop::Column::EndedAt.gte(range.from))
+        .order_by_asc(stop::Column::StartedAt)
+        .all(&db)
+        .await
+        .map_err(DbError::from)?;
+
+    Ok(Json(VehicleTripsDto { trips, stops }))
+}
+
+/// Lists the daily driving summaries of a vehicle, days are in UTC and days
+/// without any location sent by the vehicle tracker are not listed
+#[utoipa::path(
+    get,
+    tag = "vehicle",
+    path = "/vehicle/{vehicle_id}/summary/daily",
+    security(("session_id" = [])),
+    params(
+        GetVehicleSummaryDto,
+        ("vehicle_id" = u128, Path, description = "id of the vehicle to summarize"),
+    ),
+    responses(
+        (
+            status = OK,
+            content_type = "application/json",
+            body = Vec<DailySummaryDto>,
+        ),
+        (
+            status = BAD_REQUEST,
+            description = "invalid dto error message",
+            body = SimpleError,
+        ),
+    ),
+)]
+pub async fn list_vehicle_daily_summaries(
+    ValidatedQuery(range): ValidatedQuery<GetVehicleSummaryDto>,
+    DbConnection(db): DbConnection,
+    OrgBoundEntityFromPathId(v): OrgBoundEntityFromPathId<vehicle::Entity>,
+) -> Result<Json<Vec<DailySummaryDto>>, (StatusCode, SimpleError)> {
+    let days = repository::get_daily_summaries(&db, v.id, range.from, range.to)
+        .await
+        .map_err(|_| internal_error_res())?;
+
+    Ok(Json(days))
+}
+
+/// Get the driving summary of a vehicle on a range of days, along with its odometer
+#[utoipa::path(
+    get,
+    tag = "vehicle",
+    path = "/vehicle/{vehicle_id}/summary",
+    security(("session_id" = [])),
+    params(
+        GetVehicleSummaryDto,
+        ("vehicle_id" = u128, Path, description = "id of the vehicle to summarize"),
+    ),
+    responses(
+        (
+            status = OK,
+            content_type = "application/json",
+            body = VehicleSummaryDto,
+        ),
+        (
+            status = BAD_REQUEST,
+            description = "invalid dto error message",
+            body = SimpleError,
+        ),
+    ),
+)]
+pub async fn get_vehicle_summary(
+    ValidatedQuery(range): ValidatedQuery<GetVehicleSummaryDto>,
+    DbConnection(db): DbConnection,
+    OrgBoundEntityFromPathId(v): OrgBoundEntityFromPathId<vehicle::Entity>,
+) -> Result<Json<VehicleSummaryDto>, (StatusCode, SimpleError)> {
+    let days = repository::get_daily_summaries(&db, v.id, range.from, range.to)
+        .await
+        .map_err(|_| internal_error_res())?;
+
+    Ok(Json(VehicleSummaryDto::from_days(
+        &range, &days, v.odometer,
+    )))
+}
+
+/// Calibrate a vehicle odometer, setting it to the distance shown on the vehicle
+/// dashboard, from then on it increases with the distance between its locations
+///
+/// Required permissions: UPDATE_VEHICLE
+#[utoipa::path(
+    put,
+    tag = "vehicle",
+    path = "/vehicle/{vehicle_id}/odometer",
+    security(("session_id" = [])),
+    params(
+        ("vehicle_id" = u128, Path, description = "id of the vehicle to calibrate"),
+    ),
+    request_body(content = CalibrateOdometerDto, content_type = "application/json"),
+    responses(
+        (
+            status = OK,
+            content_type = "application/json",
+            body = entity::vehicle::Model,
+        ),
+    ),
+)]
+pub async fn calibrate_vehicle_odometer(
+    DbConnection(db): DbConnection,
+    OrgBoundEntityFromPathId(vehicle): OrgBoundEntityFromPathId<vehicle::Entity>,
+    ValidatedJson(dto): ValidatedJson<CalibrateOdometerDto>,
+) -> Result<Json<vehicle::Model>, (StatusCode, SimpleError)> {
+    let mut v: vehicle::ActiveModel = vehicle.into();
+
+    v.odometer = Set(dto.odometer);
+
+    let updated_vehicle = v.update(&db).await.map_err(DbError::from)?;
+
+    Ok(Json(updated_vehicle))
+}
+
+/// Export the locations sent by the trackers installed on a vehicle within a time range
+/// as a file, the file is streamed so exports of long ranges start right away
+#[utoipa::path(
+    get,
+    tag = "vehicle",
+    path = "/vehicle/{vehicle_id}/export-locations",
+    security(("session_id" = [])),
+    params(
+        ExportLocationsDto,
+        ("vehicle_id" = u128, Path, description = "id of the vehicle"),
+    ),
+    responses(
+        (
+            status = OK,
+            description = "the locations in the requested format, as a attachment",
+            body = String,
+            content_type = ["application/gpx+xml", "application/vnd.google-earth.kml+xml", "application/geo+json", "text/csv"],
+        ),
+        (
+            status = BAD_REQUEST,
+            description = "invalid dto error message",
+            body = SimpleError,
+        ),
+    ),
+)]
+pub async fn export_vehicle_locations(
+    OrgBoundEntityFromPathId(v): OrgBoundEntityFromPathId<vehicle::Entity>,
+    DbConnection(db): DbConnection,
+    ValidatedQuery(dto): ValidatedQuery<ExportLocationsDto>,
+) -> Response {
+    let filename = format!("vehicle-{}-locations", v.id);
+
+    export::export_locations(&db, LocationOwner::Vehicle(v.id), v.plate, filename, dto)
+}
+
 /// Get a vehicle tracker
 #[utoipa::path(
     get,
@@ -174,6 +174,23 @@ impl Rmq {
         );
         println!("[RMQ] tracker events queue declared");
 
+        panic_on_err(
+            publish_channel
+                .queue_declare(
+                    shared::constants::rabbitmq::TRACKER_COMMANDS_QUEUE,
+                    QueueDeclareOptions {
+                        passive: false,
+                        durable: true,
+                        exclusive: false,
+                        auto_delete: false,
+                        nowait: false,
+                    },
+                    FieldTable::default(),
+                )
+                .await,
+        );
+        println!("[RMQ] tracker commands queue declared");
+
         // bind the tracker events queue to the tracker events exchange and listen to all events (#)
         publish_channel
             .queue_bind(
@@ -1,15 +1,16 @@
 use super::open_api;
 use crate::{
     config::app_config,
+    cronjobs,
     modules::{
         access_level,
         auth::{self, service::AuthService},
-        organization, sim_card, tracker,
+        geofence, organization, sim_card, tracker,
         tracking::{self},
         user, vehicle,
     },
     rabbitmq::Rmq,
-    services::{mailer::service::MailerService, s3::S3},
+    services::{decoder::DecoderService, mailer::service::MailerService, s3::S3},
     utils::string::StringExt,
 };
 use axum::{body::Body, routing::get, Router};
@@ -18,7 +19,7 @@ use http::{header, HeaderValue, Method, Request, StatusCode};
 use rand_chacha::ChaCha8Rng;
 use rand_core::{OsRng, RngCore, SeedableRng};
 use sea_orm::DatabaseConnection;
-use std::sync::Arc;
+use std::{sync::Arc, time::Duration};
 use tower::ServiceBuilder;
 use tower_http::{
     cors::CorsLayer,
@@ -35,6 +36,7 @@ pub struct AppState {
     pub db: DatabaseConnection,
     pub auth_service: AuthService,
     pub mailer_service: MailerService,
+    pub decoder_service: DecoderService,
 }
 
 /// Creates the main axum router/controller to be served over https
@@ -47,7 +49,8 @@ pub fn new(db: DatabaseConnection, s3: S3, rmq: Arc<Rmq>) -> Router {
         s3,
         db: db.clone(),
         auth_service: AuthService::new(db.clone(), rng),
-        mailer_service: MailerService::new(rmq),
+        mailer_service: MailerService::new(rmq.clone()),
+        decoder_service: DecoderService::new(rmq),
     };
 
     let (socket_io_layer, socket_io) = socketioxide::SocketIo::builder()
@@ -56,6 +59,12 @@ pub fn new(db: DatabaseConnection, s3: S3, rmq: Arc<Rmq>) -> Router {
 
     socket_io.ns("/tracking", tracking::routes::on_connect);
 
+    cronjobs::start_notify_offline_trackers_cronjob(
+        db.clone(),
+        socket_io.clone(),
+        Duration::from_secs(60),
+    );
+
     tracking::background::start_positions_consumer(positions_consumer_rmq, socket_io, db);
 
     // URL.to_string for some reason adds a trailing slash
@@ -105,6 +114,7 @@ pub fn new(db: DatabaseConnection, s3: S3, rmq: Arc<Rmq>) -> Router {
         .nest("/vehicle", vehicle::routes::create_router(state.clone()))
         .nest("/sim-card", sim_card::routes::create_router(state.clone()))
         .nest("/tracker", tracker::routes::create_router(state.clone()))
+        .nest("/geofence", geofence::routes::create_router(state.clone()))
         .nest("/tracking", tracking::routes::create_router(state.clone()))
         .nest(
             "/access-level",
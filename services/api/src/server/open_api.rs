@@ -1,4 +1,4 @@
-use crate::modules::{auth, common, user, organization, vehicle, tracker, sim_card, access_level, tracking};
+use crate::modules::{auth, common, user, organization, vehicle, tracker, sim_card, access_level, tracking, geofence};
 use crate::server::controller;
 use utoipa::openapi::security::{ApiKey, ApiKeyValue, SecurityScheme};
 use utoipa::openapi::{ContactBuilder, InfoBuilder};
@@ -12,15 +12,33 @@ use shared::entity;
 #[openapi(
     components(schemas(
         shared::constants::TrackerModel,
+        shared::constants::TrackerCommandStatus,
+        shared::constants::TrackerAlarmType,
+        shared::constants::TrackerConnectivity,
+        shared::constants::GeofenceShape,
+        shared::constants::GeofenceEventType,
+        shared::constants::PositionFilterReason,
 
         entity::vehicle::Model,
         entity::sim_card::Model,
         entity::vehicle_tracker::Model,
+        entity::tracker_command::Model,
+        entity::tracker_alarm::Model,
+        entity::geofence::Model,
+        entity::geofence_event::Model,
+        entity::trip::Model,
+        entity::stop::Model,
+        entity::position_filter_decision::Model,
         
         common::dto::PaginatedUser,
         common::dto::PaginatedSimCard,
         common::dto::PaginatedVehicle,
         common::dto::PaginatedVehicleTracker,
+        common::dto::PaginatedTrackerCommand,
+        common::dto::PaginatedTrackerAlarm,
+        common::dto::PaginatedGeofence,
+        common::dto::PaginatedGeofenceEvent,
+        common::dto::PaginatedPositionFilterDecision,
 
         common::dto::Token,
         common::dto::EmailAddress,
@@ -44,17 +62,34 @@ use shared::entity;
 
         vehicle::dto::CreateVehicleDto,
         vehicle::dto::UpdateVehicleDto,
+        vehicle::dto::VehicleTripsDto,
+        vehicle::dto::DailySummaryDto,
+        vehicle::dto::VehicleSummaryDto,
+        vehicle::dto::CalibrateOdometerDto,
         
         tracker::dto::Point,
         tracker::dto::UpdateTrackerDto,
         tracker::dto::CreateTrackerDto,
         tracker::dto::TrackerLocationDto,
+        tracker::dto::TrackerLocationListDto,
         tracker::dto::SetTrackerVehicleDto,
         tracker::dto::GetTrackerPositionsDto,
+        tracker::dto::SendTrackerCommandDto,
+        tracker::dto::AcknowledgeTrackerAlarmDto,
+        tracker::dto::TrackerDto,
 
         tracking::dto::PositionDto,
+        tracking::dto::TelemetryDto,
         tracking::dto::GetTrackersLastPositionsDto,
+        tracking::dto::TrackerStatusDto,
+        tracking::dto::ExportFormat,
         
+        geofence::dto::CoordinateDto,
+        geofence::dto::GeofenceShapeDto,
+        geofence::dto::CreateGeofenceDto,
+        geofence::dto::UpdateGeofenceDto,
+        geofence::dto::SetGeofenceVehiclesDto,
+
         sim_card::dto::CreateSimCardDto,
         sim_card::dto::UpdateSimCardDto,
         sim_card::dto::SetSimCardTrackerDto,
@@ -98,6 +133,11 @@ use shared::entity;
         vehicle::routes::update_vehicle,
         vehicle::routes::delete_vehicle,
         vehicle::routes::get_vehicle_tracker,
+        vehicle::routes::list_vehicle_trips,
+        vehicle::routes::export_vehicle_locations,
+        vehicle::routes::get_vehicle_summary,
+        vehicle::routes::list_vehicle_daily_summaries,
+        vehicle::routes::calibrate_vehicle_odometer,
         vehicle::routes::update_vehicle_photo,
         vehicle::routes::delete_vehicle_photo,
         
@@ -117,6 +157,21 @@ use shared::entity;
         tracker::routes::get_tracker_location,
         tracker::routes::list_tracker_sim_cards,
         tracker::routes::get_location_list,
+        tracker::routes::export_tracker_locations,
+        tracker::routes::send_tracker_command,
+        tracker::routes::list_tracker_commands,
+        tracker::routes::list_tracker_alarms,
+        tracker::routes::acknowledge_tracker_alarm,
+        tracker::routes::list_tracker_filtered_positions,
+
+        geofence::routes::list_geofences,
+        geofence::routes::create_geofence,
+        geofence::routes::geofence_by_id,
+        geofence::routes::update_geofence,
+        geofence::routes::delete_geofence,
+        geofence::routes::list_geofence_vehicles,
+        geofence::routes::set_geofence_vehicles,
+        geofence::routes::list_geofence_events,
 
 
         tracking::routes::get_trackers_last_positions,
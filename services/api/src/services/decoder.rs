@@ -0,0 +1,45 @@
+use crate::rabbitmq::Rmq;
+use anyhow::Result;
+use lapin::{
+    options::BasicPublishOptions, publisher_confirm::PublisherConfirm, types::FieldTable,
+    BasicProperties,
+};
+use shared::dto::decoder::command::TrackerCommand;
+use std::sync::Arc;
+use tracing::Span;
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+/// A abstraction to send commands to trackers through the decoder microservice
+#[derive(Clone)]
+pub struct DecoderService {
+    rmq: Arc<Rmq>,
+}
+
+impl DecoderService {
+    pub fn new(rmq: Arc<Rmq>) -> DecoderService {
+        DecoderService { rmq }
+    }
+
+    /// Publishes the command to the tracker commands queue, the command status is
+    /// received later as `command_sent`, `command_failed` and `command_ack` tracker events
+    #[tracing::instrument(skip_all)]
+    pub async fn send_command(&self, command: &TrackerCommand) -> Result<PublisherConfirm> {
+        let span = Span::current();
+        let ctx = span.context();
+
+        let amqp_headers = shared::tracer::create_amqp_headers_with_span_ctx(&ctx);
+
+        Ok(self
+            .rmq
+            .publish(
+                shared::constants::rabbitmq::DEFAULT_EXCHANGE,
+                shared::constants::rabbitmq::TRACKER_COMMANDS_QUEUE,
+                BasicPublishOptions::default(),
+                serde_json::to_string(command)?.as_bytes(),
+                BasicProperties::default()
+                    .with_content_type("application/json".into())
+                    .with_headers(FieldTable::from(amqp_headers)),
+            )
+            .await?)
+    }
+}
@@ -1,2 +1,3 @@
+pub mod decoder;
 pub mod mailer;
 pub mod s3;
@@ -0,0 +1,181 @@
+use crate::config;
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use crate::protocols::{gt06, h02, teltonika};
+use crate::server::connections;
+use crate::server::listeners::{send_event, RmqMsgSender};
+use chrono::Utc;
+use lapin::{
+    options::{BasicAckOptions, BasicConsumeOptions, QueueDeclareOptions},
+    types::FieldTable,
+    Connection, ConnectionProperties,
+};
+use shared::dto::decoder::command::{CommandFailedMsg, CommandSentMsg, TrackerCommand};
+use std::{str::FromStr, time::Duration};
+use tokio::io::AsyncWriteExt;
+use tokio_stream::StreamExt;
+
+/// Time to wait between attempts to reconnect to RabbitMQ
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Time to wait for a command to be written to the tracker connection
+const WRITE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Encodes the command to the packet to send to a tracker of the protocol
+fn encode(protocol: Protocol, command: &TrackerCommand) -> Result<Box<[u8]>, String> {
+    match protocol {
+        Protocol::H02 => Ok(h02::command::encode(
+            &command.imei,
+            &command.command,
+            Utc::now(),
+        )),
+        // the command id is sent as the server flag, echoed back on the tracker response
+        Protocol::Gt06 => gt06::command::encode(&command.command, command.id as u32, 0),
+        Protocol::Teltonika => teltonika::command::encode(&command.command),
+    }
+}
+
+/// Writes the command to the live connection of the tracker
+async fn send_command(protocol: Protocol, command: &TrackerCommand) -> Result<(), String> {
+    let connection = connections::get(&command.imei)
+        .await
+        .ok_or("tracker not connected")?;
+
+    if connection.protocol != protocol {
+        return Err(format!(
+            "tracker connected with the {} protocol",
+            connection.protocol
+        ));
+    }
+
+    let packet = encode(protocol, command)?;
+
+    let mut writer = connection.writer.lock().await;
+
+    match tokio::time::timeout(WRITE_TIMEOUT, writer.write_all(&packet)).await {
+        Ok(Ok(())) => Ok(()),
+        Ok(Err(err)) => Err(format!("failed to write command: {}", err)),
+        Err(_) => Err("timed out writing command".to_string()),
+    }
+}
+
+/// A consumer of the tracker commands queue, sending the commands to the live
+/// connections of the trackers and publishing if each command was sent or failed
+/// as a tracker event, the tracker acknowledgement of the command is published
+/// as a `command_ack` event once received.
+///
+/// Commands can only be sent to trackers connected over TCP to this instance.
+pub struct CommandsConsumer {
+    rmq_uri: String,
+
+    tracker_commands_queue: String,
+
+    /// sender to publish the command events to the tracker events exchange
+    sender: RmqMsgSender,
+}
+
+impl CommandsConsumer {
+    pub fn new(cfg: &config::AppConfig, sender: RmqMsgSender) -> CommandsConsumer {
+        CommandsConsumer {
+            rmq_uri: cfg.rmq_uri.to_owned(),
+            tracker_commands_queue: cfg.tracker_commands_queue.to_owned(),
+            sender,
+        }
+    }
+
+    /// Starts a infinite loop that will attempt to recconect to
+    /// RabbitMQ, once a connection is stablished calls `self.run`
+    pub async fn start(&self) {
+        loop {
+            if let Err(err) = self.run().await {
+                println!("[CMD] connection error: {}", err)
+            }
+
+            tokio::time::sleep(RECONNECT_INTERVAL).await;
+            println!("[CMD] reconnecting");
+        }
+    }
+
+    /// Connects to RabbitMQ and consumes the tracker commands queue until the
+    /// connection is lost, commands are acknowledged once handled regardless
+    /// of being sent, since failures are published as events
+    async fn run(&self) -> Result<(), lapin::Error> {
+        let conn_options = ConnectionProperties::default()
+            .with_executor(tokio_executor_trait::Tokio::current())
+            .with_reactor(tokio_reactor_trait::Tokio);
+
+        let connection = Connection::connect(&self.rmq_uri, conn_options).await?;
+        let channel = connection.create_channel().await?;
+
+        channel
+            .queue_declare(
+                &self.tracker_commands_queue,
+                QueueDeclareOptions {
+                    durable: true,
+                    ..QueueDeclareOptions::default()
+                },
+                FieldTable::default(),
+            )
+            .await?;
+
+        let mut consumer = channel
+            .basic_consume(
+                &self.tracker_commands_queue,
+                "decoder",
+                BasicConsumeOptions::default(),
+                FieldTable::default(),
+            )
+            .await?;
+
+        println!("[CMD] consuming tracker commands");
+
+        while let Some(delivery) = consumer.next().await {
+            let delivery = delivery?;
+
+            match serde_json::from_slice::<TrackerCommand>(&delivery.data) {
+                Ok(command) => self.handle_command(command).await,
+                Err(err) => println!("[CMD] invalid tracker command: {}", err),
+            }
+
+            delivery.ack(BasicAckOptions::default()).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn handle_command(&self, command: TrackerCommand) {
+        let Ok(protocol) = Protocol::from_str(&command.protocol) else {
+            println!("[CMD] unknown tracker protocol: {}", command.protocol);
+            return;
+        };
+
+        let result = match send_command(protocol, &command).await {
+            Ok(()) => send_event(
+                Decoded {
+                    data: CommandSentMsg { id: command.id },
+                    imei: command.imei,
+                    response: None,
+                    protocol,
+                    event_type: TrackerEvent::CommandSent,
+                },
+                &self.sender,
+            ),
+            Err(reason) => send_event(
+                Decoded {
+                    data: CommandFailedMsg {
+                        id: command.id,
+                        reason,
+                    },
+                    imei: command.imei,
+                    response: None,
+                    protocol,
+                    event_type: TrackerEvent::CommandFailed,
+                },
+                &self.sender,
+            ),
+        };
+
+        if let Err(err) = result {
+            println!("[CMD] failed to publish command event: {}", err);
+        }
+    }
+}
@@ -12,14 +12,74 @@ fn def_tracker_events_exchange() -> String {
     "tracker_events".to_string()
 }
 
+fn def_tracker_commands_queue() -> String {
+    "tracker_commands".to_string()
+}
+
 fn def_tracer_service_name() -> String {
     "tracker_decoder".to_string()
 }
 
+fn def_transport() -> Transport {
+    Transport::Tcp
+}
+
 fn def_port_h02() -> usize {
     3003
 }
 
+fn def_port_gt06() -> usize {
+    3004
+}
+
+fn def_port_teltonika() -> usize {
+    3005
+}
+
+/// The transport protocol(s) to listen for trackers of a tracker protocol,
+/// both listen on the same port
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum Transport {
+    Tcp,
+    Udp,
+    Both,
+}
+
+impl Transport {
+    pub fn tcp(self) -> bool {
+        matches!(self, Transport::Tcp | Transport::Both)
+    }
+
+    pub fn udp(self) -> bool {
+        matches!(self, Transport::Udp | Transport::Both)
+    }
+}
+
+fn def_multiplexed_detection_timeout_secs() -> u64 {
+    10
+}
+
+fn def_offline_buffer_file() -> String {
+    "offline_buffer.jsonl".to_string()
+}
+
+fn def_offline_buffer_memory_limit() -> usize {
+    1000
+}
+
+fn def_offline_buffer_max_messages() -> usize {
+    100_000
+}
+
+fn def_packet_archive_max_file_size() -> u64 {
+    10 * 1024 * 1024
+}
+
+fn def_packet_archive_max_files() -> usize {
+    5
+}
+
 #[derive(Deserialize, Debug)]
 pub struct AppConfig {
     /// If the application should be run in debug mode and print additional info to stdout
@@ -34,6 +94,10 @@ pub struct AppConfig {
     #[serde(default = "def_tracker_events_exchange")]
     pub tracker_events_exchange: String,
 
+    /// Name of the queue to consume commands to send to trackers from
+    #[serde(default = "def_tracker_commands_queue")]
+    pub tracker_commands_queue: String,
+
     /// The service name to be used on the tracing spans
     #[serde(default = "def_tracer_service_name")]
     pub tracer_service_name: String,
@@ -41,12 +105,75 @@ pub struct AppConfig {
     /// Default port to listen for trackers with the H02 protocol
     #[serde(default = "def_port_h02")]
     pub port_h02: usize,
+
+    /// Transport to listen for trackers with the H02 protocol
+    #[serde(default = "def_transport")]
+    pub transport_h02: Transport,
+
+    /// Default port to listen for trackers with the GT06 protocol
+    #[serde(default = "def_port_gt06")]
+    pub port_gt06: usize,
+
+    /// Transport to listen for trackers with the GT06 protocol, only TCP is supported
+    /// since GT06 packets do not contain the tracker imei, only its login packet
+    #[serde(default = "def_transport")]
+    pub transport_gt06: Transport,
+
+    /// Default port to listen for trackers with the Teltonika protocol
+    #[serde(default = "def_port_teltonika")]
+    pub port_teltonika: usize,
+
+    /// Transport to listen for trackers with the Teltonika protocol
+    #[serde(default = "def_transport")]
+    pub transport_teltonika: Transport,
+
+    /// Port to listen for trackers of any protocol, detecting the protocol by the
+    /// first bytes of the connection, if not set the multiplexed listener is disabled
+    pub port_multiplexed: Option<usize>,
+
+    /// Seconds to wait for a connection on the multiplexed port to send enough
+    /// bytes to detect its protocol before closing it
+    #[serde(default = "def_multiplexed_detection_timeout_secs")]
+    pub multiplexed_detection_timeout_secs: u64,
+
+    /// Path of the file to store messages received while disconnected from
+    /// RabbitMQ, when they do not fit in memory or the application is stopped
+    #[serde(default = "def_offline_buffer_file")]
+    pub offline_buffer_file: String,
+
+    /// Amount of messages received while disconnected from RabbitMQ
+    /// to keep in memory before storing them on the file
+    #[serde(default = "def_offline_buffer_memory_limit")]
+    pub offline_buffer_memory_limit: usize,
+
+    /// Maximum amount of messages received while disconnected from
+    /// RabbitMQ to store, once reached the oldest messages are dropped
+    #[serde(default = "def_offline_buffer_max_messages")]
+    pub offline_buffer_max_messages: usize,
+
+    /// Directory to archive the raw packets received from trackers, one file per
+    /// tracker imei, if not set packets are not archived
+    pub packet_archive_dir: Option<String>,
+
+    /// Size in bytes a tracker archive file can reach before being rotated
+    #[serde(default = "def_packet_archive_max_file_size")]
+    pub packet_archive_max_file_size: u64,
+
+    /// Maximum amount of archive files to keep per tracker, including the current one
+    #[serde(default = "def_packet_archive_max_files")]
+    pub packet_archive_max_files: usize,
 }
 
 impl AppConfig {
     pub fn from_env() -> Result<AppConfig, envy::Error> {
         match envy::from_env::<AppConfig>() {
             Ok(config) => {
+                if config.transport_gt06 != Transport::Tcp {
+                    return Err(envy::Error::Custom(
+                        "the GT06 protocol only supports TCP".to_string(),
+                    ));
+                }
+
                 if config.debug {
                     println!("[CFG] {:?}", config);
                 }
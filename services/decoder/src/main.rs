@@ -1,27 +1,56 @@
+use commands::CommandsConsumer;
 use config::AppConfig;
+use packet_archive::PacketArchive;
 use rabbitmq::{RmqListener, RmqMessage};
-use server::{h02, listeners};
+use server::{gt06, h02, listeners, multiplexer, teltonika};
 use signal_hook::{
     consts::{SIGINT, SIGTERM},
     iterator::Signals,
 };
-use std::sync::Arc;
+use std::{path::PathBuf, sync::Arc, time::Duration};
 use tokio::sync::mpsc;
 
+mod commands;
 mod config;
 mod errors;
+mod offline_buffer;
+mod packet_archive;
 mod protocols;
 mod rabbitmq;
+mod replay;
 mod server;
 mod tracer;
 
 #[tokio::main]
 #[allow(clippy::never_loop)]
 async fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    if let Some("replay") = args.first().map(String::as_str) {
+        if let Err(err) = replay::run(&args[1..]) {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
     let config = AppConfig::from_env().expect("failed to load application config");
 
     tracer::init(config.tracer_service_name.to_owned()).expect("failed to init tracer");
 
+    if let Some(dir) = &config.packet_archive_dir {
+        let archive = PacketArchive::new(
+            PathBuf::from(dir),
+            config.packet_archive_max_file_size,
+            config.packet_archive_max_files,
+        )
+        .expect("failed to create packet archive directory");
+
+        packet_archive::init(archive);
+        println!("[ARC] archiving tracker packets at: {}", dir);
+    }
+
     let mut signals = Signals::new([SIGINT, SIGTERM]).expect("failed to setup signals hook");
 
     let (sender, receiver) = mpsc::unbounded_channel::<(RmqMessage, tracing::Span)>();
@@ -31,6 +60,10 @@ async fn main() {
 
     tokio::spawn(async move { rmq_server.start().await });
 
+    let commands_consumer = CommandsConsumer::new(&config, sender.clone());
+
+    tokio::spawn(async move { commands_consumer.start().await });
+
     tokio::spawn(async move {
         for sig in signals.forever() {
             println!("\n[APP] received signal: {}, shutting down", sig);
@@ -42,11 +75,61 @@ async fn main() {
         }
     });
 
-    listeners::start_tcp_listener(
-        format!("127.0.0.1:{}", config.port_h02).as_str(),
-        sender,
-        h02::stream_handler,
-    )
-    .await
-    .unwrap();
+    let h02_addr = format!("127.0.0.1:{}", config.port_h02);
+    let gt06_addr = format!("127.0.0.1:{}", config.port_gt06);
+    let teltonika_addr = format!("127.0.0.1:{}", config.port_teltonika);
+
+    let mut listener_handles = Vec::new();
+
+    if config.transport_h02.tcp() {
+        listener_handles.push(listeners::start_tcp_listener(
+            &h02_addr,
+            sender.clone(),
+            h02::stream_handler,
+        ));
+    }
+
+    if config.transport_h02.udp() {
+        listener_handles.push(listeners::start_udp_listener(
+            &h02_addr,
+            sender.clone(),
+            h02::datagram_handler,
+        ));
+    }
+
+    if config.transport_gt06.tcp() {
+        listener_handles.push(listeners::start_tcp_listener(
+            &gt06_addr,
+            sender.clone(),
+            gt06::stream_handler,
+        ));
+    }
+
+    if config.transport_teltonika.tcp() {
+        listener_handles.push(listeners::start_tcp_listener(
+            &teltonika_addr,
+            sender.clone(),
+            teltonika::stream_handler,
+        ));
+    }
+
+    if config.transport_teltonika.udp() {
+        listener_handles.push(listeners::start_udp_listener(
+            &teltonika_addr,
+            sender.clone(),
+            teltonika::datagram_handler,
+        ));
+    }
+
+    if let Some(port) = config.port_multiplexed {
+        listener_handles.push(multiplexer::start_multiplexed_listener(
+            &format!("127.0.0.1:{}", port),
+            sender.clone(),
+            Duration::from_secs(config.multiplexed_detection_timeout_secs),
+        ));
+    }
+
+    for handle in listener_handles {
+        handle.await.unwrap();
+    }
 }
@@ -0,0 +1,337 @@
+use crate::rabbitmq::RmqMessage;
+use std::{
+    collections::VecDeque,
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+/// A bounded FIFO buffer of messages that could not be published because the
+/// RabbitMQ connection was lost, so they can be published once it is restored.
+///
+/// Up to `memory_limit` of the newest messages are kept in memory while older
+/// messages are spilled to an append-only file (one JSON message per line), if
+/// the buffer reaches `max_messages` the oldest messages are dropped.
+///
+/// Since the file is kept until its messages are published, messages spilled to
+/// it survive restarts and are loaded again when the buffer is created.
+pub struct OfflineBuffer {
+    /// newest messages, all of them are newer than the messages on the file
+    memory: VecDeque<RmqMessage>,
+
+    memory_limit: usize,
+
+    max_messages: usize,
+
+    file_path: PathBuf,
+
+    /// append handle to the file, opened on the first spill
+    file: Option<File>,
+
+    /// amount of messages written to the file
+    file_len: usize,
+
+    /// amount of messages at the start of the file that were already published or
+    /// dropped, since its not possible to remove lines from the start of a file
+    file_skipped: usize,
+
+    /// amount of messages dropped since the buffer was last empty
+    dropped: usize,
+}
+
+impl OfflineBuffer {
+    /// Creates the buffer, loading any messages left on the file by a previous run
+    pub fn new(file_path: PathBuf, memory_limit: usize, max_messages: usize) -> OfflineBuffer {
+        let mut buffer = OfflineBuffer {
+            memory: VecDeque::new(),
+            memory_limit,
+            max_messages,
+            file_path,
+            file: None,
+            file_len: 0,
+            file_skipped: 0,
+            dropped: 0,
+        };
+
+        match buffer.read_file() {
+            Ok(messages) if !messages.is_empty() => {
+                println!(
+                    "[BUF] loaded {} messages from {}",
+                    messages.len(),
+                    buffer.file_path.display()
+                );
+
+                // rewrite the messages so any corrupted line (eg: a partial write
+                // before a crash) is removed, keeping them on the file until published
+                if let Err(err) = buffer.rewrite_file(&messages) {
+                    println!("[BUF] failed to rewrite offline buffer file: {}", err);
+                }
+            }
+            Ok(_) => {}
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => println!("[BUF] failed to load offline buffer file: {}", err),
+        }
+
+        buffer
+    }
+
+    /// amount of messages on the buffer
+    pub fn len(&self) -> usize {
+        self.file_len - self.file_skipped + self.memory.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Adds a message to the end of the buffer, spilling the oldest messages in
+    /// memory to the file and dropping the oldest message if the buffer is full
+    pub fn push(&mut self, message: RmqMessage) {
+        self.memory.push_back(message);
+
+        while self.memory.len() > self.memory_limit {
+            let Some(oldest) = self.memory.pop_front() else {
+                break;
+            };
+
+            if let Err(err) = self.append_to_file(&oldest) {
+                println!(
+                    "[BUF] failed to spill message to file, dropping it: {}",
+                    err
+                );
+            }
+        }
+
+        if self.len() > self.max_messages {
+            self.dropped += 1;
+
+            // log only once in a while as every new message is a drop once the buffer is full
+            if self.dropped % 1000 == 1 {
+                println!(
+                    "[BUF] offline buffer full, {} oldest messages dropped",
+                    self.dropped
+                );
+            }
+
+            if let Err(err) = self.remove_oldest(self.len() - self.max_messages) {
+                println!("[BUF] failed to drop oldest message: {}", err);
+            }
+        }
+    }
+
+    /// All the messages on the buffer, from oldest to newest
+    pub fn pending(&self) -> io::Result<Vec<RmqMessage>> {
+        let mut messages = match self.file_len - self.file_skipped {
+            0 => Vec::new(),
+            _ => self
+                .read_file()?
+                .into_iter()
+                .skip(self.file_skipped)
+                .collect(),
+        };
+
+        messages.extend(self.memory.iter().cloned());
+
+        Ok(messages)
+    }
+
+    /// Removes the `n` oldest messages, after they were published or dropped
+    pub fn remove_oldest(&mut self, n: usize) -> io::Result<()> {
+        let from_file = n.min(self.file_len - self.file_skipped);
+        let from_memory = (n - from_file).min(self.memory.len());
+
+        self.file_skipped += from_file;
+        self.memory.drain(..from_memory);
+
+        if self.is_empty() {
+            self.dropped = 0;
+        }
+
+        if self.file_skipped == self.file_len {
+            return self.clear_file();
+        }
+
+        // compact the file once most of it was skipped, so it does not grow forever
+        if self.file_skipped * 2 > self.file_len {
+            self.compact_file()?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes every message in memory to the file, so they are
+    /// not lost when the application is shutdown
+    pub fn persist(&mut self) -> io::Result<()> {
+        while let Some(message) = self.memory.pop_front() {
+            self.append_to_file(&message)?;
+        }
+
+        Ok(())
+    }
+
+    fn read_file(&self) -> io::Result<Vec<RmqMessage>> {
+        let file = File::open(&self.file_path)?;
+
+        Ok(BufReader::new(file)
+            .lines()
+            .map_while(Result::ok)
+            .filter_map(|line| serde_json::from_str(&line).ok())
+            .collect())
+    }
+
+    fn append_to_file(&mut self, message: &RmqMessage) -> io::Result<()> {
+        let line = serde_json::to_string(message)? + "\n";
+
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => self.file.insert(
+                OpenOptions::new()
+                    .create(true)
+                    .append(true)
+                    .open(&self.file_path)?,
+            ),
+        };
+
+        file.write_all(line.as_bytes())?;
+        self.file_len += 1;
+
+        Ok(())
+    }
+
+    fn clear_file(&mut self) -> io::Result<()> {
+        self.file = None;
+        self.file_len = 0;
+        self.file_skipped = 0;
+
+        match fs::remove_file(&self.file_path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    fn compact_file(&mut self) -> io::Result<()> {
+        let remaining: Vec<RmqMessage> = self
+            .read_file()?
+            .into_iter()
+            .skip(self.file_skipped)
+            .collect();
+
+        self.rewrite_file(&remaining)
+    }
+
+    fn rewrite_file(&mut self, messages: &[RmqMessage]) -> io::Result<()> {
+        self.clear_file()?;
+
+        messages
+            .iter()
+            .try_for_each(|message| self.append_to_file(message))?;
+
+        match self.len().checked_sub(self.max_messages) {
+            Some(exceeding) if exceeding > 0 => self.remove_oldest(exceeding),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::OfflineBuffer;
+    use crate::rabbitmq::RmqMessage;
+    use std::path::PathBuf;
+
+    fn message(i: usize) -> RmqMessage {
+        RmqMessage {
+            body: format!("{{\"i\":{i}}}"),
+            routing_key: format!("h02.location.{i}"),
+        }
+    }
+
+    fn bodies(buffer: &OfflineBuffer) -> Vec<String> {
+        buffer
+            .pending()
+            .unwrap()
+            .into_iter()
+            .map(|m| m.body)
+            .collect()
+    }
+
+    fn expected_bodies(range: std::ops::Range<usize>) -> Vec<String> {
+        range.map(|i| message(i).body).collect()
+    }
+
+    /// a unique file per test, since tests run in parallel
+    fn file_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "decoder_offline_buffer_{}_{}.jsonl",
+            name,
+            std::process::id()
+        ));
+
+        let _ = std::fs::remove_file(&path);
+
+        path
+    }
+
+    #[test]
+    fn keeps_order_when_spilling_to_file() {
+        let path = file_path("order");
+        let mut buffer = OfflineBuffer::new(path.clone(), 3, 100);
+
+        (0..10).for_each(|i| buffer.push(message(i)));
+
+        assert_eq!(buffer.len(), 10);
+        assert_eq!(bodies(&buffer), expected_bodies(0..10));
+        assert!(path.exists());
+
+        buffer.remove_oldest(10).unwrap();
+
+        assert!(buffer.is_empty());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn drops_oldest_when_full() {
+        let path = file_path("drop");
+        let mut buffer = OfflineBuffer::new(path.clone(), 2, 5);
+
+        (0..20).for_each(|i| buffer.push(message(i)));
+
+        assert_eq!(buffer.len(), 5);
+        assert_eq!(bodies(&buffer), expected_bodies(15..20));
+
+        buffer.remove_oldest(5).unwrap();
+    }
+
+    #[test]
+    fn removes_partially_replayed_messages() {
+        let path = file_path("partial");
+        let mut buffer = OfflineBuffer::new(path.clone(), 2, 100);
+
+        (0..6).for_each(|i| buffer.push(message(i)));
+        buffer.remove_oldest(3).unwrap();
+
+        assert_eq!(bodies(&buffer), expected_bodies(3..6));
+
+        buffer.push(message(6));
+
+        assert_eq!(bodies(&buffer), expected_bodies(3..7));
+
+        buffer.remove_oldest(4).unwrap();
+    }
+
+    #[test]
+    fn loads_persisted_messages() {
+        let path = file_path("persist");
+
+        let mut buffer = OfflineBuffer::new(path.clone(), 10, 100);
+        (0..5).for_each(|i| buffer.push(message(i)));
+        buffer.persist().unwrap();
+
+        let mut buffer = OfflineBuffer::new(path.clone(), 10, 100);
+
+        assert_eq!(bodies(&buffer), expected_bodies(0..5));
+        assert!(path.exists());
+
+        buffer.remove_oldest(5).unwrap();
+    }
+}
@@ -0,0 +1,375 @@
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::{
+    collections::HashMap,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{
+        mpsc::{self, RecvTimeoutError, SyncSender, TrySendError},
+        OnceLock,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Name of the archive of packets whose tracker imei is not known, such as
+/// packets sent before the first decodable packet of a connection
+const UNKNOWN_IMEI: &str = "unknown";
+
+/// Maximum length of a imei used on a archive file name
+const MAX_IMEI_LEN: usize = 20;
+
+/// Amount of packets waiting to be written before new packets are dropped,
+/// so a slow disk does not make the tracker connections wait or use all memory
+const QUEUE_SIZE: usize = 10_000;
+
+/// Time without packets after which the archive file of a tracker is closed, as
+/// UDP trackers have no connection whose end would close their archive file
+const MAX_IDLE_TIME: Duration = Duration::from_secs(5 * 60);
+
+/// A packet received from a tracker, as stored on the archive
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    pub timestamp: DateTime<Utc>,
+
+    /// address of the tracker that sent the packet
+    pub peer: SocketAddr,
+
+    /// the bytes as received, a single TCP read or UDP datagram
+    pub packet: Vec<u8>,
+}
+
+impl Entry {
+    /// Formats the entry as a archive line: timestamp, peer address and hex payload
+    /// separated by tabs, eg: `2024-02-15T08:05:09.123Z\t10.0.0.1:5000\t2a48512c...`
+    pub fn to_line(&self) -> String {
+        format!(
+            "{}\t{}\t{}\n",
+            self.timestamp.to_rfc3339_opts(SecondsFormat::Millis, true),
+            self.peer,
+            hex::encode(&self.packet)
+        )
+    }
+
+    pub fn from_line(line: &str) -> Result<Entry, String> {
+        let mut columns = line.trim_end().split('\t');
+
+        let mut column = |name: &str| columns.next().ok_or(format!("missing {name} column"));
+
+        Ok(Entry {
+            timestamp: DateTime::parse_from_rfc3339(column("timestamp")?)
+                .map_err(|e| format!("invalid timestamp: {e}"))?
+                .with_timezone(&Utc),
+            peer: column("peer")?
+                .parse()
+                .map_err(|e| format!("invalid peer address: {e}"))?,
+            packet: hex::decode(column("packet")?).map_err(|e| format!("invalid packet: {e}"))?,
+        })
+    }
+}
+
+/// A open archive file and its size, to know when it should be rotated
+struct ArchiveFile {
+    file: File,
+    size: u64,
+    last_write: Instant,
+}
+
+/// Archive of the raw packets sent by trackers, to reproduce decoding issues offline.
+///
+/// Each tracker has its own file on the archive directory named by its imei, once a file
+/// reaches `max_file_size` bytes it is rotated (`<imei>.log` to `<imei>.1.log`, `<imei>.1.log`
+/// to `<imei>.2.log` and so on), keeping at most `max_files` files per tracker.
+pub struct PacketArchive {
+    dir: PathBuf,
+    max_file_size: u64,
+    max_files: usize,
+    files: HashMap<String, ArchiveFile>,
+}
+
+impl PacketArchive {
+    pub fn new(dir: PathBuf, max_file_size: u64, max_files: usize) -> io::Result<PacketArchive> {
+        fs::create_dir_all(&dir)?;
+
+        Ok(PacketArchive {
+            dir,
+            max_file_size,
+            max_files: max_files.max(1),
+            files: HashMap::new(),
+        })
+    }
+
+    /// path of the archive file of a tracker, `0` being the current file
+    fn file_path(&self, imei: &str, index: usize) -> PathBuf {
+        match index {
+            0 => self.dir.join(format!("{imei}.log")),
+            _ => self.dir.join(format!("{imei}.{index}.log")),
+        }
+    }
+
+    /// shifts the tracker files by one, removing the oldest
+    fn rotate(&self, imei: &str) -> io::Result<()> {
+        for index in (0..self.max_files).rev() {
+            let path = self.file_path(imei, index);
+
+            let result = match index + 1 < self.max_files {
+                true => fs::rename(&path, self.file_path(imei, index + 1)),
+                false => fs::remove_file(&path),
+            };
+
+            match result {
+                Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn open(&self, imei: &str) -> io::Result<ArchiveFile> {
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.file_path(imei, 0))?;
+
+        let size = file.metadata()?.len();
+
+        Ok(ArchiveFile {
+            file,
+            size,
+            last_write: Instant::now(),
+        })
+    }
+
+    /// Appends the entry to the archive of the tracker
+    pub fn append(&mut self, imei: &str, entry: &Entry) -> io::Result<()> {
+        let line = entry.to_line();
+
+        // the imei is sent by the tracker, so it cannot be trusted as a file name
+        let is_valid_imei = !imei.is_empty()
+            && imei.len() <= MAX_IMEI_LEN
+            && imei.chars().all(|c| c.is_ascii_alphanumeric());
+
+        let imei = if is_valid_imei { imei } else { UNKNOWN_IMEI };
+
+        let exceeds_max_size = self
+            .files
+            .get(imei)
+            .is_some_and(|f| f.size > 0 && f.size + line.len() as u64 > self.max_file_size);
+
+        if exceeds_max_size {
+            self.files.remove(imei);
+            self.rotate(imei)?;
+        }
+
+        if !self.files.contains_key(imei) {
+            let archive_file = self.open(imei)?;
+            self.files.insert(imei.to_string(), archive_file);
+        }
+
+        let Some(archive_file) = self.files.get_mut(imei) else {
+            return Ok(());
+        };
+
+        archive_file.file.write_all(line.as_bytes())?;
+        archive_file.size += line.len() as u64;
+        archive_file.last_write = Instant::now();
+
+        Ok(())
+    }
+
+    /// Closes the archive file of a tracker, once its connection ends
+    pub fn close(&mut self, imei: &str) {
+        self.files.remove(imei);
+    }
+
+    /// Closes the archive files without writes for longer than `max_idle`
+    pub fn close_idle(&mut self, max_idle: Duration) {
+        self.files
+            .retain(|_, archive_file| archive_file.last_write.elapsed() < max_idle);
+    }
+}
+
+enum Command {
+    Append(String, Entry),
+    Close(String),
+}
+
+/// Writes the archive commands on a dedicated thread, since file writes are
+/// blocking and packets are recorded from the tokio tasks of the connections
+fn start_writer(mut archive: PacketArchive) -> SyncSender<Command> {
+    let (tx, rx) = mpsc::sync_channel::<Command>(QUEUE_SIZE);
+
+    thread::spawn(move || loop {
+        match rx.recv_timeout(MAX_IDLE_TIME) {
+            Ok(Command::Append(imei, entry)) => {
+                if let Err(err) = archive.append(&imei, &entry) {
+                    println!("[ARC] failed to archive packet: {}", err);
+                }
+            }
+            Ok(Command::Close(imei)) => archive.close(&imei),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        archive.close_idle(MAX_IDLE_TIME);
+    });
+
+    tx
+}
+
+/// Queue of the writer of the archive used by the tracker connections,
+/// only set if archiving is enabled
+static ARCHIVE: OnceLock<SyncSender<Command>> = OnceLock::new();
+
+/// Enables archiving the packets received from trackers
+pub fn init(archive: PacketArchive) {
+    if ARCHIVE.get().is_some() {
+        println!("[ARC] packet archive already initialized");
+        return;
+    }
+
+    let _ = ARCHIVE.set(start_writer(archive));
+}
+
+/// Archives a packet received from a tracker, if archiving is enabled, `imei` being
+/// the imei of the tracker on the connection or `None` if it is not known yet.
+///
+/// the packet is only queued to be written, so this never blocks, packets
+/// are dropped if the archive writer cannot keep up with them
+pub fn record(imei: Option<&str>, peer: SocketAddr, packet: &[u8]) {
+    let Some(archive) = ARCHIVE.get() else {
+        return;
+    };
+
+    let entry = Entry {
+        timestamp: Utc::now(),
+        peer,
+        packet: packet.to_vec(),
+    };
+
+    let imei = imei.unwrap_or(UNKNOWN_IMEI).to_string();
+
+    if let Err(TrySendError::Full(_)) = archive.try_send(Command::Append(imei, entry)) {
+        println!("[ARC] archive queue full, packet not archived");
+    }
+}
+
+/// Closes the archive file of a tracker, if archiving is enabled
+pub fn close(imei: &str) {
+    if let Some(archive) = ARCHIVE.get() {
+        let _ = archive.try_send(Command::Close(imei.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Entry, PacketArchive};
+    use chrono::{TimeZone, Utc};
+    use std::{fs, path::PathBuf, time::Duration};
+
+    fn entry(packet: &[u8]) -> Entry {
+        Entry {
+            timestamp: Utc.with_ymd_and_hms(2024, 2, 15, 8, 5, 9).unwrap(),
+            peer: "10.0.0.1:5000".parse().unwrap(),
+            packet: packet.to_vec(),
+        }
+    }
+
+    /// a unique and empty directory per test, since tests run in parallel
+    fn archive_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "decoder_packet_archive_{}_{}",
+            name,
+            std::process::id()
+        ));
+
+        let _ = fs::remove_dir_all(&dir);
+
+        dir
+    }
+
+    #[test]
+    fn parses_formatted_lines() {
+        let entry = entry(b"*HQ,867232051148352,HTBT#");
+        let line = entry.to_line();
+
+        assert_eq!(
+            line,
+            "2024-02-15T08:05:09.000Z\t10.0.0.1:5000\t2a48512c3836373233323035313134383335322c48544254\
+             23\n"
+        );
+        assert_eq!(Entry::from_line(&line), Ok(entry));
+        assert!(Entry::from_line("2024-02-15T08:05:09.000Z\t10.0.0.1:5000").is_err());
+    }
+
+    #[test]
+    fn rotates_files() {
+        let dir = archive_dir("rotate");
+        let line_len = entry(b"1").to_line().len() as u64;
+
+        // fits two lines per file
+        let mut archive = PacketArchive::new(dir.clone(), line_len * 2, 3).unwrap();
+
+        for packet in [b"1", b"2", b"3", b"4", b"5", b"6", b"7"] {
+            archive.append("867232051148352", &entry(packet)).unwrap();
+        }
+
+        let packets = |file: &str| -> Vec<Vec<u8>> {
+            fs::read_to_string(dir.join(file))
+                .unwrap()
+                .lines()
+                .map(|line| Entry::from_line(line).unwrap().packet)
+                .collect()
+        };
+
+        assert_eq!(packets("867232051148352.log"), vec![b"7".to_vec()]);
+        assert_eq!(
+            packets("867232051148352.1.log"),
+            vec![b"5".to_vec(), b"6".to_vec()]
+        );
+        assert_eq!(
+            packets("867232051148352.2.log"),
+            vec![b"3".to_vec(), b"4".to_vec()]
+        );
+        assert!(!dir.join("867232051148352.3.log").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn does_not_trust_imeis_as_file_names() {
+        let dir = archive_dir("imei");
+        let mut archive = PacketArchive::new(dir.clone(), 1024, 1).unwrap();
+
+        archive.append("../../etc/passwd", &entry(b"1")).unwrap();
+
+        assert!(dir.join("unknown.log").exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn closes_idle_files() {
+        let dir = archive_dir("idle");
+        let mut archive = PacketArchive::new(dir.clone(), 1024, 1).unwrap();
+
+        archive.append("867232051148352", &entry(b"1")).unwrap();
+        archive.append("867232051148353", &entry(b"1")).unwrap();
+
+        archive.close_idle(Duration::from_secs(60));
+        assert_eq!(archive.files.len(), 2);
+
+        archive.close_idle(Duration::ZERO);
+        assert_eq!(archive.files.len(), 0);
+
+        // reopened on the next packet, appending to the same file
+        archive.append("867232051148352", &entry(b"2")).unwrap();
+
+        let lines = fs::read_to_string(dir.join("867232051148352.log")).unwrap();
+        assert_eq!(lines.lines().count(), 2);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
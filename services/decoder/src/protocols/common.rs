@@ -1,12 +1,14 @@
 use crate::rabbitmq::RmqMessage;
 use serde::Serialize;
-use strum::Display;
+use strum::{Display, EnumString};
 
 /// all protocols at least partially supported by this service
-#[derive(Display)]
+#[derive(Display, EnumString, Debug, Clone, Copy, PartialEq)]
 #[strum(serialize_all = "snake_case")]
 pub enum Protocol {
     H02,
+    Gt06,
+    Teltonika,
 }
 
 #[derive(Display)]
@@ -14,6 +16,15 @@ pub enum Protocol {
 pub enum TrackerEvent {
     Location,
     Heartbeat,
+    Lbs,
+    LinkStatus,
+    CommandAck,
+    CommandSent,
+    CommandFailed,
+    Login,
+    Alarm,
+    Connected,
+    Disconnected,
 }
 
 /// The result of decoding a tracker packet.
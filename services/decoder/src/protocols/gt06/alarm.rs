@@ -0,0 +1,46 @@
+use super::{
+    decoder::Packet,
+    utils::{self, CELL_TOWER_LEN, GPS_INFO_LEN},
+};
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use shared::dto::decoder::gt06::AlarmMsg;
+
+/// Alarm packet layout (bytes), the LBS length counts itself, so its 9
+/// when the cell tower is sent or 1 when it is not
+///
+/// | 18       | 1          | 8          | 5      |
+/// |----------|------------|------------|--------|
+/// | gps info | lbs length | cell tower | status |
+impl TryFrom<Packet<'_>> for Decoded<AlarmMsg> {
+    type Error = String;
+
+    fn try_from(packet: Packet) -> Result<Self, Self::Error> {
+        let imei = packet.imei()?;
+
+        let mut location = utils::parse_gps_info(packet.content)?;
+
+        let lbs_len = *packet
+            .content
+            .get(GPS_INFO_LEN)
+            .ok_or("incomplete alarm message")? as usize;
+
+        if lbs_len > CELL_TOWER_LEN {
+            let cell_tower_start = GPS_INFO_LEN + 1;
+
+            location.cell_tower = Some(utils::parse_cell_tower(
+                packet.content.get(cell_tower_start..).unwrap_or_default(),
+            )?);
+        }
+
+        let status_start = GPS_INFO_LEN + lbs_len.max(1);
+        let status = utils::parse_status(packet.content.get(status_start..).unwrap_or_default())?;
+
+        Ok(Decoded {
+            data: AlarmMsg { location, status },
+            imei,
+            response: Some(utils::ack(packet.protocol, packet.serial)),
+            protocol: Protocol::Gt06,
+            event_type: TrackerEvent::Alarm,
+        })
+    }
+}
@@ -0,0 +1,132 @@
+use super::{decoder::Packet, utils};
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use shared::dto::decoder::{command::Command, gt06::CommandAckMsg};
+
+/// Protocol number of online commands sent to the tracker
+const ONLINE_COMMAND: u8 = 0x80;
+
+/// Amount of bytes of the server flag, sent with the command and echoed back on the response
+const SERVER_FLAG_LEN: usize = 4;
+
+/// The command content, a ASCII string understood by the tracker
+fn command_content(command: &Command) -> String {
+    match command {
+        Command::EngineStop => "DYD,000000#".to_string(),
+        Command::EngineResume => "HFYD,000000#".to_string(),
+        Command::PositionInterval { seconds } => format!("TIMER,{seconds}#"),
+        Command::Reboot => "RESET#".to_string(),
+        Command::Custom { content } => content.clone(),
+    }
+}
+
+/// Encodes a online command packet, the `server_flag` is echoed back on the
+/// tracker response so its used to identify the command.
+///
+/// | 2         | 1      | 1    | 1              | 4           | N       | 2      | 2   | 2         |
+/// |-----------|--------|------|----------------|-------------|---------|--------|-----|-----------|
+/// | start bit | length | 0x80 | command length | server flag | content | serial | CRC | stop bit  |
+pub fn encode(command: &Command, server_flag: u32, serial: u16) -> Result<Box<[u8]>, String> {
+    let content = command_content(command);
+
+    let command_len = SERVER_FLAG_LEN + content.len();
+
+    // protocol (1) + command length (1) + command + serial (2) + CRC (2)
+    let packet_len = command_len + 6;
+
+    if packet_len > u8::MAX as usize {
+        return Err("command too long".to_string());
+    }
+
+    let mut packet = vec![packet_len as u8, ONLINE_COMMAND, command_len as u8];
+    packet.extend_from_slice(&server_flag.to_be_bytes());
+    packet.extend_from_slice(content.as_bytes());
+    packet.extend_from_slice(&serial.to_be_bytes());
+    packet.extend_from_slice(&utils::crc_itu(&packet).to_be_bytes());
+
+    Ok([&[0x78, 0x78], packet.as_slice(), &[0x0D, 0x0A]]
+        .concat()
+        .into())
+}
+
+impl TryFrom<Packet<'_>> for Decoded<CommandAckMsg> {
+    type Error = String;
+
+    /// `command length (1) | server flag (4) | content`, some models send
+    /// a 2 bytes language after the content, that is not counted on the length
+    fn try_from(packet: Packet) -> Result<Self, Self::Error> {
+        let command_len = *packet.content.first().ok_or("empty command response")? as usize;
+
+        if command_len < SERVER_FLAG_LEN || packet.content.len() < command_len + 1 {
+            return Err("incomplete command response message".to_string());
+        }
+
+        let server_flag = u32::from_be_bytes(
+            packet.content[1..1 + SERVER_FLAG_LEN]
+                .try_into()
+                .or(Err("invalid server flag"))?,
+        );
+
+        let content =
+            String::from_utf8_lossy(&packet.content[1 + SERVER_FLAG_LEN..1 + command_len])
+                .to_string();
+
+        Ok(Decoded {
+            data: CommandAckMsg {
+                server_flag,
+                content,
+            },
+            imei: packet.imei()?,
+            response: None,
+            protocol: Protocol::Gt06,
+            event_type: TrackerEvent::CommandAck,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode;
+    use crate::protocols::gt06::decoder::{decode, Message};
+    use crate::protocols::gt06::utils;
+    use shared::dto::decoder::command::Command;
+
+    #[test]
+    fn encodes_online_command() {
+        let packet = encode(&Command::Reboot, 1, 1).unwrap();
+
+        assert_eq!(&packet[..7], [0x78, 0x78, 0x10, 0x80, 0x0A, 0x00, 0x00]);
+        assert_eq!(&packet[7..15], b"\x00\x01RESET#");
+        assert_eq!(&packet[packet.len() - 2..], [0x0D, 0x0A]);
+
+        let crc = utils::crc_itu(&packet[2..packet.len() - 4]);
+        assert_eq!(
+            &packet[packet.len() - 4..packet.len() - 2],
+            crc.to_be_bytes()
+        );
+    }
+
+    #[test]
+    fn decodes_command_response() {
+        let content = b"DYD=Success!";
+
+        let mut packet = vec![(content.len() + 12) as u8, 0x15, (content.len() + 4) as u8];
+        packet.extend_from_slice(&7_u32.to_be_bytes());
+        packet.extend_from_slice(content);
+        packet.extend_from_slice(&[0x00, 0x02, 0x00, 0x05]);
+        packet.extend_from_slice(&utils::crc_itu(&packet).to_be_bytes());
+
+        let frame = [&[0x78, 0x78], packet.as_slice(), &[0x0D, 0x0A]].concat();
+
+        let Ok(Message::CommandAck(decoded)) = decode(&frame, Some("123456789012345")) else {
+            panic!("not decoded as command ack");
+        };
+
+        assert_eq!(
+            decoded.get_routing_key(),
+            "gt06.command_ack.123456789012345"
+        );
+        assert_eq!(decoded.data.server_flag, 7);
+        assert_eq!(decoded.data.content, "DYD=Success!");
+        assert!(decoded.response.is_none());
+    }
+}
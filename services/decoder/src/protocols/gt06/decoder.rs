@@ -0,0 +1,233 @@
+use super::utils;
+use crate::protocols::common::Decoded;
+use shared::dto::decoder::gt06::{AlarmMsg, CommandAckMsg, LocationMsg, LoginMsg, StatusMsg};
+
+pub mod msg_ids {
+    pub const LOGIN: u8 = 0x01;
+    pub const LOCATION: u8 = 0x12;
+    pub const STATUS: u8 = 0x13;
+    pub const ALARM: u8 = 0x16;
+
+    /// response of the tracker to a online command
+    pub const COMMAND_RESPONSE: u8 = 0x15;
+
+    /// location packet of newer models (GT06N, etc), also containing the ACC state
+    pub const LOCATION_2: u8 = 0x22;
+}
+
+/// All possible message types decodable from the GT06 tracker protocol
+pub enum Message {
+    Login(Decoded<LoginMsg>),
+    Location(Decoded<LocationMsg>),
+    Status(Decoded<StatusMsg>),
+    Alarm(Decoded<AlarmMsg>),
+    CommandAck(Decoded<CommandAckMsg>),
+}
+
+/// A GT06 packet with a valid CRC, without the start bit, length, CRC and stop bit
+pub struct Packet<'a> {
+    /// the protocol number, the type of the packet
+    pub protocol: u8,
+
+    /// the information content of the packet
+    pub content: &'a [u8],
+
+    /// serial number of the packet, to be used on the acknowledgement
+    pub serial: u16,
+
+    /// imei of the tracker, only known after it sends the login packet
+    pub imei: Option<&'a str>,
+}
+
+impl Packet<'_> {
+    /// the imei of the tracker, since only the login packet contains the imei
+    /// any other packet sent before the login cannot be decoded
+    pub fn imei(&self) -> Result<String, String> {
+        self.imei
+            .map(|imei| imei.to_string())
+            .ok_or("packet received before login".to_string())
+    }
+}
+
+/// Decodes a complete GT06 frame, `imei` is the imei of the tracker on
+/// the connection, received on its login packet.
+pub fn decode<'a>(frame: &'a [u8], imei: Option<&'a str>) -> Result<Message, String> {
+    // start bit (2) + length (1 or 2) + protocol (1) + serial (2) + crc (2) + stop bit (2)
+    let header_len = if frame.starts_with(&[0x79, 0x79]) {
+        4
+    } else {
+        3
+    };
+
+    if frame.len() < header_len + 7 {
+        return Err("incomplete GT06 packet".to_string());
+    }
+
+    let crc_start = frame.len() - 4;
+    let crc = u16::from_be_bytes([frame[crc_start], frame[crc_start + 1]]);
+
+    if crc != utils::crc_itu(&frame[2..crc_start]) {
+        return Err("invalid GT06 packet CRC".to_string());
+    }
+
+    let serial_start = crc_start - 2;
+
+    let packet = Packet {
+        protocol: frame[header_len],
+        content: &frame[header_len + 1..serial_start],
+        serial: u16::from_be_bytes([frame[serial_start], frame[serial_start + 1]]),
+        imei,
+    };
+
+    match packet.protocol {
+        msg_ids::LOGIN => Ok(Message::Login(packet.try_into()?)),
+        msg_ids::LOCATION | msg_ids::LOCATION_2 => Ok(Message::Location(packet.try_into()?)),
+        msg_ids::STATUS => Ok(Message::Status(packet.try_into()?)),
+        msg_ids::ALARM => Ok(Message::Alarm(packet.try_into()?)),
+        msg_ids::COMMAND_RESPONSE => Ok(Message::CommandAck(packet.try_into()?)),
+        protocol => Err(format!("unknown protocol number: {protocol:#04x}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, Message};
+    use crate::protocols::gt06::utils;
+    use shared::dto::decoder::gt06::Alarm;
+
+    const IMEI: Option<&str> = Some("123456789012345");
+
+    /// builds a short packet with a valid CRC
+    fn packet(protocol: u8, content: &[u8], serial: u16) -> Vec<u8> {
+        let mut packet = vec![(content.len() + 5) as u8, protocol];
+        packet.extend_from_slice(content);
+        packet.extend_from_slice(&serial.to_be_bytes());
+        packet.extend_from_slice(&utils::crc_itu(&packet).to_be_bytes());
+
+        [&[0x78, 0x78], packet.as_slice(), &[0x0D, 0x0A]].concat()
+    }
+
+    #[test]
+    fn decodes_login() {
+        // sample from the GT06 protocol docs
+        let frame = [
+            0x78, 0x78, 0x0D, 0x01, 0x01, 0x23, 0x45, 0x67, 0x89, 0x01, 0x23, 0x45, 0x00, 0x01,
+            0x8C, 0xDD, 0x0D, 0x0A,
+        ];
+
+        let Ok(Message::Login(decoded)) = decode(&frame, None) else {
+            panic!("not decoded as login");
+        };
+
+        assert_eq!(decoded.get_routing_key(), "gt06.login.123456789012345");
+        assert_eq!(
+            decoded.response.as_deref(),
+            Some([0x78, 0x78, 0x05, 0x01, 0x00, 0x01, 0xD9, 0xDC, 0x0D, 0x0A].as_slice())
+        );
+    }
+
+    #[test]
+    fn decodes_location() {
+        // sample from the GT06 protocol docs
+        let frame = [
+            0x78, 0x78, 0x1F, 0x12, 0x0B, 0x08, 0x1D, 0x11, 0x2E, 0x10, 0xCF, 0x02, 0x7A, 0xC7,
+            0xEB, 0x0C, 0x46, 0x58, 0x49, 0x00, 0x14, 0x8F, 0x01, 0xCC, 0x00, 0x28, 0x7D, 0x00,
+            0x1F, 0xB8, 0x00, 0x03, 0x80, 0x81, 0x0D, 0x0A,
+        ];
+
+        let Ok(Message::Location(decoded)) = decode(&frame, IMEI) else {
+            panic!("not decoded as location");
+        };
+
+        assert_eq!(decoded.get_routing_key(), "gt06.location.123456789012345");
+        assert!(decoded.response.is_none());
+
+        let location = decoded.data;
+
+        assert!((location.lat - 23.111668).abs() < 0.000001);
+        assert!((location.lng - 114.409285).abs() < 0.000001);
+        assert_eq!(location.speed, 0.0);
+        assert_eq!(location.direction, 143);
+        assert_eq!(location.satellites, 15);
+        assert!(location.positioned);
+        assert_eq!(location.timestamp.to_rfc3339(), "2011-08-29T17:46:16+00:00");
+
+        let cell_tower = location.cell_tower.unwrap();
+
+        assert_eq!(cell_tower.mcc, 460);
+        assert_eq!(cell_tower.lac, 0x287D);
+        assert_eq!(cell_tower.cell_id, 0x1FB8);
+    }
+
+    #[test]
+    fn rejects_location_before_login() {
+        let content = [
+            0x0B, 0x08, 0x1D, 0x11, 0x2E, 0x10, 0xCF, 0x02, 0x7A, 0xC7, 0xEB, 0x0C, 0x46, 0x58,
+            0x49, 0x00, 0x14, 0x8F,
+        ];
+
+        assert!(decode(&packet(0x12, &content, 1), None).is_err());
+        assert!(decode(&packet(0x12, &content, 1), IMEI).is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_crc() {
+        let mut frame = packet(0x13, &[0x46, 0x04, 0x03, 0x00, 0x02], 1);
+        frame[5] = 0x00;
+
+        assert!(decode(&frame, IMEI).is_err());
+    }
+
+    #[test]
+    fn decodes_status() {
+        // defense on, ACC on, charging, GPS tracking on, 4 (high) voltage
+        // and 3 (good) gsm signal
+        let frame = packet(0x13, &[0x47, 0x04, 0x03, 0x00, 0x02], 0x0A);
+
+        let Ok(Message::Status(decoded)) = decode(&frame, IMEI) else {
+            panic!("not decoded as status");
+        };
+
+        assert_eq!(decoded.get_routing_key(), "gt06.heartbeat.123456789012345");
+        assert_eq!(decoded.response, Some(utils::ack(0x13, 0x0A)));
+
+        let status = decoded.data;
+
+        assert!(status.terminal_info.armed);
+        assert!(status.terminal_info.acc);
+        assert!(status.terminal_info.charging);
+        assert!(status.terminal_info.gps_tracking);
+        assert!(!status.terminal_info.oil_and_electricity_disconnected);
+        assert!(status.terminal_info.alarm.is_none());
+        assert_eq!(status.voltage_level, 4);
+        assert_eq!(status.gsm_signal, 3);
+        assert!(status.alarm.is_none());
+    }
+
+    #[test]
+    fn decodes_alarm() {
+        let gps_info = [
+            0x0B, 0x08, 0x1D, 0x11, 0x2E, 0x10, 0xCF, 0x02, 0x7A, 0xC7, 0xEB, 0x0C, 0x46, 0x58,
+            0x49, 0x00, 0x14, 0x8F,
+        ];
+        let lbs = [0x09, 0x01, 0xCC, 0x00, 0x28, 0x7D, 0x00, 0x1F, 0xB8];
+        // GPS tracking on, charging and SOS alarm
+        let status = [0x64, 0x04, 0x03, 0x01, 0x02];
+
+        let content = [gps_info.as_slice(), &lbs, &status].concat();
+
+        let Ok(Message::Alarm(decoded)) = decode(&packet(0x16, &content, 7), IMEI) else {
+            panic!("not decoded as alarm");
+        };
+
+        assert_eq!(decoded.get_routing_key(), "gt06.alarm.123456789012345");
+        assert_eq!(decoded.response, Some(utils::ack(0x16, 7)));
+
+        let alarm = decoded.data;
+
+        assert!(matches!(alarm.status.alarm, Some(Alarm::Sos)));
+        assert!(matches!(alarm.status.terminal_info.alarm, Some(Alarm::Sos)));
+        assert_eq!(alarm.location.direction, 143);
+        assert_eq!(alarm.location.cell_tower.unwrap().mcc, 460);
+    }
+}
@@ -0,0 +1,191 @@
+/// Start of GT06 packets whose length fits on a single byte
+const SHORT_PREFIX: &[u8] = &[0x78, 0x78];
+
+/// Start of GT06 packets with a two bytes length, used by newer models for bigger packets
+const LONG_PREFIX: &[u8] = &[0x79, 0x79];
+
+/// End of every GT06 packet
+const SUFFIX: &[u8] = &[0x0D, 0x0A];
+
+/// The maximum amount of bytes a frame can have, a GT06 packet is way smaller than
+/// this, so a long packet claiming a bigger length is considered garbage.
+const MAX_FRAME_SIZE: usize = 1024;
+
+/// A per connection buffer to reassemble GT06 frames from a TCP stream.
+///
+/// Every GT06 frame starts with `0x7878` (or `0x7979`) followed by its length
+/// and ends with `0x0D0A`, so complete frames are extracted by their length and
+/// any trailing partial frame is kept until the next read completes it.
+#[derive(Default)]
+pub struct FrameBuffer {
+    buffer: Vec<u8>,
+}
+
+impl FrameBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// appends the bytes read from the connection to the buffer
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Removes and returns the next complete frame on the buffer, including its
+    /// start and stop bytes, returns `None` if there is no complete frame.
+    ///
+    /// Invalid bytes before a frame or frames without the stop bytes at the end of
+    /// their length are discarded and returned as a `Err` so the caller can count
+    /// them as invalid packets.
+    pub fn next_frame(&mut self) -> Option<Result<Vec<u8>, String>> {
+        if self.buffer.is_empty() {
+            return None;
+        }
+
+        let start = match find_frame_start(&self.buffer) {
+            Some(start) => start,
+            None => {
+                // keep the last byte as it might be the start of a prefix split between reads
+                let keep = usize::from(is_prefix_start(self.buffer[self.buffer.len() - 1]));
+                let discarded = self.buffer.len() - keep;
+
+                if discarded == 0 {
+                    return None;
+                }
+
+                self.buffer.drain(..discarded);
+                return Some(Err(format!(
+                    "discarded {discarded} bytes without a GT06 start bit"
+                )));
+            }
+        };
+
+        if start > 0 {
+            self.buffer.drain(..start);
+            return Some(Err(format!(
+                "discarded {start} bytes before the GT06 start bit"
+            )));
+        }
+
+        let frame_len = self.frame_len()?;
+
+        if frame_len > MAX_FRAME_SIZE {
+            self.buffer.drain(..SHORT_PREFIX.len());
+            return Some(Err(format!(
+                "discarded frame with invalid length: {frame_len}"
+            )));
+        }
+
+        if self.buffer.len() < frame_len {
+            return None;
+        }
+
+        if !self.buffer[..frame_len].ends_with(SUFFIX) {
+            // drop only the start bit, so the next frame is found even if the
+            // length was corrupted and the frame is shorter than it claims
+            self.buffer.drain(..SHORT_PREFIX.len());
+            return Some(Err("discarded frame without the GT06 stop bit".to_string()));
+        }
+
+        Some(Ok(self.buffer.drain(..frame_len).collect()))
+    }
+
+    /// total length of the frame at the start of the buffer, the length on the
+    /// packet does not count the start bit, the length itself and the stop bit.
+    fn frame_len(&self) -> Option<usize> {
+        if self.buffer.starts_with(LONG_PREFIX) {
+            let len = self.buffer.get(2..4)?;
+            return Some(u16::from_be_bytes([len[0], len[1]]) as usize + 6);
+        }
+
+        Some(*self.buffer.get(2)? as usize + 5)
+    }
+}
+
+/// position of the first short or long packet prefix
+fn find_frame_start(bytes: &[u8]) -> Option<usize> {
+    bytes
+        .windows(2)
+        .position(|w| w == SHORT_PREFIX || w == LONG_PREFIX)
+}
+
+fn is_prefix_start(byte: u8) -> bool {
+    byte == SHORT_PREFIX[0] || byte == LONG_PREFIX[0]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrameBuffer;
+
+    /// login packet sample from the GT06 protocol docs
+    const LOGIN: &[u8] = &[
+        0x78, 0x78, 0x0D, 0x01, 0x01, 0x23, 0x45, 0x67, 0x89, 0x01, 0x23, 0x45, 0x00, 0x01, 0x8C,
+        0xDD, 0x0D, 0x0A,
+    ];
+
+    const LONG_PACKET: &[u8] = &[
+        0x79, 0x79, 0x00, 0x07, 0x94, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x0D, 0x0A,
+    ];
+
+    fn drain_frames(buffer: &mut FrameBuffer) -> Vec<Result<Vec<u8>, String>> {
+        std::iter::from_fn(|| buffer.next_frame()).collect()
+    }
+
+    #[test]
+    fn single_frame() {
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(LOGIN);
+
+        assert_eq!(drain_frames(&mut buffer), vec![Ok(LOGIN.to_vec())]);
+    }
+
+    #[test]
+    fn frame_split_across_reads() {
+        let mut buffer = FrameBuffer::new();
+
+        for chunk in LOGIN.chunks(1) {
+            assert!(drain_frames(&mut buffer).is_empty());
+            buffer.extend(chunk);
+        }
+
+        assert_eq!(drain_frames(&mut buffer), vec![Ok(LOGIN.to_vec())]);
+    }
+
+    #[test]
+    fn concatenated_frames() {
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(&[LOGIN, LONG_PACKET, LOGIN].concat());
+
+        assert_eq!(
+            drain_frames(&mut buffer),
+            vec![
+                Ok(LOGIN.to_vec()),
+                Ok(LONG_PACKET.to_vec()),
+                Ok(LOGIN.to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn garbage_before_frame_is_reported() {
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(&[b"GET / HTTP/1.1", LOGIN].concat());
+
+        let frames = drain_frames(&mut buffer);
+
+        assert_eq!(frames.len(), 2);
+        assert!(frames[0].is_err());
+        assert_eq!(frames[1], Ok(LOGIN.to_vec()));
+    }
+
+    #[test]
+    fn frame_without_stop_bit_is_reported() {
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(&[&LOGIN[..10], LOGIN, LOGIN].concat());
+
+        let frames = drain_frames(&mut buffer);
+
+        assert!(frames.iter().any(|f| f.is_err()));
+        assert_eq!(frames.last(), Some(&Ok(LOGIN.to_vec())));
+    }
+}
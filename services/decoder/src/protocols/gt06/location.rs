@@ -0,0 +1,41 @@
+use super::{
+    decoder::{msg_ids, Packet},
+    utils::{self, CELL_TOWER_LEN, GPS_INFO_LEN},
+};
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use shared::dto::decoder::gt06::LocationMsg;
+
+/// Location packet layout (bytes), the cell tower is not sent by all models
+/// and the ACC is only sent on the newer location packet (`0x22`)
+///
+/// | 18       | 8          | 1   | ...     |
+/// |----------|------------|-----|---------|
+/// | gps info | cell tower | acc | ignored |
+impl TryFrom<Packet<'_>> for Decoded<LocationMsg> {
+    type Error = String;
+
+    fn try_from(packet: Packet) -> Result<Self, Self::Error> {
+        let imei = packet.imei()?;
+
+        let mut location = utils::parse_gps_info(packet.content)?;
+
+        let cell_tower_end = GPS_INFO_LEN + CELL_TOWER_LEN;
+
+        if let Some(cell_tower) = packet.content.get(GPS_INFO_LEN..cell_tower_end) {
+            location.cell_tower = Some(utils::parse_cell_tower(cell_tower)?);
+        }
+
+        if packet.protocol == msg_ids::LOCATION_2 {
+            location.acc = packet.content.get(cell_tower_end).map(|acc| *acc == 0x01);
+        }
+
+        Ok(Decoded {
+            data: location,
+            imei,
+            // location packets do not need to be acknowledged
+            response: None,
+            protocol: Protocol::Gt06,
+            event_type: TrackerEvent::Location,
+        })
+    }
+}
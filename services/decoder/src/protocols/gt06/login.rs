@@ -0,0 +1,31 @@
+use super::{decoder::Packet, utils};
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use shared::dto::decoder::gt06::LoginMsg;
+
+/// Amount of bytes of the terminal id (the IMEI, BCD encoded with a leading zero)
+const TERMINAL_ID_LEN: usize = 8;
+
+impl TryFrom<Packet<'_>> for Decoded<LoginMsg> {
+    type Error = String;
+
+    fn try_from(packet: Packet) -> Result<Self, Self::Error> {
+        if packet.content.len() < TERMINAL_ID_LEN {
+            return Err("incomplete login message".to_string());
+        }
+
+        let terminal_id = hex::encode(&packet.content[..TERMINAL_ID_LEN]);
+        let imei = terminal_id[1..].to_string();
+
+        if !imei.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("invalid terminal id: {terminal_id}"));
+        }
+
+        Ok(Decoded {
+            data: LoginMsg { imei: imei.clone() },
+            imei,
+            response: Some(utils::ack(packet.protocol, packet.serial)),
+            protocol: Protocol::Gt06,
+            event_type: TrackerEvent::Login,
+        })
+    }
+}
@@ -0,0 +1,8 @@
+pub mod alarm;
+pub mod command;
+pub mod decoder;
+pub mod framing;
+pub mod location;
+pub mod login;
+pub mod status;
+pub mod utils;
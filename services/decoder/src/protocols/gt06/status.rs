@@ -0,0 +1,19 @@
+use super::{decoder::Packet, utils};
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use shared::dto::decoder::gt06::StatusMsg;
+
+/// Status packets are sent periodically to keep the connection alive, so
+/// they are published as heartbeats
+impl TryFrom<Packet<'_>> for Decoded<StatusMsg> {
+    type Error = String;
+
+    fn try_from(packet: Packet) -> Result<Self, Self::Error> {
+        Ok(Decoded {
+            data: utils::parse_status(packet.content)?,
+            imei: packet.imei()?,
+            response: Some(utils::ack(packet.protocol, packet.serial)),
+            protocol: Protocol::Gt06,
+            event_type: TrackerEvent::Heartbeat,
+        })
+    }
+}
@@ -0,0 +1,168 @@
+use chrono::{DateTime, TimeZone, Utc};
+use shared::dto::decoder::gt06::{Alarm, CellTower, LocationMsg, StatusMsg, TerminalInfo};
+
+/// Amount of bytes of the GPS info on location and alarm packets
+pub const GPS_INFO_LEN: usize = 18;
+
+/// Amount of bytes of the cell tower info on location and alarm packets
+pub const CELL_TOWER_LEN: usize = 8;
+
+/// Amount of bytes of the status info on status and alarm packets, not counting
+/// the language byte after it, that is ignored and not sent by some models
+const STATUS_LEN: usize = 4;
+
+/// CRC-ITU (CRC-16/X-25) used by GT06 to check packets, calculated from the
+/// packet length to the serial number
+pub fn crc_itu(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+
+    for byte in bytes {
+        crc ^= *byte as u16;
+
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0x8408
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    !crc
+}
+
+/// Acknowledgement of a packet, GT06 trackers wait for it after sending a login
+/// packet before reporting positions, and resend status and alarm packets until
+/// they are acknowledged.
+pub fn ack(protocol: u8, serial: u16) -> Box<[u8]> {
+    let [serial_hi, serial_lo] = serial.to_be_bytes();
+
+    let content = [0x05, protocol, serial_hi, serial_lo];
+    let [crc_hi, crc_lo] = crc_itu(&content).to_be_bytes();
+
+    Box::new([
+        0x78, 0x78, 0x05, protocol, serial_hi, serial_lo, crc_hi, crc_lo, 0x0D, 0x0A,
+    ])
+}
+
+/// parses the 6 bytes date time (YY MM DD hh mm ss), in UTC
+pub fn parse_timestamp(bytes: &[u8]) -> Result<DateTime<Utc>, String> {
+    match bytes {
+        [year, month, day, hour, minute, second, ..] => Utc
+            .with_ymd_and_hms(
+                2000 + *year as i32,
+                *month as u32,
+                *day as u32,
+                *hour as u32,
+                *minute as u32,
+                *second as u32,
+            )
+            .single()
+            .ok_or("invalid date time".to_string()),
+        _ => Err("incomplete date time".to_string()),
+    }
+}
+
+/// Parses the GPS info shared by location and alarm packets
+///
+/// | 6         | 1                        | 4   | 4   | 1     | 2              |
+/// |-----------|--------------------------|-----|-----|-------|----------------|
+/// | date time | info length + satellites | lat | lng | speed | course, status |
+pub fn parse_gps_info(bytes: &[u8]) -> Result<LocationMsg, String> {
+    if bytes.len() < GPS_INFO_LEN {
+        return Err("incomplete GPS info".to_string());
+    }
+
+    let lat = u32::from_be_bytes([bytes[7], bytes[8], bytes[9], bytes[10]]);
+    let lng = u32::from_be_bytes([bytes[11], bytes[12], bytes[13], bytes[14]]);
+    let course_and_status = u16::from_be_bytes([bytes[16], bytes[17]]);
+
+    // coordinates are sent in 1/30000 of a minute
+    let mut lat = lat as f64 / 30000.0 / 60.0;
+    let mut lng = lng as f64 / 30000.0 / 60.0;
+
+    if course_and_status & (1 << 10) == 0 {
+        lat *= -1.0;
+    }
+
+    if course_and_status & (1 << 11) != 0 {
+        lng *= -1.0;
+    }
+
+    Ok(LocationMsg {
+        lat,
+        lng,
+        speed: bytes[15] as f64,
+        direction: (course_and_status & 0x03FF) as i32,
+        satellites: bytes[6] & 0x0F,
+        positioned: course_and_status & (1 << 12) != 0,
+        cell_tower: None,
+        acc: None,
+        timestamp: parse_timestamp(&bytes[..6])?,
+    })
+}
+
+/// Parses the cell tower info (MCC 2 bytes, MNC 1 byte, LAC 2 bytes, Cell ID 3 bytes)
+pub fn parse_cell_tower(bytes: &[u8]) -> Result<CellTower, String> {
+    if bytes.len() < CELL_TOWER_LEN {
+        return Err("incomplete cell tower info".to_string());
+    }
+
+    Ok(CellTower {
+        mcc: u16::from_be_bytes([bytes[0], bytes[1]]) as i32,
+        mnc: bytes[2] as i32,
+        lac: u16::from_be_bytes([bytes[3], bytes[4]]) as i32,
+        cell_id: u32::from_be_bytes([0, bytes[5], bytes[6], bytes[7]]) as i32,
+    })
+}
+
+fn parse_alarm(code: u8) -> Option<Alarm> {
+    match code {
+        0x00 => None,
+        0x01 => Some(Alarm::Sos),
+        0x02 => Some(Alarm::PowerCut),
+        0x03 => Some(Alarm::Shock),
+        0x04 => Some(Alarm::FenceIn),
+        0x05 => Some(Alarm::FenceOut),
+        code => Some(Alarm::Other(code)),
+    }
+}
+
+/// Parses the terminal info byte, where bits 3 to 5 are the alarm being raised
+fn parse_terminal_info(byte: u8) -> TerminalInfo {
+    let alarm = match (byte >> 3) & 0b111 {
+        0b000 => None,
+        0b001 => Some(Alarm::Shock),
+        0b010 => Some(Alarm::PowerCut),
+        0b011 => Some(Alarm::LowBattery),
+        0b100 => Some(Alarm::Sos),
+        code => Some(Alarm::Other(code)),
+    };
+
+    TerminalInfo {
+        oil_and_electricity_disconnected: byte & (1 << 7) != 0,
+        gps_tracking: byte & (1 << 6) != 0,
+        alarm,
+        charging: byte & (1 << 2) != 0,
+        acc: byte & (1 << 1) != 0,
+        armed: byte & 1 != 0,
+    }
+}
+
+/// Parses the status info shared by status and alarm packets
+///
+/// | 1             | 1             | 1          | 1     | 1        |
+/// |---------------|---------------|------------|-------|----------|
+/// | terminal info | voltage level | GSM signal | alarm | language |
+pub fn parse_status(bytes: &[u8]) -> Result<StatusMsg, String> {
+    if bytes.len() < STATUS_LEN {
+        return Err("incomplete status info".to_string());
+    }
+
+    Ok(StatusMsg {
+        terminal_info: parse_terminal_info(bytes[0]),
+        voltage_level: bytes[1],
+        gsm_signal: bytes[2],
+        alarm: parse_alarm(bytes[3]),
+    })
+}
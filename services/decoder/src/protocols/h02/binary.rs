@@ -0,0 +1,207 @@
+use super::utils;
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use shared::dto::decoder::h02::LocationMsg;
+
+/// Minimum amount of bytes of a binary location, every field after this is model
+/// dependent (LBS, mileage, external voltage, etc) and ignored for now
+const MIN_LOCATION_LEN: usize = 29;
+
+/// Bit on the coordinates flags nibble that is set if the location is valid
+const FLAG_VALID: u8 = 0b0010;
+
+/// Bit on the coordinates flags nibble that is set for north latitudes
+const FLAG_NORTH: u8 = 0b0100;
+
+/// Bit on the coordinates flags nibble that is set for east longitudes
+const FLAG_EAST: u8 = 0b1000;
+
+/// The fields of a binary H02 location, most fields are BCD encoded so they
+/// are kept as their hex representation, which are their decimal digits.
+///
+/// Binary location layout (bytes):
+///
+/// | 1 | 5        | 3      | 3      | 4        | 1       | 5                | 3                | 4      |
+/// |---|----------|--------|--------|----------|---------|------------------|------------------|--------|
+/// | $ | imei/id  | hhmmss | ddmmyy | ddmmmmmm | battery | dddmmmmmm + flag | speed + course   | status |
+struct BinaryLocationPackets<'a> {
+    imei: String,
+    time: String,
+    date: String,
+    lat: String,
+    lng: String,
+    flags: u8,
+    speed: String,
+    direction_degrees: String,
+    status: &'a [u8],
+}
+
+impl<'a> TryFrom<&'a [u8]> for BinaryLocationPackets<'a> {
+    type Error = String;
+
+    fn try_from(packets: &'a [u8]) -> Result<Self, Self::Error> {
+        if packets.len() < MIN_LOCATION_LEN {
+            return Err("incomplete binary location message".to_string());
+        }
+
+        let lng_and_flags = hex::encode(&packets[17..22]);
+        let speed_and_direction = hex::encode(&packets[22..25]);
+
+        Ok(Self {
+            imei: hex::encode(&packets[1..6]),
+            time: hex::encode(&packets[6..9]),
+            date: hex::encode(&packets[9..12]),
+            lat: hex::encode(&packets[12..16]),
+            lng: lng_and_flags[..9].to_string(),
+            flags: packets[21] & 0x0F,
+            speed: speed_and_direction[..3].to_string(),
+            direction_degrees: speed_and_direction[3..].to_string(),
+            status: &packets[25..29],
+        })
+    }
+}
+
+impl BinaryLocationPackets<'_> {
+    fn parse_direction(&self) -> Result<i32, &str> {
+        self.direction_degrees
+            .parse::<i32>()
+            .or(Err("failed to parse direction degrees to int"))
+    }
+
+    fn parse_speed(&self) -> Result<f64, &str> {
+        let s = self
+            .speed
+            .parse::<f64>()
+            .or(Err("failed to parse speed to float in km/h"))?;
+
+        // convert knots/h to km/h
+        Ok(s * 1.852)
+    }
+
+    /// "ddmmmmmm" to the "ddmm.mmmm" format of text messages
+    fn parse_lat(&self) -> Result<f64, String> {
+        let mut lat = utils::str_to_lat(&format!("{}.{}", &self.lat[..4], &self.lat[4..]))?;
+
+        if self.flags & FLAG_NORTH == 0 {
+            lat *= -1.0
+        }
+
+        Ok(lat)
+    }
+
+    /// "dddmmmmmm" to the "dddmm.mmmm" format of text messages
+    fn parse_lng(&self) -> Result<f64, String> {
+        let mut lng = utils::str_to_lng(&format!("{}.{}", &self.lng[..5], &self.lng[5..]))?;
+
+        if self.flags & FLAG_EAST == 0 {
+            lng *= -1.0
+        }
+
+        Ok(lng)
+    }
+
+    fn decode(&self) -> Result<LocationMsg, String> {
+        if self.flags & FLAG_VALID == 0 {
+            return Err("invalid location data (data valid bit != A)".to_string());
+        }
+
+        Ok(LocationMsg {
+            lat: self.parse_lat()?,
+            lng: self.parse_lng()?,
+            speed: self.parse_speed()?,
+            status: utils::decode_status(self.status)?,
+            direction: self.parse_direction()?,
+            timestamp: utils::parse_timestamp(&self.date, &self.time)?,
+        })
+    }
+}
+
+impl TryFrom<&[u8]> for Decoded<LocationMsg> {
+    type Error = String;
+
+    fn try_from(packets: &[u8]) -> Result<Self, Self::Error> {
+        let packets = BinaryLocationPackets::try_from(packets)?;
+
+        Ok(Decoded {
+            data: packets.decode()?,
+            imei: packets.imei.clone(),
+            response: None,
+            protocol: Protocol::H02,
+            event_type: TrackerEvent::Location,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::protocols::common::Decoded;
+    use chrono::{TimeZone, Utc};
+    use shared::dto::decoder::h02::LocationMsg;
+
+    /// sample location from `docs/h02/protocol_docs/icargps_protocol.xlsx`, with
+    /// the leading zero of the time field that is missing on the spreadsheet
+    const SAMPLE_LOCATION: &str = "247301000001053646200617223612120611351771\
+        0E023329FFFFFBFFFF001546000000EA01CC0100000000008C47";
+
+    fn decode_hex(s: &str) -> Result<Decoded<LocationMsg>, String> {
+        hex::decode(s).unwrap().as_slice().try_into()
+    }
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 0.000001, "{a} != {b}");
+    }
+
+    #[test]
+    fn decodes_sample_location() {
+        let decoded = decode_hex(SAMPLE_LOCATION).unwrap();
+
+        assert_eq!(decoded.imei, "7301000001");
+        assert_eq!(decoded.get_routing_key(), "h02.location.7301000001");
+
+        let location = decoded.data;
+
+        assert_close(location.lat, 22.0 + 36.1212 / 60.0);
+        assert_close(location.lng, 113.0 + 51.7710 / 60.0);
+        assert_close(location.speed, 23.0 * 1.852);
+        assert_eq!(location.direction, 329);
+        assert_eq!(
+            location.timestamp,
+            Utc.with_ymd_and_hms(2017, 6, 20, 5, 36, 46).unwrap()
+        );
+
+        // FFFFFBFF: only the engine flag, on the third byte, is active
+        assert!(location.status.engine);
+        assert!(!location.status.door_open);
+        assert!(!location.status.has_alarm());
+    }
+
+    #[test]
+    fn decodes_short_location() {
+        let decoded = decode_hex(&SAMPLE_LOCATION[..64]).unwrap();
+
+        assert_eq!(decoded.imei, "7301000001");
+        assert_eq!(decoded.data.direction, 329);
+    }
+
+    #[test]
+    fn decodes_south_west_location() {
+        // flags 0x2: valid, south and west
+        let sample = SAMPLE_LOCATION.replacen("17710E", "177102", 1);
+        let location = decode_hex(&sample).unwrap().data;
+
+        assert_close(location.lat, -(22.0 + 36.1212 / 60.0));
+        assert_close(location.lng, -(113.0 + 51.7710 / 60.0));
+    }
+
+    #[test]
+    fn rejects_invalid_location() {
+        // flags 0xC: north and east but not valid
+        let sample = SAMPLE_LOCATION.replacen("17710E", "17710C", 1);
+
+        assert!(decode_hex(&sample).is_err());
+    }
+
+    #[test]
+    fn rejects_incomplete_location() {
+        assert!(decode_hex(&SAMPLE_LOCATION[..40]).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Utc};
+use shared::dto::decoder::command::Command;
+
+/// The command name and arguments, without the time of the command
+fn command_parts(command: &Command) -> Vec<String> {
+    let parts: &[&str] = match command {
+        Command::EngineStop => &["S20", "1", "1"],
+        Command::EngineResume => &["S20", "0", "0"],
+        Command::Reboot => &["R1"],
+        Command::PositionInterval { seconds } => {
+            return vec!["D1".to_string(), seconds.to_string()]
+        }
+        Command::Custom { content } => return vec![content.clone()],
+    };
+
+    parts.iter().map(|part| part.to_string()).collect()
+}
+
+/// Encodes a command to the tracker, the time of the command (hhmmss)
+/// is echoed back by the tracker on its `V4` acknowledgement.
+///
+/// `*HQ,imei,command,hhmmss,args...#` eg: `*HQ,7301000001,S20,031738,1,1#`
+///
+/// custom commands are sent as is, eg: `*HQ,imei,content#`
+pub fn encode(imei: &str, command: &Command, time: DateTime<Utc>) -> Box<[u8]> {
+    let mut parts = command_parts(command);
+
+    if !matches!(command, Command::Custom { .. }) {
+        parts.insert(1, time.format("%H%M%S").to_string());
+    }
+
+    format!("*HQ,{},{}#", imei, parts.join(","))
+        .into_bytes()
+        .into_boxed_slice()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode;
+    use chrono::{TimeZone, Utc};
+    use shared::dto::decoder::command::Command;
+
+    #[test]
+    fn encodes_commands() {
+        let time = Utc.with_ymd_and_hms(2024, 2, 20, 3, 17, 38).unwrap();
+
+        let cases = [
+            (Command::EngineStop, "*HQ,7301000001,S20,031738,1,1#"),
+            (Command::EngineResume, "*HQ,7301000001,S20,031738,0,0#"),
+            (Command::Reboot, "*HQ,7301000001,R1,031738#"),
+            (
+                Command::PositionInterval { seconds: 30 },
+                "*HQ,7301000001,D1,031738,30#",
+            ),
+            (
+                Command::Custom {
+                    content: "SCF,031738,0,0".to_string(),
+                },
+                "*HQ,7301000001,SCF,031738,0,0#",
+            ),
+        ];
+
+        for (command, expected) in cases {
+            assert_eq!(
+                encode("7301000001", &command, time).as_ref(),
+                expected.as_bytes()
+            );
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use shared::dto::decoder::h02::CommandAckMsg;
+
+impl TryFrom<Vec<&str>> for Decoded<CommandAckMsg> {
+    type Error = String;
+
+    /// `imei,V4,command,args...` eg: `7301000001,V4,S20,031738,1,1`
+    fn try_from(parts: Vec<&str>) -> Result<Self, Self::Error> {
+        if parts.len() < 3 {
+            return Err("incomplete command acknowledgement message".to_string());
+        }
+
+        let data = CommandAckMsg {
+            command: parts[2].to_string(),
+            args: parts[3..].iter().map(|arg| arg.to_string()).collect(),
+        };
+
+        Ok(Decoded {
+            data,
+            imei: parts[0].to_string(),
+            response: None,
+            protocol: Protocol::H02,
+            event_type: TrackerEvent::CommandAck,
+        })
+    }
+}
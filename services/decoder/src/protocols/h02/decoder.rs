@@ -1,20 +1,46 @@
 use super::{heartbeat::HeartbeatMsg, utils};
 use crate::protocols::common::Decoded;
-use shared::dto::decoder::h02::LocationMsg;
+use shared::dto::decoder::h02::{CommandAckMsg, LbsMsg, LinkMsg, LocationMsg};
 use std::str::{self, from_utf8};
 
 mod msg_ids {
     pub const LOCATION: &str = "V1";
     pub const HEARTBEAT: &str = "HTBT";
+    pub const LBS: &str = "NBR";
+    pub const LINK: &str = "LINK";
+    pub const COMMAND_ACK: &str = "V4";
 }
 
 /// All possible message types decodable from the H02 tracker protocol
 pub enum Message {
     Heartbeat(Decoded<HeartbeatMsg>),
     Location(Decoded<LocationMsg>),
+    Lbs(Decoded<LbsMsg>),
+    LinkStatus(Decoded<LinkMsg>),
+    CommandAck(Decoded<CommandAckMsg>),
 }
 
+impl Message {
+    /// imei of the tracker who sent the message, every H02 message contains it
+    pub fn imei(&self) -> &str {
+        match self {
+            Message::Heartbeat(decoded) => &decoded.imei,
+            Message::Location(decoded) => &decoded.imei,
+            Message::Lbs(decoded) => &decoded.imei,
+            Message::LinkStatus(decoded) => &decoded.imei,
+            Message::CommandAck(decoded) => &decoded.imei,
+        }
+    }
+}
+
+/// Start of H02 binary messages, only used for locations
+const BINARY_MSG_PREFIX: u8 = b'$';
+
 pub fn decode(packets: &[u8]) -> Result<Message, String> {
+    if packets.first() == Some(&BINARY_MSG_PREFIX) {
+        return Ok(Message::Location(packets.try_into()?));
+    }
+
     let packets = from_utf8(packets)
         .or(Err("failed to read packets as utf8"))?
         .to_string();
@@ -32,6 +58,106 @@ pub fn decode(packets: &[u8]) -> Result<Message, String> {
     match message_type {
         msg_ids::HEARTBEAT => Ok(Message::Heartbeat(parts.try_into()?)),
         msg_ids::LOCATION => Ok(Message::Location(parts.try_into()?)),
+        msg_ids::LBS => Ok(Message::Lbs(parts.try_into()?)),
+        msg_ids::LINK => Ok(Message::LinkStatus(parts.try_into()?)),
+        msg_ids::COMMAND_ACK => Ok(Message::CommandAck(parts.try_into()?)),
         _ => Err("unknown message type".to_string()),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, Message};
+
+    #[test]
+    fn decodes_lbs() {
+        // sample from `docs/h02/protocol_docs/H02_protocol_en.doc`
+        let packets = b"*HQ,7893267560,NBR,081606,460,0,1,4,9338,3692,150,9338,3691,145,9338,3690,140, 9338,3692,139,220513,FFFFFBFF,5#";
+
+        let Ok(Message::Lbs(decoded)) = decode(packets) else {
+            panic!("not decoded as lbs");
+        };
+
+        assert_eq!(decoded.get_routing_key(), "h02.lbs.7893267560");
+        assert_eq!(decoded.data.mcc, 460);
+        assert_eq!(decoded.data.timing_advance, 1);
+        assert_eq!(decoded.data.cell_towers.len(), 4);
+        assert_eq!(decoded.data.cell_towers[3].lac, 9338);
+        assert_eq!(decoded.data.cell_towers[3].rx_level, 139);
+        assert_eq!(decoded.data.battery_level, Some(5));
+        assert_eq!(
+            decoded.data.timestamp.to_rfc3339(),
+            "2013-05-22T08:16:06+00:00"
+        );
+    }
+
+    #[test]
+    fn rejects_lbs_with_invalid_cell_count() {
+        for cell_count in ["-1", "7", "18446744073709551615", "x"] {
+            let packets = format!(
+                "*HQ,7893267560,NBR,081606,460,0,1,{cell_count},9338,3692,150,220513,FFFFFBFF,5#"
+            );
+
+            assert!(decode(packets.as_bytes()).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_lbs_with_missing_cells() {
+        let packets = b"*HQ,7893267560,NBR,081606,460,0,1,4,9338,3692,150,220513,FFFFFBFF#";
+
+        assert!(decode(packets).is_err());
+    }
+
+    #[test]
+    fn decodes_link() {
+        let packets = b"*HQ,867232051148352,LINK,112725,22,7,84,0,0,310523,FFFFFBFF#";
+
+        let Ok(Message::LinkStatus(decoded)) = decode(packets) else {
+            panic!("not decoded as link");
+        };
+
+        assert_eq!(decoded.get_routing_key(), "h02.link_status.867232051148352");
+        assert_eq!(decoded.data.gsm_signal, 22);
+        assert_eq!(decoded.data.satellites, 7);
+        assert_eq!(decoded.data.battery_level, 84);
+    }
+
+    #[test]
+    fn decodes_command_ack() {
+        // sample from `docs/h02/protocol_docs/icargps_protocol.xlsx`
+        let packets = b"*HQ,7301000001,V4,S20,031738,1,1#";
+
+        let Ok(Message::CommandAck(decoded)) = decode(packets) else {
+            panic!("not decoded as command ack");
+        };
+
+        assert_eq!(decoded.get_routing_key(), "h02.command_ack.7301000001");
+        assert_eq!(decoded.data.command, "S20");
+        assert_eq!(decoded.data.args, vec!["031738", "1", "1"]);
+    }
+
+    #[test]
+    fn replies_to_alarms() {
+        let no_alarm =
+            b"*HQ,7301000001,V1,080526,A,2234.0612,N,11351.7667,E,000.00,243,160519,FFFFFBFF#";
+        let sos_alarm =
+            b"*HQ,7301000001,V1,080526,A,2234.0612,N,11351.7667,E,000.00,243,160519,FFDFFBFF#";
+
+        let Ok(Message::Location(decoded)) = decode(no_alarm) else {
+            panic!("not decoded as location");
+        };
+
+        assert!(decoded.response.is_none());
+
+        let Ok(Message::Location(decoded)) = decode(sos_alarm) else {
+            panic!("not decoded as location");
+        };
+
+        assert!(decoded.data.status.sos_alarm);
+
+        let response = String::from_utf8(decoded.response.unwrap().to_vec()).unwrap();
+        assert!(response.starts_with("*HQ,7301000001,V4,V1,"));
+        assert!(response.ends_with('#'));
+    }
+}
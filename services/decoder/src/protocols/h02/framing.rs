@@ -0,0 +1,479 @@
+/// Start of every H02 text message
+const MSG_PREFIX: &[u8] = b"*HQ";
+
+/// End of every H02 text message
+const MSG_SUFFIX: u8 = b'#';
+
+/// Start of every H02 binary message
+const BINARY_MSG_PREFIX: u8 = b'$';
+
+/// Known lengths of H02 binary messages, binary messages have no suffix, so
+/// the only way to tell where a message ends is by its length, depending on
+/// the model a binary location has 32 bytes, 45 bytes with LBS and mileage
+/// info or 47 bytes when also containing the external voltage.
+const BINARY_MSG_LENGTHS: [usize; 3] = [32, 45, 47];
+
+/// Bytes after a binary message needed to tell if a new frame starts there, the `$`
+/// prefix and the 5 bytes of the IMEI, which is the same for every message of a
+/// connection, so a `$` within the data of a longer message is not mistaken for
+/// the start of the next message
+const NEXT_FRAME_PEEK_LEN: usize = 6;
+
+/// The maximum amount of bytes a incomplete frame can have before its
+/// considered garbage and discarded, a valid H02 message is way smaller
+/// than this, so if no suffix was found by then it will never be.
+const MAX_FRAME_SIZE: usize = 1024;
+
+/// A per connection buffer to reassemble H02 text and binary frames from a TCP stream.
+///
+/// TCP does not preserve message boundaries, a single read might contain
+/// a partial frame or multiple frames, so every read is appended to this
+/// buffer and complete frames are extracted with `next_frame`, any trailing
+/// partial frame is kept until the next read completes it.
+#[derive(Default)]
+pub struct FrameBuffer {
+    buffer: Vec<u8>,
+
+    /// length of the binary messages of the connection, known once a binary
+    /// message is followed by another frame, as trackers of the same model
+    /// always send binary messages of the same length
+    binary_msg_len: Option<usize>,
+
+    /// if no more bytes will be appended, such as for UDP datagrams
+    is_complete: bool,
+}
+
+impl FrameBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// appends the bytes read from the connection to the buffer
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Marks that no more bytes will be appended to the buffer, so a binary frame
+    /// ending exactly at a known binary message length is complete, instead of
+    /// possibly being the start of a longer binary message
+    pub fn finish(&mut self) {
+        self.is_complete = true;
+    }
+
+    /// Removes and returns the next complete frame on the buffer, including its
+    /// `*HQ` prefix and `#` suffix for text frames or `$` prefix for binary frames,
+    /// returns `None` if there is no complete frame.
+    ///
+    /// Invalid bytes before a frame are discarded and returned as a `Err` so the
+    /// caller can count them as invalid packets, whitespace between frames (such
+    /// as a newline after the `#` suffix, sent by some models) is silently ignored.
+    pub fn next_frame(&mut self) -> Option<Result<Vec<u8>, String>> {
+        let leading_whitespace = self
+            .buffer
+            .iter()
+            .take_while(|b| b.is_ascii_whitespace())
+            .count();
+
+        self.buffer.drain(..leading_whitespace);
+
+        if self.buffer.is_empty() {
+            return None;
+        }
+
+        let start = match find_frame_start(&self.buffer) {
+            Some(start) => start,
+            None => {
+                // keep the bytes that might be the start of a prefix split between reads
+                let keep = partial_prefix_len(&self.buffer);
+                let discarded = self.buffer.len() - keep;
+
+                if discarded == 0 {
+                    return None;
+                }
+
+                self.buffer.drain(..discarded);
+                return Some(Err(format!(
+                    "discarded {discarded} bytes without a *HQ or $ message prefix"
+                )));
+            }
+        };
+
+        if start > 0 {
+            self.buffer.drain(..start);
+            return Some(Err(format!(
+                "discarded {start} bytes before the *HQ or $ message prefix"
+            )));
+        }
+
+        if self.buffer[0] == BINARY_MSG_PREFIX {
+            return self.next_binary_frame();
+        }
+
+        self.next_text_frame()
+    }
+
+    fn next_text_frame(&mut self) -> Option<Result<Vec<u8>, String>> {
+        let end = self.buffer.iter().position(|b| *b == MSG_SUFFIX);
+
+        // a new prefix before the suffix means the current frame was truncated
+        // by the tracker, discard it so the following frame is not lost
+        if let Some(next_start) = find_frame_start(&self.buffer[1..]).map(|i| i + 1) {
+            if end.is_none_or(|end| next_start < end) {
+                self.buffer.drain(..next_start);
+                return Some(Err(format!(
+                    "discarded truncated frame of {next_start} bytes"
+                )));
+            }
+        }
+
+        match end {
+            Some(end) => Some(Ok(self.buffer.drain(..=end).collect())),
+            None if self.buffer.len() > MAX_FRAME_SIZE => {
+                let discarded = self.buffer.len();
+                self.buffer.clear();
+
+                Some(Err(format!(
+                    "discarded {discarded} bytes without a # message suffix"
+                )))
+            }
+            None => None,
+        }
+    }
+
+    /// Since binary frames have no suffix, the length of a binary frame is only known once
+    /// another frame starts right after one of the known binary message lengths, a frame
+    /// is also complete when the buffer ends exactly at the length of the previous binary
+    /// messages of the connection or at the longest known length.
+    ///
+    /// a buffer ending at a shorter known length might be a longer message split between
+    /// reads, so it is kept until more bytes are read, as emitting it early would split a
+    /// long message in two and the rest of it would be counted as invalid packets.
+    fn next_binary_frame(&mut self) -> Option<Result<Vec<u8>, String>> {
+        let len = self.buffer.len();
+        let max_len = BINARY_MSG_LENGTHS[BINARY_MSG_LENGTHS.len() - 1];
+
+        let followed_by_frame = BINARY_MSG_LENGTHS
+            .into_iter()
+            .find(|l| len >= l + NEXT_FRAME_PEEK_LEN && self.is_next_frame_at(*l));
+
+        if let Some(frame_len) = followed_by_frame {
+            self.binary_msg_len = Some(frame_len);
+            return Some(Ok(self.buffer.drain(..frame_len).collect()));
+        }
+
+        let is_known_len = BINARY_MSG_LENGTHS.contains(&len);
+
+        if len == max_len || Some(len) == self.binary_msg_len || (self.is_complete && is_known_len)
+        {
+            return Some(Ok(self.buffer.drain(..).collect()));
+        }
+
+        // no known length is followed by a frame, so the message has unknown trailing data,
+        // return the length of the connection messages or the shortest length, as it contains
+        // all the location fields, and let the following bytes be discarded as invalid
+        if len >= max_len + NEXT_FRAME_PEEK_LEN || (self.is_complete && len > max_len) {
+            let frame_len = self.binary_msg_len.unwrap_or(BINARY_MSG_LENGTHS[0]);
+            return Some(Ok(self.buffer.drain(..frame_len).collect()));
+        }
+
+        None
+    }
+
+    /// if a text frame or a binary frame of the same IMEI as the binary
+    /// frame at the start of the buffer starts at `offset`
+    fn is_next_frame_at(&self, offset: usize) -> bool {
+        let next = &self.buffer[offset..];
+
+        match next.first() {
+            Some(&BINARY_MSG_PREFIX) => {
+                next.len() >= NEXT_FRAME_PEEK_LEN
+                    && next[1..NEXT_FRAME_PEEK_LEN] == self.buffer[1..NEXT_FRAME_PEEK_LEN]
+            }
+            Some(_) => next.starts_with(MSG_PREFIX),
+            None => false,
+        }
+    }
+}
+
+/// position of the first text message prefix or binary message prefix
+fn find_frame_start(bytes: &[u8]) -> Option<usize> {
+    (0..bytes.len()).find(|i| bytes[*i] == BINARY_MSG_PREFIX || bytes[*i..].starts_with(MSG_PREFIX))
+}
+
+/// length of the longest suffix of `bytes` that is also a start of the message prefix,
+/// eg: `2` for `"...*H"`
+fn partial_prefix_len(bytes: &[u8]) -> usize {
+    (1..MSG_PREFIX.len())
+        .rev()
+        .find(|len| bytes.len() >= *len && bytes.ends_with(&MSG_PREFIX[..*len]))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrameBuffer;
+
+    const LOCATION: &[u8] =
+        b"*HQ,867232051148352,V1,044639,A,2027.93290,S,05434.94389,W,000.00,000,110722,FFFFFBFF#";
+
+    const HEARTBEAT: &[u8] = b"*HQ,867232051148352,HTBT#";
+
+    /// 32 bytes binary location
+    const BINARY_LOCATION: &[u8] = &[
+        0x24, 0x73, 0x01, 0x00, 0x00, 0x01, 0x05, 0x36, 0x46, 0x20, 0x06, 0x17, 0x22, 0x36, 0x12,
+        0x12, 0x06, 0x11, 0x35, 0x17, 0x71, 0x0E, 0x02, 0x33, 0x29, 0xFF, 0xFF, 0xFB, 0xFF, 0xFF,
+        0x00, 0x47,
+    ];
+
+    fn drain_frames(buffer: &mut FrameBuffer) -> Vec<Result<Vec<u8>, String>> {
+        std::iter::from_fn(|| buffer.next_frame()).collect()
+    }
+
+    #[test]
+    fn single_frame() {
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(LOCATION);
+
+        assert_eq!(drain_frames(&mut buffer), vec![Ok(LOCATION.to_vec())]);
+    }
+
+    #[test]
+    fn frame_split_across_reads() {
+        let mut buffer = FrameBuffer::new();
+
+        for chunk in LOCATION.chunks(7) {
+            assert!(drain_frames(&mut buffer).is_empty());
+            buffer.extend(chunk);
+        }
+
+        assert_eq!(drain_frames(&mut buffer), vec![Ok(LOCATION.to_vec())]);
+    }
+
+    #[test]
+    fn prefix_split_across_reads() {
+        let mut buffer = FrameBuffer::new();
+
+        buffer.extend(b"*H");
+        assert!(drain_frames(&mut buffer).is_empty());
+
+        buffer.extend(&HEARTBEAT[2..]);
+        assert_eq!(drain_frames(&mut buffer), vec![Ok(HEARTBEAT.to_vec())]);
+    }
+
+    #[test]
+    fn concatenated_frames() {
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(&[LOCATION, HEARTBEAT, LOCATION].concat());
+
+        assert_eq!(
+            drain_frames(&mut buffer),
+            vec![
+                Ok(LOCATION.to_vec()),
+                Ok(HEARTBEAT.to_vec()),
+                Ok(LOCATION.to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn concatenated_frames_with_trailing_partial_frame() {
+        let mut buffer = FrameBuffer::new();
+        let (head, tail) = LOCATION.split_at(20);
+
+        buffer.extend(&[HEARTBEAT, b"\r\n", head].concat());
+        assert_eq!(drain_frames(&mut buffer), vec![Ok(HEARTBEAT.to_vec())]);
+
+        buffer.extend(tail);
+        assert_eq!(drain_frames(&mut buffer), vec![Ok(LOCATION.to_vec())]);
+    }
+
+    #[test]
+    fn garbage_before_frame_is_reported() {
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(&[b"GET / HTTP/1.1", HEARTBEAT].concat());
+
+        let frames = drain_frames(&mut buffer);
+
+        assert_eq!(frames.len(), 2);
+        assert!(frames[0].is_err());
+        assert_eq!(frames[1], Ok(HEARTBEAT.to_vec()));
+    }
+
+    #[test]
+    fn truncated_frame_is_reported() {
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(&[&LOCATION[..30], HEARTBEAT].concat());
+
+        let frames = drain_frames(&mut buffer);
+
+        assert_eq!(frames.len(), 2);
+        assert!(frames[0].is_err());
+        assert_eq!(frames[1], Ok(HEARTBEAT.to_vec()));
+    }
+
+    #[test]
+    fn oversized_frame_is_discarded() {
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(b"*HQ,");
+        buffer.extend(&[b'0'; 2048]);
+
+        let frames = drain_frames(&mut buffer);
+
+        assert_eq!(frames.len(), 1);
+        assert!(frames[0].is_err());
+
+        buffer.extend(HEARTBEAT);
+        assert_eq!(drain_frames(&mut buffer), vec![Ok(HEARTBEAT.to_vec())]);
+    }
+
+    #[test]
+    fn binary_frame() {
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(BINARY_LOCATION);
+
+        // might be the start of a longer binary message
+        assert!(drain_frames(&mut buffer).is_empty());
+
+        buffer.extend(HEARTBEAT);
+        assert_eq!(
+            drain_frames(&mut buffer),
+            vec![Ok(BINARY_LOCATION.to_vec()), Ok(HEARTBEAT.to_vec())]
+        );
+    }
+
+    #[test]
+    fn binary_frame_of_complete_input() {
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(BINARY_LOCATION);
+        buffer.finish();
+
+        assert_eq!(
+            drain_frames(&mut buffer),
+            vec![Ok(BINARY_LOCATION.to_vec())]
+        );
+    }
+
+    #[test]
+    fn binary_frame_split_across_reads() {
+        let mut buffer = FrameBuffer::new();
+        let (head, tail) = BINARY_LOCATION.split_at(10);
+
+        buffer.extend(head);
+        assert!(drain_frames(&mut buffer).is_empty());
+
+        buffer.extend(tail);
+        assert!(drain_frames(&mut buffer).is_empty());
+
+        // the length of the first message is known once the next one starts,
+        // and from then on every message of the connection has the same length
+        buffer.extend(BINARY_LOCATION);
+        assert_eq!(
+            drain_frames(&mut buffer),
+            vec![Ok(BINARY_LOCATION.to_vec()), Ok(BINARY_LOCATION.to_vec())]
+        );
+    }
+
+    #[test]
+    fn binary_and_text_frames_concatenated() {
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(&[BINARY_LOCATION, HEARTBEAT, BINARY_LOCATION, BINARY_LOCATION].concat());
+
+        assert_eq!(
+            drain_frames(&mut buffer),
+            vec![
+                Ok(BINARY_LOCATION.to_vec()),
+                Ok(HEARTBEAT.to_vec()),
+                Ok(BINARY_LOCATION.to_vec()),
+                Ok(BINARY_LOCATION.to_vec())
+            ]
+        );
+    }
+
+    #[test]
+    fn long_binary_frames_concatenated() {
+        let long_location = [BINARY_LOCATION, &[0; 13]].concat();
+
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(&[&long_location, BINARY_LOCATION, HEARTBEAT].concat());
+
+        assert_eq!(
+            drain_frames(&mut buffer),
+            vec![
+                Ok(long_location),
+                Ok(BINARY_LOCATION.to_vec()),
+                Ok(HEARTBEAT.to_vec())
+            ]
+        );
+    }
+
+    /// binary location with LBS and mileage info, with a `$` where a 32 bytes message would end
+    fn location_45() -> Vec<u8> {
+        let mut location = [BINARY_LOCATION, &[0x11; 13]].concat();
+        location[32] = b'$';
+        location
+    }
+
+    /// binary location with LBS, mileage and external voltage info
+    fn location_47() -> Vec<u8> {
+        [BINARY_LOCATION, &[0x11; 15]].concat()
+    }
+
+    #[test]
+    fn long_binary_frames_split_at_short_length() {
+        for location in [location_45(), location_47()] {
+            let mut buffer = FrameBuffer::new();
+            let (head, tail) = location.split_at(32);
+
+            buffer.extend(head);
+            assert!(drain_frames(&mut buffer).is_empty());
+
+            buffer.extend(tail);
+            buffer.extend(&location);
+            assert_eq!(
+                drain_frames(&mut buffer),
+                vec![Ok(location.clone()), Ok(location.clone())]
+            );
+        }
+    }
+
+    #[test]
+    fn long_binary_frames_split_across_many_reads() {
+        for location in [location_45(), location_47()] {
+            let mut buffer = FrameBuffer::new();
+            let stream = [&location[..], &location, HEARTBEAT].concat();
+            let mut frames = vec![];
+
+            for chunk in stream.chunks(5) {
+                buffer.extend(chunk);
+                frames.extend(drain_frames(&mut buffer));
+            }
+
+            assert_eq!(
+                frames,
+                vec![
+                    Ok(location.clone()),
+                    Ok(location.clone()),
+                    Ok(HEARTBEAT.to_vec())
+                ]
+            );
+        }
+    }
+
+    #[test]
+    fn long_binary_frames_concatenated_with_prefix_in_data() {
+        let (location_45, location_47) = (location_45(), location_47());
+
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(&[&location_45[..], &location_45, &location_47, HEARTBEAT].concat());
+
+        assert_eq!(
+            drain_frames(&mut buffer),
+            vec![
+                Ok(location_45.clone()),
+                Ok(location_45),
+                Ok(location_47),
+                Ok(HEARTBEAT.to_vec())
+            ]
+        );
+    }
+}
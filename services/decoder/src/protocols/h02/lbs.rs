@@ -0,0 +1,81 @@
+use super::utils;
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use shared::dto::decoder::h02::{CellTower, LbsMsg};
+
+/// amount of fields before the cell towers list
+const FIELDS_BEFORE_CELLS: usize = 7;
+
+/// maximum amount of cell towers of a lbs message, larger counts are from corrupted
+/// messages and would only make the cells slice end way past the message fields
+const MAX_CELL_COUNT: usize = 6;
+
+impl TryFrom<Vec<&str>> for Decoded<LbsMsg> {
+    type Error = String;
+
+    /// `imei,NBR,hhmmss,mcc,mnc,ta,num,[lac,cid,rxlev]*num,ddmmyy,status[,battery]`
+    fn try_from(parts: Vec<&str>) -> Result<Self, Self::Error> {
+        if parts.len() < FIELDS_BEFORE_CELLS {
+            return Err("incomplete lbs message".to_string());
+        }
+
+        let cell_count = parts[6]
+            .trim()
+            .parse::<usize>()
+            .or(Err("failed to parse cell tower count to unsigned int"))?;
+
+        if cell_count > MAX_CELL_COUNT {
+            return Err(format!("invalid lbs cell tower count {cell_count}"));
+        }
+
+        let cells_end = cell_count
+            .checked_mul(3)
+            .and_then(|len| len.checked_add(FIELDS_BEFORE_CELLS))
+            .ok_or("invalid lbs cell tower count")?;
+
+        if parts.len() < cells_end + 2 {
+            return Err("incomplete lbs message".to_string());
+        }
+
+        let cell_towers = parts[FIELDS_BEFORE_CELLS..cells_end]
+            .chunks(3)
+            .map(|cell| {
+                Ok(CellTower {
+                    lac: utils::parse_int(cell[0], "cell lac")?,
+                    cell_id: utils::parse_int(cell[1], "cell id")?,
+                    rx_level: utils::parse_int(cell[2], "cell rx level")?,
+                })
+            })
+            .collect::<Result<Vec<CellTower>, String>>()?;
+
+        let status_bytes =
+            hex::decode(parts[cells_end + 1].trim()).or(Err("failed to parse status bytes"))?;
+
+        let battery_level = match parts.get(cells_end + 2) {
+            Some(battery) => Some(
+                battery
+                    .trim()
+                    .parse::<u8>()
+                    .or(Err("failed to parse battery level"))?,
+            ),
+            None => None,
+        };
+
+        let data = LbsMsg {
+            mcc: utils::parse_int(parts[3], "mcc")?,
+            mnc: utils::parse_int(parts[4], "mnc")?,
+            timing_advance: utils::parse_int(parts[5], "timing advance")?,
+            cell_towers,
+            status: utils::decode_status(&status_bytes)?,
+            battery_level,
+            timestamp: utils::parse_timestamp(parts[cells_end].trim(), parts[2])?,
+        };
+
+        Ok(Decoded {
+            data,
+            imei: parts[0].to_string(),
+            response: None,
+            protocol: Protocol::H02,
+            event_type: TrackerEvent::Lbs,
+        })
+    }
+}
@@ -0,0 +1,34 @@
+use super::utils;
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use shared::dto::decoder::h02::LinkMsg;
+
+impl TryFrom<Vec<&str>> for Decoded<LinkMsg> {
+    type Error = String;
+
+    /// `imei,LINK,hhmmss,gsm_signal,satellites,battery,steps,turnovers,ddmmyy,status`
+    fn try_from(parts: Vec<&str>) -> Result<Self, Self::Error> {
+        if parts.len() < 10 {
+            return Err("incomplete link message".to_string());
+        }
+
+        let status_bytes = hex::decode(parts[9]).or(Err("failed to parse status bytes"))?;
+
+        let data = LinkMsg {
+            gsm_signal: utils::parse_int(parts[3], "gsm signal")?,
+            satellites: utils::parse_int(parts[4], "satellites")?,
+            battery_level: utils::parse_int(parts[5], "battery level")?,
+            steps: utils::parse_int(parts[6], "steps")?,
+            turnovers: utils::parse_int(parts[7], "turnovers")?,
+            status: utils::decode_status(&status_bytes)?,
+            timestamp: utils::parse_timestamp(parts[8], parts[2])?,
+        };
+
+        Ok(Decoded {
+            data,
+            imei: parts[0].to_string(),
+            response: None,
+            protocol: Protocol::H02,
+            event_type: TrackerEvent::LinkStatus,
+        })
+    }
+}
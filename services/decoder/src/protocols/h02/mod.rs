@@ -1,4 +1,10 @@
+pub mod binary;
+pub mod command;
+pub mod command_ack;
 pub mod decoder;
+pub mod framing;
 pub mod heartbeat;
+pub mod lbs;
+pub mod link;
 pub mod location;
 pub mod utils;
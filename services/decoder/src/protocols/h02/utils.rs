@@ -1,3 +1,6 @@
+use chrono::{DateTime, Utc};
+use shared::dto::decoder::h02::Status;
+
 #[derive(PartialEq)]
 enum Coord {
     Lat,
@@ -81,3 +84,138 @@ pub fn str_to_lat(s: &str) -> Result<f64, String> {
 pub fn str_to_lng(s: &str) -> Result<f64, String> {
     str_to_coord(s, Coord::Lng)
 }
+
+/// Decodes the 4 status bytes sent by H02 trackers on location messages,
+/// the status bits use negative logic, so a flag is active when its bit is 0
+pub fn decode_status(status_bytes: &[u8]) -> Result<Status, String> {
+    if status_bytes.len() < 4 {
+        return Err("cannot decoded status bytes, as it does not contain 4 bytes".to_string());
+    }
+
+    let mut binary_str = "".to_string();
+
+    // bytes must be zero padded, otherwise a byte such as 0x0F
+    // would produce less than 8 bits and shift all the flags
+    for byte in &status_bytes[..4] {
+        binary_str.push_str(&format!("{:08b}", byte));
+    }
+
+    let bin_chars: Vec<char> = binary_str.chars().collect();
+
+    let b = |i: usize| -> bool { bin_chars[i] == '0' };
+
+    Ok(Status {
+        // byte 1
+        temperature_alarm: b(0),
+        three_times_pass_error_alarm: b(1),
+        gprs_occlusion_alarm: b(2),
+        oil_and_engine_cut_off: b(3),
+        storage_battery_removal_state: b(4),
+        high_level_sensor1: b(5),
+        high_level_sensor2: b(6),
+        low_level_sensor1_bond_strap: b(7),
+
+        // byte 2
+        gps_receiver_fault_alarm: b(8),
+        analog_quantity_transfinit_alarm: b(9),
+        sos_alarm: b(10),
+        host_powered_by_backup_battery: b(11),
+        storage_battery_removed: b(12),
+        open_circuit_for_gps_antenna: b(13),
+        short_circuit_for_gps_antenna: b(14),
+        low_level_sensor2_bond_strap: b(15),
+
+        // byte 3
+        door_open: b(16),
+        vehicle_fortified: b(17),
+        acc: b(18),
+        // 19: reserved
+        // 20: reserved
+        engine: b(21),
+        custom_alarm: b(22),
+        overspeed: b(23),
+
+        // byte 4
+        theft_alarm: b(24),
+        roberry_alarm: b(25),
+        overspeed_alarm: b(26),
+        illegal_ignition_alarm: b(27),
+        no_entry_cross_border_alarm_in: b(28),
+        gps_antenna_open_circuit_alarm: b(29),
+        gps_antenna_short_circuit_alarm: b(30),
+        no_entry_cross_border_alarm_out: b(31),
+    })
+}
+
+/// Parses a integer field of a H02 text message, `field` is the
+/// name of the field used in the error message
+pub fn parse_int(s: &str, field: &str) -> Result<i32, String> {
+    s.trim()
+        .parse::<i32>()
+        .or(Err(format!("failed to parse {field} to int")))
+}
+
+/// Parses a H02 date in the `ddmmyy` format and time in the `hhmmss` format
+pub fn parse_timestamp(date: &str, time: &str) -> Result<DateTime<Utc>, String> {
+    if date.len() < 6 {
+        return Err("cannot parse date outside expected ddmmyy format".to_string());
+    }
+
+    if time.len() < 6 {
+        return Err("cannot parse time outside expected hhmmss format".to_string());
+    }
+
+    // example: "2014-11-28T12:00:09Z"
+    let iso_timestamp = [
+        "20",
+        &date[4..6],
+        "-",
+        &date[2..4],
+        "-",
+        &date[..2],
+        "T",
+        &time[..2],
+        ":",
+        &time[2..4],
+        ":",
+        &time[4..6],
+        "Z",
+    ]
+    .concat();
+
+    iso_timestamp
+        .parse::<DateTime<Utc>>()
+        .or(Err(format!("failed to parse date time {iso_timestamp}")))
+}
+
+/// Creates the `V4` reply H02 trackers expect after uploading some messages
+/// (such as alarms), trackers keep resending the message until they get it.
+///
+/// eg: `*HQ,7301000001,V4,V1,20190516080526#`
+pub fn v4_reply(imei: &str, msg_type: &str) -> Box<[u8]> {
+    let now = Utc::now().format("%Y%m%d%H%M%S");
+
+    format!("*HQ,{imei},V4,{msg_type},{now}#")
+        .into_bytes()
+        .into_boxed_slice()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::decode_status;
+
+    #[test]
+    fn decodes_status_bits_as_active_low() {
+        // sample from `docs/h02/protocol_docs/H02_protocol_en.doc`, where
+        // the only unset bit (21) is the engine flag
+        let status = decode_status(&hex::decode("FFFFFBFF").unwrap()).unwrap();
+
+        let serde_json::Value::Object(flags) = serde_json::to_value(&status).unwrap() else {
+            panic!("status not serialized as an object");
+        };
+
+        for (flag, active) in flags {
+            assert_eq!(active, flag == "engine", "unexpected value for {flag}");
+        }
+    }
+}
@@ -1,2 +1,4 @@
 pub mod common;
+pub mod gt06;
 pub mod h02;
+pub mod teltonika;
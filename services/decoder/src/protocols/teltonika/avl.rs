@@ -0,0 +1,169 @@
+use super::decoder::Message;
+use super::utils::{self, Reader};
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use chrono::DateTime;
+use shared::dto::decoder::teltonika::{IoValue, LocationMsg};
+use std::collections::BTreeMap;
+
+pub mod codecs {
+    pub const CODEC_8: u8 = 0x08;
+    pub const CODEC_8_EXTENDED: u8 = 0x8E;
+
+    /// used to send commands to the tracker and receive their responses
+    pub const CODEC_12: u8 = 0x0C;
+}
+
+/// name of well known IO elements of FMB devices, the meaning of most IO
+/// elements depends on the model and configuration so they are kept by id
+fn io_name(id: u16) -> String {
+    let name = match id {
+        1 => "digital_input_1",
+        2 => "digital_input_2",
+        3 => "digital_input_3",
+        9 => "analog_input_1",
+        16 => "total_odometer",
+        21 => "gsm_signal",
+        24 => "speed",
+        66 => "external_voltage",
+        67 => "battery_voltage",
+        68 => "battery_current",
+        69 => "gnss_status",
+        179 => "digital_output_1",
+        180 => "digital_output_2",
+        181 => "gnss_pdop",
+        182 => "gnss_hdop",
+        199 => "trip_odometer",
+        200 => "sleep_mode",
+        239 => "ignition",
+        240 => "movement",
+        _ => return id.to_string(),
+    };
+
+    name.to_string()
+}
+
+/// Parses the IO elements of a record, on Codec 8 the ids and counts are 1
+/// byte long while on Codec 8 Extended they are 2 bytes long and there is
+/// a extra group of variable length elements
+fn parse_io(
+    reader: &mut Reader,
+    extended: bool,
+) -> Result<(u16, BTreeMap<String, IoValue>), String> {
+    let read_id = |reader: &mut Reader| match extended {
+        true => reader.u16(),
+        false => reader.u8().map(u16::from),
+    };
+
+    let event_io_id = read_id(reader)?;
+    let _total_io_count = read_id(reader)?;
+
+    let mut io = BTreeMap::new();
+
+    for value_len in [1, 2, 4, 8] {
+        for _ in 0..read_id(reader)? {
+            let id = read_id(reader)?;
+            io.insert(io_name(id), IoValue::Number(reader.uint(value_len)?));
+        }
+    }
+
+    if extended {
+        for _ in 0..reader.u16()? {
+            let id = reader.u16()?;
+            let value_len = reader.u16()? as usize;
+
+            io.insert(
+                io_name(id),
+                IoValue::Bytes(hex::encode(reader.bytes(value_len)?)),
+            );
+        }
+    }
+
+    Ok((event_io_id, io))
+}
+
+/// AVL record layout (bytes)
+///
+/// | 8         | 1        | 4   | 4   | 2        | 2     | 1          | 2     | ...         |
+/// |-----------|----------|-----|-----|----------|-------|------------|-------|-------------|
+/// | timestamp | priority | lng | lat | altitude | angle | satellites | speed | IO elements |
+fn parse_record(reader: &mut Reader, extended: bool) -> Result<LocationMsg, String> {
+    let timestamp = DateTime::from_timestamp_millis(reader.u64()? as i64)
+        .ok_or("invalid AVL record timestamp")?;
+
+    let priority = reader.u8()?;
+
+    // coordinates are sent as signed integers, multiplied by 10^7
+    let lng = reader.u32()? as i32 as f64 / 10_000_000.0;
+    let lat = reader.u32()? as i32 as f64 / 10_000_000.0;
+
+    let altitude = reader.u16()? as i16 as i32;
+    let direction = reader.u16()? as i32;
+    let satellites = reader.u8()?;
+    let speed = reader.u16()? as f64;
+
+    let (event_io_id, io) = parse_io(reader, extended)?;
+
+    Ok(LocationMsg {
+        lat,
+        lng,
+        altitude,
+        speed,
+        direction,
+        satellites,
+        positioned: satellites > 0,
+        priority,
+        event_io_id,
+        io,
+        timestamp,
+    })
+}
+
+/// Decodes the AVL data shared by TCP and UDP packets into its records
+///
+/// | 1        | 1               | ...     | 1               |
+/// |----------|-----------------|---------|-----------------|
+/// | codec id | number of data  | records | number of data  |
+pub fn decode_records(data: &[u8], imei: &str) -> Result<Vec<Decoded<LocationMsg>>, String> {
+    if data.len() < 3 {
+        return Err("incomplete AVL data".to_string());
+    }
+
+    let codec = data[0];
+    let records_cnt = data[1];
+
+    if data[data.len() - 1] != records_cnt {
+        return Err("AVL data number of data mismatch".to_string());
+    }
+
+    let extended = match codec {
+        codecs::CODEC_8 => false,
+        codecs::CODEC_8_EXTENDED => true,
+        codec => return Err(format!("unsupported codec: {codec:#04x}")),
+    };
+
+    let mut reader = Reader::new(&data[2..data.len() - 1]);
+
+    (0..records_cnt)
+        .map(|_| {
+            Ok(Decoded {
+                data: parse_record(&mut reader, extended)?,
+                imei: imei.to_string(),
+                response: None,
+                protocol: Protocol::Teltonika,
+                event_type: TrackerEvent::Location,
+            })
+        })
+        .collect()
+}
+
+/// Decodes a TCP AVL data packet into its records and the response to the tracker,
+/// that is the amount of records received, so the tracker can delete them.
+pub fn decode(frame: &[u8], imei: &str) -> Result<Message, String> {
+    let records = decode_records(utils::packet_data(frame)?, imei)?;
+    let response = (records.len() as u32).to_be_bytes();
+
+    Ok(Message::AvlData {
+        records,
+        response: Box::new(response),
+    })
+}
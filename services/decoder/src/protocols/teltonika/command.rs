@@ -0,0 +1,126 @@
+use super::avl::codecs;
+use super::decoder::Message;
+use super::utils::{self, Reader};
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use shared::dto::decoder::{command::Command, teltonika::CommandAckMsg};
+
+/// Codec 12 message type of commands sent to the tracker
+const COMMAND_TYPE: u8 = 0x05;
+
+/// Codec 12 message type of the tracker responses to commands
+const RESPONSE_TYPE: u8 = 0x06;
+
+/// The command text, as it would be sent by SMS or the configurator
+fn command_text(command: &Command) -> Result<String, String> {
+    match command {
+        Command::EngineStop => Ok("setdigout 1".to_string()),
+        Command::EngineResume => Ok("setdigout 0".to_string()),
+        Command::Reboot => Ok("cpureset".to_string()),
+        Command::Custom { content } => Ok(content.clone()),
+        // the parameter ids of the record periods depend on the model and
+        // on the data acquisition mode, so they can only be sent as custom
+        Command::PositionInterval { .. } => {
+            Err("position interval not supported, use a custom setparam command".to_string())
+        }
+    }
+}
+
+/// Encodes a Codec 12 command packet
+///
+/// | 4            | 4           | 1     | 1        | 1    | 4            | N       | 1        | 4   |
+/// |--------------|-------------|-------|----------|------|--------------|---------|----------|-----|
+/// | zeroed bytes | data length | codec | quantity | type | command size | command | quantity | CRC |
+pub fn encode(command: &Command) -> Result<Box<[u8]>, String> {
+    let text = command_text(command)?;
+
+    let mut data = vec![codecs::CODEC_12, 0x01, COMMAND_TYPE];
+    data.extend_from_slice(&(text.len() as u32).to_be_bytes());
+    data.extend_from_slice(text.as_bytes());
+    data.push(0x01);
+
+    let crc = utils::crc16(&data) as u32;
+
+    Ok([
+        [0, 0, 0, 0].as_slice(),
+        &(data.len() as u32).to_be_bytes(),
+        &data,
+        &crc.to_be_bytes(),
+    ]
+    .concat()
+    .into())
+}
+
+/// Decodes the Codec 12 response of the tracker to a command, the response
+/// has the same layout as the command but with the response type
+pub fn decode(frame: &[u8], imei: &str) -> Result<Message, String> {
+    let mut reader = Reader::new(utils::packet_data(frame)?);
+
+    let _codec = reader.u8()?;
+    let _quantity = reader.u8()?;
+
+    if reader.u8()? != RESPONSE_TYPE {
+        return Err("Codec 12 message is not a command response".to_string());
+    }
+
+    let size = reader.u32()? as usize;
+    let response = String::from_utf8_lossy(reader.bytes(size)?).to_string();
+
+    Ok(Message::CommandAck(Decoded {
+        data: CommandAckMsg { response },
+        imei: imei.to_string(),
+        response: None,
+        protocol: Protocol::Teltonika,
+        event_type: TrackerEvent::CommandAck,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::encode;
+    use crate::protocols::teltonika::decoder::{decode, Message};
+    use shared::dto::decoder::command::Command;
+
+    #[test]
+    fn encodes_command() {
+        // sample from the teltonika wiki
+        let expected =
+            hex::decode("000000000000000F0C010500000007676574696E666F0100004312").unwrap();
+
+        let packet = encode(&Command::Custom {
+            content: "getinfo".to_string(),
+        })
+        .unwrap();
+
+        assert_eq!(packet.as_ref(), expected);
+    }
+
+    #[test]
+    fn decodes_command_response() {
+        let text = b"DOUT1:1 Timeout:INFINITY";
+
+        let mut data = vec![0x0C, 0x01, 0x06];
+        data.extend_from_slice(&(text.len() as u32).to_be_bytes());
+        data.extend_from_slice(text);
+        data.push(0x01);
+
+        let crc = crate::protocols::teltonika::utils::crc16(&data) as u32;
+        let frame = [
+            [0, 0, 0, 0].as_slice(),
+            &(data.len() as u32).to_be_bytes(),
+            &data,
+            &crc.to_be_bytes(),
+        ]
+        .concat();
+
+        let Ok(Message::CommandAck(decoded)) = decode(&frame, Some("356307042441013")) else {
+            panic!("not decoded as command ack");
+        };
+
+        assert_eq!(
+            decoded.get_routing_key(),
+            "teltonika.command_ack.356307042441013"
+        );
+        assert_eq!(decoded.data.response, "DOUT1:1 Timeout:INFINITY");
+        assert!(decoded.response.is_none());
+    }
+}
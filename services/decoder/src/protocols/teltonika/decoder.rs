@@ -0,0 +1,192 @@
+use super::{avl, command, udp};
+use crate::protocols::common::Decoded;
+use shared::dto::decoder::teltonika::{CommandAckMsg, LocationMsg, LoginMsg};
+
+/// All possible message types decodable from the Teltonika tracker protocol
+pub enum Message {
+    Login(Decoded<LoginMsg>),
+
+    /// a AVL data packet, containing many records and the acknowledgement
+    /// of the amount of records received to be sent to the tracker
+    AvlData {
+        records: Vec<Decoded<LocationMsg>>,
+        response: Box<[u8]>,
+    },
+
+    CommandAck(Decoded<CommandAckMsg>),
+}
+
+/// Decodes a complete Teltonika frame, `imei` is the imei of the tracker
+/// on the connection, received on its IMEI handshake.
+pub fn decode(frame: &[u8], imei: Option<&str>) -> Result<Message, String> {
+    // AVL data packets start with 4 zeroed bytes, the IMEI handshake with its length
+    if !frame.starts_with(&[0, 0]) {
+        return Ok(Message::Login(frame.try_into()?));
+    }
+
+    let imei = imei.ok_or("AVL data received before IMEI handshake")?;
+
+    // the codec id is the first byte after the zeroed bytes and data length
+    match frame.get(8) {
+        Some(&avl::codecs::CODEC_12) => command::decode(frame, imei),
+        _ => avl::decode(frame, imei),
+    }
+}
+
+/// Decodes a Teltonika UDP packet, each packet is independent and contains the tracker imei
+pub fn decode_datagram(datagram: &[u8]) -> Result<Message, String> {
+    udp::decode(datagram)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode, Message};
+    use shared::dto::decoder::teltonika::IoValue;
+
+    const IMEI: Option<&str> = Some("356307042441013");
+
+    fn decode_hex(s: &str, imei: Option<&str>) -> Result<Message, String> {
+        decode(&hex::decode(s).unwrap(), imei)
+    }
+
+    #[test]
+    fn decodes_imei_handshake() {
+        // sample from the teltonika wiki
+        let Ok(Message::Login(decoded)) = decode_hex("000F333536333037303432343431303133", None)
+        else {
+            panic!("not decoded as login");
+        };
+
+        assert_eq!(decoded.get_routing_key(), "teltonika.login.356307042441013");
+        assert_eq!(decoded.response.as_deref(), Some([0x01].as_slice()));
+    }
+
+    #[test]
+    fn decodes_codec_8() {
+        // sample from the teltonika wiki
+        let frame = "000000000000003608010000016B40D8EA30010000000000000000000000000000\
+            000105021503010101425E0F01F10000601A014E0000000000000000010000C7CF";
+
+        let Ok(Message::AvlData { records, response }) = decode_hex(frame, IMEI) else {
+            panic!("not decoded as avl data");
+        };
+
+        assert_eq!(response.as_ref(), [0, 0, 0, 1]);
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].get_routing_key(),
+            "teltonika.location.356307042441013"
+        );
+
+        let record = &records[0].data;
+
+        assert_eq!(record.timestamp.timestamp_millis(), 0x16B40D8EA30);
+        assert_eq!(record.priority, 1);
+        assert!(!record.positioned);
+        assert_eq!(record.event_io_id, 1);
+        assert_eq!(record.io.len(), 5);
+        assert_eq!(record.io.get("gsm_signal"), Some(&IoValue::Number(3)));
+        assert_eq!(record.io.get("digital_input_1"), Some(&IoValue::Number(1)));
+        assert_eq!(
+            record.io.get("external_voltage"),
+            Some(&IoValue::Number(0x5E0F))
+        );
+        assert_eq!(record.io.get("241"), Some(&IoValue::Number(0x601A)));
+        assert_eq!(record.io.get("78"), Some(&IoValue::Number(0)));
+    }
+
+    #[test]
+    fn decodes_codec_8_extended() {
+        // sample from the teltonika wiki
+        let frame = "000000000000004A8E010000016B412CEE000100000000000000000000000000000000\
+            010005000100010100010011001D00010010015E2C880002000B000000003544C87A000E00\
+            0000001DD7E06A00000100002994";
+
+        let Ok(Message::AvlData { records, response }) = decode_hex(frame, IMEI) else {
+            panic!("not decoded as avl data");
+        };
+
+        assert_eq!(response.as_ref(), [0, 0, 0, 1]);
+
+        let record = &records[0].data;
+
+        assert_eq!(record.event_io_id, 1);
+        assert_eq!(record.io.len(), 5);
+        assert_eq!(record.io.get("digital_input_1"), Some(&IoValue::Number(1)));
+        assert_eq!(record.io.get("17"), Some(&IoValue::Number(0x1D)));
+        assert_eq!(
+            record.io.get("total_odometer"),
+            Some(&IoValue::Number(0x15E2C88))
+        );
+        assert_eq!(record.io.get("11"), Some(&IoValue::Number(0x3544C87A)));
+        assert_eq!(record.io.get("14"), Some(&IoValue::Number(0x1DD7E06A)));
+    }
+
+    #[test]
+    fn decodes_coordinates() {
+        // codec 8 record at -23.5505199, -46.6333094 with 9 satellites, 80 km/h
+        // and 760m of altitude, built with a valid CRC
+        let data =
+            hex::decode("08010000016B40D8EA3001E434525AF1F679D102F8005A09005000000000000001")
+                .unwrap();
+
+        let crc = super::super::utils::crc16(&data) as u32;
+        let frame = [
+            [0, 0, 0, 0].as_slice(),
+            &(data.len() as u32).to_be_bytes(),
+            &data,
+            &crc.to_be_bytes(),
+        ]
+        .concat();
+
+        let Ok(Message::AvlData { records, .. }) = decode(&frame, IMEI) else {
+            panic!("not decoded as avl data");
+        };
+
+        let record = &records[0].data;
+
+        assert!((record.lat - -23.5505199).abs() < 0.0000001);
+        assert!((record.lng - -46.6333094).abs() < 0.0000001);
+        assert_eq!(record.altitude, 760);
+        assert_eq!(record.direction, 90);
+        assert_eq!(record.satellites, 9);
+        assert_eq!(record.speed, 80.0);
+        assert!(record.positioned);
+    }
+
+    #[test]
+    fn decodes_udp_codec_8() {
+        // sample from the teltonika wiki
+        let datagram = hex::decode(
+            "003DCAFE0105000F33353230393330383634303336353508010000016B4F815B30010000000000\
+            000000000000000000000103021503010101425DBC000001",
+        )
+        .unwrap();
+
+        let Ok(Message::AvlData { records, response }) = super::decode_datagram(&datagram) else {
+            panic!("not decoded as avl data");
+        };
+
+        assert_eq!(
+            response.as_ref(),
+            [0x00, 0x05, 0xCA, 0xFE, 0x01, 0x05, 0x01]
+        );
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].get_routing_key(),
+            "teltonika.location.352093086403655"
+        );
+        assert_eq!(
+            records[0].data.io.get("external_voltage"),
+            Some(&IoValue::Number(0x5DBC))
+        );
+    }
+
+    #[test]
+    fn rejects_avl_data_before_handshake() {
+        let frame = "000000000000003608010000016B40D8EA30010000000000000000000000000000\
+            000105021503010101425E0F01F10000601A014E0000000000000000010000C7CF";
+
+        assert!(decode_hex(frame, None).is_err());
+    }
+}
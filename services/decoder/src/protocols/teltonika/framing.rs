@@ -0,0 +1,141 @@
+/// Maximum length of the IMEI sent on the handshake
+const MAX_IMEI_LEN: usize = 17;
+
+/// Amount of bytes before the AVL data, 4 zeroed bytes followed by the data length
+const AVL_HEADER_LEN: usize = 8;
+
+/// Amount of bytes of the CRC after the AVL data
+const AVL_CRC_LEN: usize = 4;
+
+/// The maximum amount of bytes a frame can have, Teltonika trackers
+/// send AVL data packets of 1280 bytes at most.
+const MAX_FRAME_SIZE: usize = 2048;
+
+/// A per connection buffer to reassemble Teltonika frames from a TCP stream.
+///
+/// The first frame of a connection is the IMEI handshake (2 bytes length followed
+/// by the IMEI) and every frame after it is a AVL data packet (4 zeroed bytes, 4 bytes
+/// data length, the data and a 4 bytes CRC), so complete frames are extracted by their
+/// length and any trailing partial frame is kept until the next read completes it.
+#[derive(Default)]
+pub struct FrameBuffer {
+    buffer: Vec<u8>,
+}
+
+impl FrameBuffer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// appends the bytes read from the connection to the buffer
+    pub fn extend(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Removes and returns the next complete frame on the buffer, returns
+    /// `None` if there is no complete frame.
+    ///
+    /// Since Teltonika frames have no start or stop bits its not possible
+    /// to find the next frame after invalid bytes, so the whole buffer is
+    /// discarded and returned as a `Err` once a invalid length is found.
+    pub fn next_frame(&mut self) -> Option<Result<Vec<u8>, String>> {
+        let frame_len = match self.frame_len()? {
+            Ok(frame_len) => frame_len,
+            Err(err) => {
+                self.buffer.clear();
+                return Some(Err(err));
+            }
+        };
+
+        if self.buffer.len() < frame_len {
+            return None;
+        }
+
+        Some(Ok(self.buffer.drain(..frame_len).collect()))
+    }
+
+    fn frame_len(&self) -> Option<Result<usize, String>> {
+        let prefix = self.buffer.get(..2)?;
+
+        // AVL data packets start with 4 zeroed bytes, while the IMEI
+        // handshake starts with the IMEI length, that is never zero
+        if prefix != [0, 0] {
+            let imei_len = u16::from_be_bytes([prefix[0], prefix[1]]) as usize;
+
+            if imei_len > MAX_IMEI_LEN {
+                return Some(Err(format!("invalid IMEI length: {imei_len}")));
+            }
+
+            return Some(Ok(imei_len + 2));
+        }
+
+        let header = self.buffer.get(..AVL_HEADER_LEN)?;
+
+        if header[2..4] != [0, 0] {
+            return Some(Err("invalid AVL data packet preamble".to_string()));
+        }
+
+        let data_len = u32::from_be_bytes([header[4], header[5], header[6], header[7]]) as usize;
+        let frame_len = AVL_HEADER_LEN + data_len + AVL_CRC_LEN;
+
+        if frame_len > MAX_FRAME_SIZE {
+            return Some(Err(format!("invalid AVL data length: {data_len}")));
+        }
+
+        Some(Ok(frame_len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FrameBuffer;
+
+    const IMEI: &str = "000F333536333037303432343431303133";
+
+    /// codec 8 packet sample from the teltonika wiki
+    const AVL_DATA: &str = "000000000000003608010000016B40D8EA30010000000000000000000000000000\
+        000105021503010101425E0F01F10000601A014E0000000000000000010000C7CF";
+
+    fn drain_frames(buffer: &mut FrameBuffer) -> Vec<Result<Vec<u8>, String>> {
+        std::iter::from_fn(|| buffer.next_frame()).collect()
+    }
+
+    #[test]
+    fn handshake_and_avl_data() {
+        let imei = hex::decode(IMEI).unwrap();
+        let avl_data = hex::decode(AVL_DATA).unwrap();
+
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(&[imei.as_slice(), &avl_data, &avl_data].concat());
+
+        assert_eq!(
+            drain_frames(&mut buffer),
+            vec![Ok(imei), Ok(avl_data.clone()), Ok(avl_data)]
+        );
+    }
+
+    #[test]
+    fn frame_split_across_reads() {
+        let avl_data = hex::decode(AVL_DATA).unwrap();
+
+        let mut buffer = FrameBuffer::new();
+
+        for chunk in avl_data.chunks(5) {
+            assert!(drain_frames(&mut buffer).is_empty());
+            buffer.extend(chunk);
+        }
+
+        assert_eq!(drain_frames(&mut buffer), vec![Ok(avl_data)]);
+    }
+
+    #[test]
+    fn invalid_length_discards_buffer() {
+        let mut buffer = FrameBuffer::new();
+        buffer.extend(b"GET / HTTP/1.1");
+
+        let frames = drain_frames(&mut buffer);
+
+        assert_eq!(frames.len(), 1);
+        assert!(frames[0].is_err());
+    }
+}
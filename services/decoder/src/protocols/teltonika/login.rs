@@ -0,0 +1,28 @@
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use shared::dto::decoder::teltonika::LoginMsg;
+
+/// Response to the IMEI handshake accepting the tracker connection,
+/// the tracker only sends AVL data after receiving it
+const ACCEPT_CONNECTION: u8 = 0x01;
+
+impl TryFrom<&[u8]> for Decoded<LoginMsg> {
+    type Error = String;
+
+    fn try_from(frame: &[u8]) -> Result<Self, Self::Error> {
+        let imei = std::str::from_utf8(frame.get(2..).unwrap_or_default())
+            .or(Err("failed to read IMEI as utf8"))?
+            .to_string();
+
+        if imei.is_empty() || !imei.chars().all(|c| c.is_ascii_digit()) {
+            return Err(format!("invalid IMEI: {imei}"));
+        }
+
+        Ok(Decoded {
+            data: LoginMsg { imei: imei.clone() },
+            imei,
+            response: Some(Box::new([ACCEPT_CONNECTION])),
+            protocol: Protocol::Teltonika,
+            event_type: TrackerEvent::Login,
+        })
+    }
+}
@@ -0,0 +1,7 @@
+pub mod avl;
+pub mod command;
+pub mod decoder;
+pub mod framing;
+pub mod login;
+pub mod udp;
+pub mod utils;
@@ -0,0 +1,55 @@
+use super::{avl, decoder::Message};
+
+/// Byte sent on every UDP packet and response, its meaning is not documented
+const NOT_USABLE_BYTE: u8 = 0x01;
+
+/// Decodes a UDP AVL data packet, unlike TCP packets every UDP packet contains
+/// the tracker IMEI and has no CRC, the response to the tracker contains the
+/// packet ids and the amount of records received.
+///
+/// | 2      | 2         | 1               | 1             | 2           | n    | ...      |
+/// |--------|-----------|-----------------|---------------|-------------|------|----------|
+/// | length | packet id | not usable byte | AVL packet id | IMEI length | IMEI | AVL data |
+pub fn decode(datagram: &[u8]) -> Result<Message, String> {
+    if datagram.len() < 8 {
+        return Err("incomplete UDP packet".to_string());
+    }
+
+    let packet_len = u16::from_be_bytes([datagram[0], datagram[1]]) as usize;
+
+    if datagram.len() != packet_len + 2 {
+        return Err(format!("invalid UDP packet length: {packet_len}"));
+    }
+
+    let packet_id = &datagram[2..4];
+    let avl_packet_id = datagram[5];
+
+    let imei_len = u16::from_be_bytes([datagram[6], datagram[7]]) as usize;
+
+    let imei = datagram
+        .get(8..8 + imei_len)
+        .ok_or("incomplete UDP packet IMEI")?;
+
+    let imei = std::str::from_utf8(imei).or(Err("failed to read IMEI as utf8"))?;
+
+    if imei.is_empty() || !imei.chars().all(|c| c.is_ascii_digit()) {
+        return Err(format!("invalid IMEI: {imei}"));
+    }
+
+    let records = avl::decode_records(&datagram[8 + imei_len..], imei)?;
+
+    let response = [
+        0x00,
+        0x05,
+        packet_id[0],
+        packet_id[1],
+        NOT_USABLE_BYTE,
+        avl_packet_id,
+        records.len() as u8,
+    ];
+
+    Ok(Message::AvlData {
+        records,
+        response: Box::new(response),
+    })
+}
@@ -0,0 +1,87 @@
+/// CRC-16/IBM used by Teltonika to check AVL data packets, calculated from
+/// the codec id to the second number of records
+pub fn crc16(bytes: &[u8]) -> u16 {
+    let mut crc: u16 = 0;
+
+    for byte in bytes {
+        crc ^= *byte as u16;
+
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xA001
+            } else {
+                crc >> 1
+            };
+        }
+    }
+
+    crc
+}
+
+/// The data of a TCP packet (AVL data or a Codec 12 command), checking its CRC
+///
+/// | 4              | 4           | ...  | 4   |
+/// |----------------|-------------|------|-----|
+/// | zeroed bytes   | data length | data | CRC |
+pub fn packet_data(frame: &[u8]) -> Result<&[u8], String> {
+    if frame.len() < 15 {
+        return Err("incomplete data packet".to_string());
+    }
+
+    let data = &frame[8..frame.len() - 4];
+    let crc = u32::from_be_bytes(frame[frame.len() - 4..].try_into().unwrap_or_default());
+
+    if crc != crc16(data) as u32 {
+        return Err("invalid data packet CRC".to_string());
+    }
+
+    Ok(data)
+}
+
+/// Reads big endian fields from a packet, failing instead of
+/// panicking if the packet ends before the field
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    pub fn bytes(&mut self, len: usize) -> Result<&'a [u8], String> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + len)
+            .ok_or("incomplete AVL data packet")?;
+
+        self.position += len;
+
+        Ok(bytes)
+    }
+
+    /// reads a unsigned integer of `len` bytes, up to 8
+    pub fn uint(&mut self, len: usize) -> Result<u64, String> {
+        Ok(self
+            .bytes(len)?
+            .iter()
+            .fold(0, |value, byte| (value << 8) | *byte as u64))
+    }
+
+    pub fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.uint(1)? as u8)
+    }
+
+    pub fn u16(&mut self) -> Result<u16, String> {
+        Ok(self.uint(2)? as u16)
+    }
+
+    pub fn u32(&mut self) -> Result<u32, String> {
+        Ok(self.uint(4)? as u32)
+    }
+
+    pub fn u64(&mut self) -> Result<u64, String> {
+        self.uint(8)
+    }
+}
@@ -1,12 +1,16 @@
-use crate::{config, errors};
+use crate::{config, errors, offline_buffer::OfflineBuffer};
 use lapin::{
     options::{BasicPublishOptions, ExchangeDeclareOptions},
     publisher_confirm::PublisherConfirm,
     types::FieldTable,
     BasicProperties, Channel, Connection, ConnectionProperties, ExchangeKind,
 };
-use std::{thread, time};
-use tokio::sync::{mpsc::UnboundedReceiver, RwLock};
+use serde::{Deserialize, Serialize};
+use std::{path::PathBuf, time::Duration};
+use tokio::{
+    sync::{mpsc::UnboundedReceiver, Mutex, RwLock},
+    time::Instant,
+};
 use tracing::{Instrument, Span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
@@ -16,15 +20,15 @@ struct Options {
     pub tracker_events_exchange: String,
 }
 
+/// Time to wait between attempts to reconnect to RabbitMQ
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(5);
+
 /// A listener that recieves RabbitMQ messages on the reciever channel
 /// and publishes those messages to the tracker events exchange.
 ///
-/// [PROD-TODO]
-///
-/// Currently if the RabbitMQ connection is lost, any messages recieved
-/// by the rust channel will be ignored, a good idea might be to create
-/// queue that stores a limited number of messages until the connection
-/// is restored, and then publish its contents on reconnection
+/// If the RabbitMQ connection is lost, messages recieved by the rust channel
+/// are stored on the offline buffer until the connection is restored, and
+/// then published in the order they were received.
 pub struct RmqListener {
     options: Options,
 
@@ -38,9 +42,12 @@ pub struct RmqListener {
 
     /// channel to receive messages to publish to the tracker events exchange
     receiver: RwLock<UnboundedReceiver<(RmqMessage, tracing::Span)>>,
+
+    /// messages received while disconnected from RabbitMQ
+    offline_buffer: Mutex<OfflineBuffer>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RmqMessage {
     /// Message content, most likely serialized JSON
     pub body: String,
@@ -59,11 +66,18 @@ impl RmqListener {
             tracker_events_exchange: cfg.tracker_events_exchange.to_owned(),
         };
 
+        let offline_buffer = OfflineBuffer::new(
+            PathBuf::from(&cfg.offline_buffer_file),
+            cfg.offline_buffer_memory_limit,
+            cfg.offline_buffer_max_messages,
+        );
+
         RmqListener {
             options,
             channel: RwLock::new(None),
             connection: RwLock::new(None),
             receiver: RwLock::new(receiver),
+            offline_buffer: Mutex::new(offline_buffer),
         }
     }
 
@@ -75,11 +89,69 @@ impl RmqListener {
                 println!("[RMQ] connection error: {}", err)
             }
 
-            thread::sleep(time::Duration::from_secs(5));
+            self.buffer_messages_until(Instant::now() + RECONNECT_INTERVAL)
+                .await;
             println!("[RMQ] reconnecting");
         }
     }
 
+    /// Stores the messages received on the rust channel on the offline
+    /// buffer until the deadline, used while disconnected from RabbitMQ
+    async fn buffer_messages_until(&self, deadline: Instant) {
+        let mut receiver = self.receiver.write().await;
+
+        while let Ok(Some((message, _))) = tokio::time::timeout_at(deadline, receiver.recv()).await
+        {
+            self.offline_buffer.lock().await.push(message);
+        }
+    }
+
+    /// Publishes the messages on the offline buffer from oldest to newest,
+    /// keeping on the buffer the messages not published due to a connection error
+    async fn publish_offline_buffer(&self) -> Result<(), lapin::Error> {
+        let mut offline_buffer = self.offline_buffer.lock().await;
+
+        if offline_buffer.is_empty() {
+            return Ok(());
+        }
+
+        let pending = match offline_buffer.pending() {
+            Ok(pending) => pending,
+            Err(err) => {
+                println!("[RMQ] failed to read offline buffer: {}", err);
+                return Ok(());
+            }
+        };
+
+        println!("[RMQ] publishing {} buffered messages", pending.len());
+
+        let mut published = 0;
+        let mut result = Ok(());
+
+        for message in &pending {
+            match self.send_message(message).await {
+                Err(
+                    err @ (lapin::Error::InvalidChannelState(_)
+                    | lapin::Error::InvalidConnectionState(_)),
+                ) => {
+                    result = Err(err);
+                    break;
+                }
+                // messages failing for other reasons would never be published, so skip them
+                _ => published += 1,
+            }
+        }
+
+        if let Err(err) = offline_buffer.remove_oldest(published) {
+            println!(
+                "[RMQ] failed to remove published messages from buffer: {}",
+                err
+            );
+        }
+
+        result
+    }
+
     /// Creates and sets the RabbitMQ connection and channel
     /// and then starts listening to the RUST messages channel
     /// indefinitely, publishing the recieved messages to the
@@ -124,6 +196,15 @@ impl RmqListener {
         *self.connection.write().await = Some(connection);
         *self.channel.write().await = Some(channel);
 
+        // messages received while disconnected are older than any message
+        // on the rust channel, so they must be published first
+        if let Err(err) = self.publish_offline_buffer().await {
+            *self.connection.write().await = None;
+            *self.channel.write().await = None;
+
+            return Err(err);
+        }
+
         while let Some((delivery, span)) = self.receiver.write().await.recv().await {
             if let Err(err) = self.send_message(&delivery).instrument(span).await {
                 match err {
@@ -134,6 +215,9 @@ impl RmqListener {
                         *self.connection.write().await = None;
                         *self.channel.write().await = None;
 
+                        // keep the message so its published once reconnected
+                        self.offline_buffer.lock().await.push(delivery);
+
                         // Its very important to return the error here
                         // so `self.run` attempts to reconnect
                         return Err(err);
@@ -195,5 +279,10 @@ impl RmqListener {
 
         *self.channel.write().await = None;
         *self.connection.write().await = None;
+
+        println!("[RMQ] persisting offline buffer");
+        if let Err(err) = self.offline_buffer.lock().await.persist() {
+            println!("[RMQ] failed to persist offline buffer: {}", err)
+        }
     }
 }
@@ -0,0 +1,149 @@
+use crate::packet_archive::Entry;
+use crate::protocols::common::Decoded;
+use crate::protocols::h02::{self, decoder::Message, framing::FrameBuffer};
+use serde::Serialize;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, Write},
+    net::SocketAddr,
+};
+
+fn write_decoded<T: Serialize>(
+    out: &mut impl Write,
+    entry: &Entry,
+    decoded: &Decoded<T>,
+) -> io::Result<()> {
+    let data = serde_json::to_string(&decoded.data)
+        .unwrap_or_else(|err| format!("failed to serialize decoded data: {err}"));
+
+    writeln!(
+        out,
+        "{} {} {} {}",
+        entry.timestamp.to_rfc3339(),
+        entry.peer,
+        decoded.get_routing_key(),
+        data
+    )
+}
+
+/// Replays the entries of a H02 packet archive, writing each decoded message to `out`
+/// along with the routing key it would be published with, or the decoding error.
+///
+/// Entries are fed to a single frame buffer per connection, so frames split
+/// between TCP reads are reassembled the same way they were when received.
+pub fn replay_h02(archive: impl BufRead, out: &mut impl Write) -> io::Result<()> {
+    let mut frames = FrameBuffer::new();
+    let mut peer: Option<SocketAddr> = None;
+
+    for (i, line) in archive.lines().enumerate() {
+        let line = line?;
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let entry = match Entry::from_line(&line) {
+            Ok(entry) => entry,
+            Err(err) => {
+                writeln!(out, "line {}: invalid archive entry: {}", i + 1, err)?;
+                continue;
+            }
+        };
+
+        // a new peer address means a new connection, whose frames
+        // cannot be a continuation of the previous connection frames
+        if peer != Some(entry.peer) {
+            frames = FrameBuffer::new();
+            peer = Some(entry.peer);
+        }
+
+        frames.extend(&entry.packet);
+
+        while let Some(frame) = frames.next_frame() {
+            match frame.and_then(|packets| h02::decoder::decode(&packets)) {
+                Ok(Message::Heartbeat(decoded)) => write_decoded(out, &entry, &decoded)?,
+                Ok(Message::Location(decoded)) => write_decoded(out, &entry, &decoded)?,
+                Ok(Message::Lbs(decoded)) => write_decoded(out, &entry, &decoded)?,
+                Ok(Message::LinkStatus(decoded)) => write_decoded(out, &entry, &decoded)?,
+                Ok(Message::CommandAck(decoded)) => write_decoded(out, &entry, &decoded)?,
+                Err(err) => writeln!(
+                    out,
+                    "{} {} error decoding packets: {}",
+                    entry.timestamp.to_rfc3339(),
+                    entry.peer,
+                    err
+                )?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Entrypoint of the `replay` subcommand, eg: `decoder replay archive/867232051148352.log`
+pub fn run(args: &[String]) -> Result<(), String> {
+    let [path] = args else {
+        return Err("usage: decoder replay <archive_file>".to_string());
+    };
+
+    let file = File::open(path).map_err(|e| format!("failed to open {path}: {e}"))?;
+
+    replay_h02(BufReader::new(file), &mut io::stdout().lock()).map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::replay_h02;
+    use crate::packet_archive::Entry;
+    use chrono::Utc;
+
+    fn line(peer: &str, packet: &[u8]) -> String {
+        Entry {
+            timestamp: Utc::now(),
+            peer: peer.parse().unwrap(),
+            packet: packet.to_vec(),
+        }
+        .to_line()
+    }
+
+    fn replay(lines: &[String]) -> Vec<String> {
+        let mut out = Vec::new();
+
+        replay_h02(lines.concat().as_bytes(), &mut out).unwrap();
+
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| line.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn replays_split_frames() {
+        let output = replay(&[
+            line("10.0.0.1:5000", b"*HQ,867232051148352,HTBT#*HQ,8672320511"),
+            line(
+                "10.0.0.1:5000",
+                b"48352,V1,080509,A,2333.0300,S,04637.9980,W,10.00,0,150224,FFFFDBFF#",
+            ),
+        ]);
+
+        assert_eq!(output.len(), 2);
+        assert!(output[0].contains("h02.heartbeat.867232051148352"));
+        assert!(output[1].contains("h02.location.867232051148352"));
+        assert!(output[1].contains("\"lat\":-23.5505"));
+    }
+
+    #[test]
+    fn does_not_join_frames_of_different_connections() {
+        let output = replay(&[
+            line("10.0.0.1:5000", b"*HQ,8672320511"),
+            line("10.0.0.1:5001", b"48352,HTBT#"),
+            "not a archive entry\n".to_string(),
+        ]);
+
+        assert_eq!(output.len(), 2);
+        assert!(output[0].contains("error decoding packets"));
+        assert!(output[1].starts_with("line 3: invalid archive entry"));
+    }
+}
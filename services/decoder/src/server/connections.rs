@@ -0,0 +1,61 @@
+use crate::protocols::common::Protocol;
+use std::{
+    collections::HashMap,
+    sync::{Arc, OnceLock},
+};
+use tokio::{
+    io::WriteHalf,
+    net::TcpStream,
+    sync::{Mutex, RwLock},
+};
+
+/// Write half of a tracker connection, shared by the connection handler to write
+/// responses to the tracker and by the commands consumer to write commands
+pub type TrackerWriter = Arc<Mutex<WriteHalf<TcpStream>>>;
+
+/// A live TCP connection of a tracker
+#[derive(Clone)]
+pub struct Connection {
+    pub protocol: Protocol,
+    pub writer: TrackerWriter,
+}
+
+/// Live connections keyed by the IMEI of the tracker, only trackers
+/// connected over TCP can be registered since UDP has no connection
+static CONNECTIONS: OnceLock<RwLock<HashMap<String, Connection>>> = OnceLock::new();
+
+fn connections() -> &'static RwLock<HashMap<String, Connection>> {
+    CONNECTIONS.get_or_init(|| RwLock::new(HashMap::new()))
+}
+
+/// Registers the connection of a tracker, replacing any previous connection of the
+/// tracker, since trackers usually reconnect before the old connection is closed
+pub async fn register(imei: &str, protocol: Protocol, writer: &TrackerWriter) {
+    let connection = Connection {
+        protocol,
+        writer: writer.clone(),
+    };
+
+    connections()
+        .write()
+        .await
+        .insert(imei.to_string(), connection);
+}
+
+/// Removes the connection of a tracker, unless it was already replaced by a new connection
+pub async fn unregister(imei: &str, writer: &TrackerWriter) {
+    let mut connections = connections().write().await;
+
+    let is_same_connection = connections
+        .get(imei)
+        .is_some_and(|connection| Arc::ptr_eq(&connection.writer, writer));
+
+    if is_same_connection {
+        connections.remove(imei);
+    }
+}
+
+/// The live connection of a tracker, if any
+pub async fn get(imei: &str) -> Option<Connection> {
+    connections().read().await.get(imei).cloned()
+}
@@ -0,0 +1,137 @@
+use crate::protocols::common::Protocol;
+use crate::protocols::gt06;
+use crate::protocols::gt06::decoder::Message;
+use crate::protocols::gt06::framing::FrameBuffer;
+use crate::server::listeners::{send_event, RmqMsgSender, BUFFER_SIZE};
+use crate::server::session::Session;
+use shared::dto::decoder::session::DisconnectReason;
+use std::sync::Arc;
+use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::sync::Mutex;
+use tracing::{error, span, Level};
+
+#[tracing::instrument(skip_all)]
+fn handle_decoded_message(message: Message, sender: &RmqMsgSender) -> Option<Box<[u8]>> {
+    match message {
+        Message::Login(decoded) => {
+            let response = decoded.response.clone();
+            let _ = send_event(decoded, sender);
+
+            response
+        }
+        Message::Location(decoded) => {
+            let response = decoded.response.clone();
+            let _ = send_event(decoded, sender);
+
+            response
+        }
+        Message::Status(decoded) => {
+            let response = decoded.response.clone();
+            let _ = send_event(decoded, sender);
+
+            response
+        }
+        Message::Alarm(decoded) => {
+            let response = decoded.response.clone();
+            let _ = send_event(decoded, sender);
+
+            response
+        }
+        Message::CommandAck(decoded) => {
+            let response = decoded.response.clone();
+            let _ = send_event(decoded, sender);
+
+            response
+        }
+    }
+}
+
+pub async fn stream_handler(stream: TcpStream, sender: RmqMsgSender) {
+    let mut buffer = vec![0; BUFFER_SIZE];
+
+    let peer = stream.peer_addr().ok();
+
+    let (mut reader, writer) = io::split(stream);
+
+    // GT06 packets do not contain the imei, only the login packet sent
+    // when the tracker connects, so it is kept on the session
+    let mut session = Session::new(Protocol::Gt06, peer, Arc::new(Mutex::new(writer)));
+
+    let mut frames = FrameBuffer::new();
+
+    let reason = loop {
+        let n = match reader.read(&mut buffer).await {
+            Ok(0) => break DisconnectReason::Closed,
+            Ok(n) => n,
+            Err(_) => break DisconnectReason::ReadError,
+        };
+
+        session.bytes_received += n;
+        frames.extend(&buffer[..n]);
+
+        let mut disconnect = None;
+
+        while let Some(frame) = frames.next_frame() {
+            let packets_len = frame.as_ref().map_or(0, |f| f.len());
+
+            let span = span!(
+                Level::ERROR,
+                "stream_handler",
+                invalid_packets_cnt = session.invalid_packets,
+                packets_len
+            );
+            let _enter = span.enter();
+
+            let decode_result =
+                frame.and_then(|packets| gt06::decoder::decode(&packets, session.imei()));
+
+            // a login on a identified connection must be of the same tracker
+            let decode_result = match decode_result {
+                Ok(Message::Login(login)) => session
+                    .bind(&login.imei, &sender)
+                    .await
+                    .map(|_| Message::Login(login)),
+                result => result,
+            };
+
+            match decode_result {
+                Ok(msg) => {
+                    session.packets_decoded += 1;
+
+                    if let Some(response_to_tracker) = handle_decoded_message(msg, &sender) {
+                        // GT06 trackers only report positions after their login is acknowledged
+                        // and resend alarms until they are, so failing to write means the
+                        // connection is unusable.
+                        let write_result = session
+                            .writer()
+                            .lock()
+                            .await
+                            .write_all(&response_to_tracker)
+                            .await;
+
+                        if let Err(err) = write_result {
+                            error!("IO error writing response to tracker: {}", err);
+                            disconnect = Some(DisconnectReason::WriteError);
+                            break;
+                        }
+                    }
+                }
+                Err(err_msg) => {
+                    error!("error parsing gt06 packets: {}", err_msg);
+
+                    if session.record_invalid_packet() {
+                        disconnect = Some(DisconnectReason::InvalidPackets);
+                        break;
+                    }
+                }
+            }
+        }
+
+        if let Some(reason) = disconnect {
+            break reason;
+        }
+    };
+
+    session.close(reason, &sender).await;
+}
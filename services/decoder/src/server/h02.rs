@@ -1,32 +1,16 @@
-use crate::protocols::common::Decoded;
+use crate::packet_archive;
+use crate::protocols::common::Protocol;
 use crate::protocols::h02;
 use crate::protocols::h02::decoder::Message;
-use crate::rabbitmq::RmqMessage;
-use crate::server::listeners::{BUFFER_SIZE, INVALID_PACKET_LIMIT};
-use serde::Serialize;
+use crate::protocols::h02::framing::FrameBuffer;
+use crate::server::listeners::{send_event, RmqMsgSender, BUFFER_SIZE};
+use crate::server::session::Session;
+use shared::dto::decoder::session::DisconnectReason;
+use std::{net::SocketAddr, sync::Arc};
 use tokio::io::{self, AsyncReadExt, AsyncWriteExt};
 use tokio::net::TcpStream;
-use tokio::sync::mpsc::UnboundedSender;
-use tracing::{error, info_span, span, Level};
-
-type RmqMsgSender = UnboundedSender<(RmqMessage, tracing::Span)>;
-
-/// sends the decoded event to the rust channel, once
-/// recieved it will be sent to the tracker events exchange
-fn send_event<T>(event: Decoded<T>, sender: &RmqMsgSender) -> Result<(), String>
-where
-    T: Serialize,
-{
-    let span = info_span!("send_event");
-
-    let rmq_msg: RmqMessage = event.try_into()?;
-
-    sender
-        .send((rmq_msg, span))
-        .or(Err("rmq msg channel closed"))?;
-
-    Ok(())
-}
+use tokio::sync::Mutex;
+use tracing::{error, span, Level};
 
 #[tracing::instrument(skip_all)]
 fn handle_decoded_message(message: Message, sender: &RmqMsgSender) -> Option<Box<[u8]>> {
@@ -43,59 +27,148 @@ fn handle_decoded_message(message: Message, sender: &RmqMsgSender) -> Option<Box
 
             response
         }
-    }
-}
-
-pub async fn stream_handler(stream: TcpStream, sender: RmqMsgSender) {
-    let mut buffer = vec![0; BUFFER_SIZE];
-
-    let (mut reader, mut writer) = io::split(stream);
+        Message::Lbs(decoded) => {
+            let response = decoded.response.clone();
+            let _ = send_event(decoded, sender);
 
-    let mut invalid_packets_cnt: usize = 0;
+            response
+        }
+        Message::LinkStatus(decoded) => {
+            let response = decoded.response.clone();
+            let _ = send_event(decoded, sender);
 
-    while let Ok(n) = reader.read(&mut buffer).await {
-        if n == 0 {
-            // EOF
-            break;
+            response
         }
+        Message::CommandAck(decoded) => {
+            let response = decoded.response.clone();
+            let _ = send_event(decoded, sender);
 
-        let packets = &buffer[..n];
-        let packets_len = packets.len();
+            response
+        }
+    }
+}
 
-        let span = span!(
-            Level::ERROR,
-            "stream_handler",
-            invalid_packets_cnt,
-            packets_len
-        );
-        let _enter = span.enter();
+pub async fn stream_handler(stream: TcpStream, sender: RmqMsgSender) {
+    let mut buffer = vec![0; BUFFER_SIZE];
 
-        let decode_result = h02::decoder::decode(packets);
+    let peer = stream.peer_addr().ok();
+
+    let (mut reader, writer) = io::split(stream);
+
+    // shared with the commands consumer once the tracker connection is registered
+    let mut session = Session::new(Protocol::H02, peer, Arc::new(Mutex::new(writer)));
+
+    let mut frames = FrameBuffer::new();
+
+    let reason = loop {
+        let n = match reader.read(&mut buffer).await {
+            Ok(0) => break DisconnectReason::Closed,
+            Ok(n) => n,
+            Err(_) => break DisconnectReason::ReadError,
+        };
+
+        session.bytes_received += n;
+        frames.extend(&buffer[..n]);
+
+        let mut disconnect = None;
+
+        // a single read might contain many frames or only part of one, so decode
+        // every complete frame and keep the rest buffered until the next read
+        while let Some(frame) = frames.next_frame() {
+            let packets_len = frame.as_ref().map_or(0, |f| f.len());
+
+            let span = span!(
+                Level::ERROR,
+                "stream_handler",
+                invalid_packets_cnt = session.invalid_packets,
+                packets_len
+            );
+            let _enter = span.enter();
+
+            let decode_result = frame.and_then(|packets| h02::decoder::decode(&packets));
+
+            // every H02 message contains the imei, so every message is checked
+            // against the imei of the first message of the connection
+            let decode_result = match decode_result {
+                Ok(msg) => session.bind(msg.imei(), &sender).await.map(|_| msg),
+                Err(err) => Err(err),
+            };
+
+            match decode_result {
+                Ok(msg) => {
+                    session.packets_decoded += 1;
+
+                    if let Some(response_to_tracker) = handle_decoded_message(msg, &sender) {
+                        // We intentionally block on write here because because writes rarely happen (so blocking should not be much of a problem)
+                        // and because some tracker models should receive the response to their commands in order, so if a tracker sends a command
+                        // A and B responses A1 and B1 should be in that order.
+                        let write_result = session
+                            .writer()
+                            .lock()
+                            .await
+                            .write_all(&response_to_tracker)
+                            .await;
+
+                        if let Err(err) = write_result {
+                            // writes to the tracker happen when responding to commands and failures
+                            // are a really bad state, so for now assume the connection is unrecoverable
+                            // and end it.
+                            error!("IO error writing response to tracker: {}", err);
+                            disconnect = Some(DisconnectReason::WriteError);
+                            break;
+                        }
+                    }
+                }
+                Err(err_msg) => {
+                    error!("error parsing h02 packets: {}", err_msg);
 
-        match decode_result {
-            Ok(msg) => {
-                if let Some(response_to_tracker) = handle_decoded_message(msg, &sender) {
-                    // We intentionally block on write here because because writes rarely happen (so blocking should not be much of a problem)
-                    // and because some tracker models should receive the response to their commands in order, so if a tracker sends a command
-                    // A and B responses A1 and B1 should be in that order.
-                    if let Err(err) = writer.write_all(&response_to_tracker).await {
-                        // writes to the tracker happen when responding to commands and failures
-                        // are a really bad state, so for now assume the connection is unrecoverable
-                        // and end it.
-                        error!("IO error writing response to tracker: {}", err);
+                    if session.record_invalid_packet() {
+                        disconnect = Some(DisconnectReason::InvalidPackets);
                         break;
                     }
                 }
             }
-            Err(err_msg) => {
-                error!("error parsing h02 packets: {}", err_msg);
+        }
 
-                invalid_packets_cnt += 1;
+        // archived after decoding so the imei of the first packet is known
+        if let Some(peer) = peer {
+            packet_archive::record(session.imei(), peer, &buffer[..n]);
+        }
 
-                if invalid_packets_cnt >= INVALID_PACKET_LIMIT {
-                    break;
-                }
+        if let Some(reason) = disconnect {
+            break reason;
+        }
+    };
+
+    session.close(reason, &sender).await;
+}
+
+/// Decodes every H02 frame on a UDP datagram, returning the responses to the tracker
+pub fn datagram_handler(
+    datagram: &[u8],
+    source: SocketAddr,
+    sender: &RmqMsgSender,
+) -> Vec<Box<[u8]>> {
+    let mut frames = FrameBuffer::new();
+    frames.extend(datagram);
+    frames.finish();
+
+    let mut responses = Vec::new();
+    let mut imei: Option<String> = None;
+
+    // unlike TCP a frame cannot be split between datagrams, so any
+    // incomplete frame left on the buffer is simply discarded
+    while let Some(frame) = frames.next_frame() {
+        match frame.and_then(|packets| h02::decoder::decode(&packets)) {
+            Ok(msg) => {
+                imei.get_or_insert_with(|| msg.imei().to_string());
+                responses.extend(handle_decoded_message(msg, sender));
             }
+            Err(err_msg) => error!("error parsing h02 datagram: {}", err_msg),
         }
     }
+
+    packet_archive::record(imei.as_deref(), source, datagram);
+
+    responses
 }
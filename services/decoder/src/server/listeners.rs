@@ -1,10 +1,13 @@
+use crate::protocols::common::Decoded;
 use crate::rabbitmq::RmqMessage;
-use std::{future::Future, marker::Send};
+use serde::Serialize;
+use std::{future::Future, marker::Send, net::SocketAddr};
 use tokio::{
-    net::{TcpListener, TcpStream},
+    net::{TcpListener, TcpStream, UdpSocket},
     sync::mpsc::UnboundedSender,
     task::JoinHandle,
 };
+use tracing::{error, info_span};
 
 /// The buffer size to be used when reading tracker connections.
 ///
@@ -13,20 +16,45 @@ use tokio::{
 /// this then its very unlikely to be a tracking device
 pub const BUFFER_SIZE: usize = 512;
 
+/// The buffer size to be used when reading UDP datagrams.
+///
+/// Unlike TCP a datagram cannot be read in parts, so this must fit the biggest
+/// packet a tracker can send, such as Teltonika AVL data with many records
+pub const DATAGRAM_BUFFER_SIZE: usize = 2048;
+
 /// The maximum amount of undecodable packets a tracker can send
 /// before its connection should be dropped
 pub const INVALID_PACKET_LIMIT: usize = 10;
 
+pub type RmqMsgSender = UnboundedSender<(RmqMessage, tracing::Span)>;
+
+/// sends the decoded event to the rust channel, once
+/// recieved it will be sent to the tracker events exchange
+pub fn send_event<T>(event: Decoded<T>, sender: &RmqMsgSender) -> Result<(), String>
+where
+    T: Serialize,
+{
+    let span = info_span!("send_event");
+
+    let rmq_msg: RmqMessage = event.try_into()?;
+
+    sender
+        .send((rmq_msg, span))
+        .or(Err("rmq msg channel closed"))?;
+
+    Ok(())
+}
+
 /// A TCP handle receives the tcp stream to handle and a unbounded sender
 /// to send the decoded tracker events sent over the TCP connection (such
 /// as a new position or tracker command response)
-type TcpHandler<R> = fn(TcpStream, UnboundedSender<(RmqMessage, tracing::Span)>) -> R;
+type TcpHandler<R> = fn(TcpStream, RmqMsgSender) -> R;
 
 /// Start a new tokio task that binds a TcpListener to addr and pass all
 /// incoming connections to the the handler on another task.
 pub fn start_tcp_listener(
     addr: &str,
-    sender: UnboundedSender<(RmqMessage, tracing::Span)>,
+    sender: RmqMsgSender,
     handler: TcpHandler<impl Future<Output = ()> + 'static + Send>,
 ) -> JoinHandle<()> {
     let addr = addr.to_string();
@@ -45,3 +73,41 @@ pub fn start_tcp_listener(
         println!("[TCP] listener at: {} stopped", addr);
     })
 }
+
+/// A UDP handler receives a datagram, its source address and a unbounded sender to send
+/// the decoded tracker events on the datagram, returning the responses to the tracker
+type UdpHandler = fn(&[u8], SocketAddr, &RmqMsgSender) -> Vec<Box<[u8]>>;
+
+/// Start a new tokio task that binds a UdpSocket to addr and pass all received
+/// datagrams to the handler, sending its responses back to the datagram source.
+pub fn start_udp_listener(addr: &str, sender: RmqMsgSender, handler: UdpHandler) -> JoinHandle<()> {
+    let addr = addr.to_string();
+
+    tokio::spawn(async move {
+        let socket = UdpSocket::bind(addr.clone())
+            .await
+            .expect("failed to start UDP listener");
+
+        println!("[UDP] listener started at: {}", addr);
+
+        let mut buffer = vec![0; DATAGRAM_BUFFER_SIZE];
+
+        loop {
+            let (n, source) = match socket.recv_from(&mut buffer).await {
+                Ok(received) => received,
+                Err(err) => {
+                    // errors on a UDP socket are caused by a single datagram (eg: a ICMP
+                    // port unreachable of a previous response), so keep listening
+                    error!("[UDP] error receiving datagram: {}", err);
+                    continue;
+                }
+            };
+
+            for response in handler(&buffer[..n], source, &sender) {
+                if let Err(err) = socket.send_to(&response, source).await {
+                    error!("[UDP] IO error writing response to tracker: {}", err);
+                }
+            }
+        }
+    })
+}
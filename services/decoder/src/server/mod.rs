@@ -1,2 +1,7 @@
+pub mod connections;
+pub mod gt06;
 pub mod h02;
 pub mod listeners;
+pub mod multiplexer;
+pub mod session;
+pub mod teltonika;
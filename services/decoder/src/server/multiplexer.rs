@@ -0,0 +1,192 @@
+use crate::protocols::common::Protocol;
+use crate::server::listeners::RmqMsgSender;
+use crate::server::{gt06, h02, teltonika};
+use std::time::Duration;
+use tokio::{
+    net::{TcpListener, TcpStream},
+    task::JoinHandle,
+};
+use tracing::{error, info};
+
+/// Amount of bytes needed to detect the protocol of any supported tracker
+const DETECTION_LEN: usize = 3;
+
+/// Interval to peek the connection again when not enough bytes were received
+const PEEK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Maximum length of the IMEI sent on the teltonika handshake
+const MAX_TELTONIKA_IMEI_LEN: u8 = 17;
+
+enum Detection {
+    Match(Protocol),
+    NoMatch,
+
+    /// more bytes are needed to tell if the connection matches a protocol
+    Incomplete,
+}
+
+/// if `bytes` starts with `prefix`, `None` if `bytes` is a partial prefix
+fn matches_prefix(bytes: &[u8], prefix: &[u8]) -> Option<bool> {
+    if bytes.len() >= prefix.len() {
+        return Some(bytes.starts_with(prefix));
+    }
+
+    match prefix.starts_with(bytes) {
+        true => None,
+        false => Some(false),
+    }
+}
+
+/// if `bytes` starts with a teltonika IMEI handshake, 2 bytes length followed
+/// by the IMEI digits, `None` if not enough bytes were received to tell
+fn matches_teltonika_handshake(bytes: &[u8]) -> Option<bool> {
+    match bytes {
+        [] | [0x00] => None,
+        [0x00, len] if (1..=MAX_TELTONIKA_IMEI_LEN).contains(len) => None,
+        [0x00, len, digit, ..] => {
+            Some((1..=MAX_TELTONIKA_IMEI_LEN).contains(len) && digit.is_ascii_digit())
+        }
+        _ => Some(false),
+    }
+}
+
+/// Detects the tracker protocol by the first bytes sent on the connection
+fn detect_protocol(bytes: &[u8]) -> Detection {
+    let candidates = [
+        (Protocol::H02, matches_prefix(bytes, b"*HQ")),
+        (Protocol::H02, matches_prefix(bytes, b"$")),
+        (Protocol::Gt06, matches_prefix(bytes, &[0x78, 0x78])),
+        (Protocol::Gt06, matches_prefix(bytes, &[0x79, 0x79])),
+        (Protocol::Teltonika, matches_teltonika_handshake(bytes)),
+    ];
+
+    let mut incomplete = false;
+
+    for (protocol, matches) in candidates {
+        match matches {
+            Some(true) => return Detection::Match(protocol),
+            Some(false) => {}
+            None => incomplete = true,
+        }
+    }
+
+    match incomplete {
+        true => Detection::Incomplete,
+        false => Detection::NoMatch,
+    }
+}
+
+/// Peeks the first bytes sent on the connection, without consuming them, until
+/// the protocol is detected so the stream can be handed to the protocol handler
+/// as if it was received on the protocol port.
+async fn detect_stream_protocol(stream: &TcpStream) -> Result<Protocol, String> {
+    let mut buffer = [0; DETECTION_LEN];
+
+    loop {
+        let n = stream.peek(&mut buffer).await.map_err(|e| e.to_string())?;
+
+        if n == 0 {
+            return Err("connection closed before protocol detection".to_string());
+        }
+
+        match detect_protocol(&buffer[..n]) {
+            Detection::Match(protocol) => return Ok(protocol),
+            Detection::NoMatch => return Err("no protocol matches the connection".to_string()),
+            // peek returns as soon as any byte is available, so wait for more
+            Detection::Incomplete => tokio::time::sleep(PEEK_INTERVAL).await,
+        }
+    }
+}
+
+async fn stream_handler(stream: TcpStream, sender: RmqMsgSender, detection_timeout: Duration) {
+    let detection = tokio::time::timeout(detection_timeout, detect_stream_protocol(&stream)).await;
+
+    let protocol = match detection {
+        Ok(Ok(protocol)) => protocol,
+        Ok(Err(err)) => {
+            error!("[MUX] failed to detect protocol: {}", err);
+            return;
+        }
+        Err(_) => {
+            error!("[MUX] protocol detection timed out");
+            return;
+        }
+    };
+
+    info!("[MUX] detected protocol: {}", protocol);
+
+    match protocol {
+        Protocol::H02 => h02::stream_handler(stream, sender).await,
+        Protocol::Gt06 => gt06::stream_handler(stream, sender).await,
+        Protocol::Teltonika => teltonika::stream_handler(stream, sender).await,
+    }
+}
+
+/// Start a new tokio task that binds a TcpListener to addr and pass all incoming
+/// connections to the handler of the protocol detected by their first bytes, so
+/// trackers of any protocol can connect on a single port.
+///
+/// Connections that do not match any protocol or do not send enough bytes to
+/// detect it within the `detection_timeout` are closed.
+pub fn start_multiplexed_listener(
+    addr: &str,
+    sender: RmqMsgSender,
+    detection_timeout: Duration,
+) -> JoinHandle<()> {
+    let addr = addr.to_string();
+
+    tokio::spawn(async move {
+        let listener = TcpListener::bind(addr.clone())
+            .await
+            .expect("failed to start multiplexed TCP listener");
+
+        println!("[MUX] listener started at: {}", addr);
+
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(stream_handler(stream, sender.clone(), detection_timeout));
+        }
+
+        println!("[MUX] listener at: {} stopped", addr);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect_protocol, Detection};
+    use crate::protocols::common::Protocol;
+
+    fn assert_match(bytes: &[u8], expected: Protocol) {
+        match detect_protocol(bytes) {
+            Detection::Match(protocol) => assert_eq!(protocol.to_string(), expected.to_string()),
+            _ => panic!("{bytes:?} did not match {expected}"),
+        }
+    }
+
+    #[test]
+    fn detects_protocols() {
+        assert_match(b"*HQ,867232051148352,HTBT#", Protocol::H02);
+        assert_match(&[0x24, 0x73, 0x01, 0x00], Protocol::H02);
+        assert_match(&[0x78, 0x78, 0x0D, 0x01], Protocol::Gt06);
+        assert_match(&[0x79, 0x79, 0x00, 0x07], Protocol::Gt06);
+        assert_match(b"\x00\x0F356307042441013", Protocol::Teltonika);
+    }
+
+    #[test]
+    fn waits_for_more_bytes() {
+        for bytes in [b"".as_slice(), b"*", b"*H", &[0x78], &[0x00], &[0x00, 0x0F]] {
+            assert!(matches!(detect_protocol(bytes), Detection::Incomplete));
+        }
+    }
+
+    #[test]
+    fn rejects_unknown_protocols() {
+        for bytes in [
+            b"GET / HTTP/1.1".as_slice(),
+            b"*HX",
+            &[0x78, 0x00],
+            &[0x00, 0x40],
+        ] {
+            assert!(matches!(detect_protocol(bytes), Detection::NoMatch));
+        }
+    }
+}
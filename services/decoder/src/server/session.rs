@@ -0,0 +1,173 @@
+use crate::packet_archive;
+use crate::protocols::common::{Decoded, Protocol, TrackerEvent};
+use crate::server::connections::{self, TrackerWriter};
+use crate::server::listeners::{send_event, RmqMsgSender, INVALID_PACKET_LIMIT};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use shared::dto::decoder::session::{ConnectedMsg, DisconnectReason, DisconnectedMsg};
+use std::net::SocketAddr;
+
+/// State of a tracker TCP connection, shared by every protocol stream handler.
+///
+/// The session is bound to the imei of the first valid packet (or login) of the connection,
+/// registering the connection for commands and publishing the `connected` event, any later
+/// packet claiming a different imei is rejected, so a connection cannot send events on behalf
+/// of another tracker.
+pub struct Session {
+    pub protocol: Protocol,
+
+    /// address of the tracker, if it could be read from the socket
+    pub peer: Option<SocketAddr>,
+
+    pub connected_at: DateTime<Utc>,
+
+    /// imei of the tracker on the connection, known after its first valid packet
+    imei: Option<String>,
+
+    writer: TrackerWriter,
+
+    pub bytes_received: usize,
+
+    pub packets_decoded: usize,
+
+    pub invalid_packets: usize,
+}
+
+impl Session {
+    pub fn new(protocol: Protocol, peer: Option<SocketAddr>, writer: TrackerWriter) -> Session {
+        Session {
+            protocol,
+            peer,
+            connected_at: Utc::now(),
+            imei: None,
+            writer,
+            bytes_received: 0,
+            packets_decoded: 0,
+            invalid_packets: 0,
+        }
+    }
+
+    pub fn imei(&self) -> Option<&str> {
+        self.imei.as_deref()
+    }
+
+    pub fn writer(&self) -> &TrackerWriter {
+        &self.writer
+    }
+
+    fn event<T: Serialize>(&self, imei: &str, event_type: TrackerEvent, data: T) -> Decoded<T> {
+        Decoded {
+            event_type,
+            imei: imei.to_string(),
+            data,
+            response: None,
+            protocol: self.protocol,
+        }
+    }
+
+    /// Binds the session to the imei of a valid packet, the first bound imei registers
+    /// the connection and publishes the `connected` event, fails if the imei differs
+    /// from the imei already bound to the session.
+    pub async fn bind(&mut self, imei: &str, sender: &RmqMsgSender) -> Result<(), String> {
+        match &self.imei {
+            Some(bound) if bound == imei => Ok(()),
+            Some(bound) => Err(format!(
+                "packet imei {imei} does not match the connection imei {bound}"
+            )),
+            None => {
+                self.imei = Some(imei.to_string());
+                connections::register(imei, self.protocol, &self.writer).await;
+
+                let connected = ConnectedMsg {
+                    peer: self.peer.map(|peer| peer.to_string()),
+                    connected_at: self.connected_at,
+                    timestamp: Utc::now(),
+                };
+
+                let _ = send_event(self.event(imei, TrackerEvent::Connected, connected), sender);
+
+                Ok(())
+            }
+        }
+    }
+
+    /// Counts a packet that could not be decoded or was rejected,
+    /// returning if the connection reached the invalid packet limit
+    pub fn record_invalid_packet(&mut self) -> bool {
+        self.invalid_packets += 1;
+        self.invalid_packets >= INVALID_PACKET_LIMIT
+    }
+
+    /// Ends the session, unregistering the connection and publishing
+    /// the `disconnected` event if the tracker was identified
+    pub async fn close(self, reason: DisconnectReason, sender: &RmqMsgSender) {
+        let Some(imei) = &self.imei else {
+            return;
+        };
+
+        connections::unregister(imei, &self.writer).await;
+        packet_archive::close(imei);
+
+        let disconnected = DisconnectedMsg {
+            peer: self.peer.map(|peer| peer.to_string()),
+            connected_at: self.connected_at,
+            timestamp: Utc::now(),
+            reason,
+            bytes_received: self.bytes_received,
+            packets_decoded: self.packets_decoded,
+            invalid_packets: self.invalid_packets,
+        };
+
+        let _ = send_event(
+            self.event(imei, TrackerEvent::Disconnected, disconnected),
+            sender,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Session;
+    use crate::protocols::common::Protocol;
+    use crate::server::connections;
+    use shared::dto::decoder::session::DisconnectReason;
+    use std::sync::Arc;
+    use tokio::{
+        io,
+        net::{TcpListener, TcpStream},
+        sync::{mpsc, Mutex},
+    };
+
+    #[tokio::test]
+    async fn binds_the_first_imei() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let stream = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+
+        let peer = stream.peer_addr().ok();
+        let (_, writer) = io::split(stream);
+
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let mut session = Session::new(Protocol::H02, peer, Arc::new(Mutex::new(writer)));
+
+        session.bind("867232051148352", &sender).await.unwrap();
+        session.bind("867232051148352", &sender).await.unwrap();
+
+        assert!(session.bind("867232051148353", &sender).await.is_err());
+        assert_eq!(session.imei(), Some("867232051148352"));
+        assert!(connections::get("867232051148352").await.is_some());
+
+        session.close(DisconnectReason::Closed, &sender).await;
+
+        assert!(connections::get("867232051148352").await.is_none());
+
+        let (connected, _) = receiver.recv().await.unwrap();
+        let (disconnected, _) = receiver.recv().await.unwrap();
+
+        assert_eq!(connected.routing_key, "h02.connected.867232051148352");
+        assert_eq!(disconnected.routing_key, "h02.disconnected.867232051148352");
+        assert!(disconnected.body.contains("\"reason\":\"closed\""));
+        assert!(receiver.try_recv().is_err());
+    }
+}
@@ -5,6 +5,21 @@ mod m20240125_134615_init;
 mod m20240125_135000_hypertable_tracker_last_location;
 mod m20240125_135052_last_position_trigger;
 mod m20240128_013232_seed_test_data;
+mod m20240205_120000_tracker_model_gt06;
+mod m20240210_120000_tracker_model_teltonika;
+mod m20240215_120000_tracker_command;
+mod m20240220_120000_tracker_connectivity;
+mod m20240225_120000_location_telemetry;
+mod m20240301_120000_tracker_alarm;
+mod m20240305_120000_geofence;
+mod m20240310_120000_trip;
+mod m20240315_120000_vehicle_odometer;
+mod m20240320_120000_position_filter;
+mod m20240325_120000_grant_tracker_command_permission;
+mod m20240325_120100_grant_tracker_alarm_permission;
+mod m20240325_120200_grant_geofence_permissions;
+mod m20240330_120000_vehicle_daily_summary;
+mod m20240405_120000_geofence_checked_at;
 mod seeder;
 mod seeder_consts;
 
@@ -19,6 +34,21 @@ impl MigratorTrait for Migrator {
             Box::new(m20240125_135000_hypertable_tracker_last_location::Migration),
             Box::new(m20240125_135052_last_position_trigger::Migration),
             Box::new(m20240128_013232_seed_test_data::Migration),
+            Box::new(m20240205_120000_tracker_model_gt06::Migration),
+            Box::new(m20240210_120000_tracker_model_teltonika::Migration),
+            Box::new(m20240215_120000_tracker_command::Migration),
+            Box::new(m20240220_120000_tracker_connectivity::Migration),
+            Box::new(m20240225_120000_location_telemetry::Migration),
+            Box::new(m20240301_120000_tracker_alarm::Migration),
+            Box::new(m20240305_120000_geofence::Migration),
+            Box::new(m20240310_120000_trip::Migration),
+            Box::new(m20240315_120000_vehicle_odometer::Migration),
+            Box::new(m20240320_120000_position_filter::Migration),
+            Box::new(m20240325_120000_grant_tracker_command_permission::Migration),
+            Box::new(m20240325_120100_grant_tracker_alarm_permission::Migration),
+            Box::new(m20240325_120200_grant_geofence_permissions::Migration),
+            Box::new(m20240330_120000_vehicle_daily_summary::Migration),
+            Box::new(m20240405_120000_geofence_checked_at::Migration),
         ]
     }
 }
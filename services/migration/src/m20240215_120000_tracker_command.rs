@@ -0,0 +1,65 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        let statement = r#"
+CREATE TYPE "tracker_command_status" AS ENUM ('PENDING', 'SENT', 'ACKNOWLEDGED', 'FAILED', 'EXPIRED');
+
+CREATE TABLE "tracker_command" (
+    "id" serial PRIMARY KEY,
+    "created_at" timestamptz(0) NOT NULL DEFAULT now(),
+    "command" jsonb NOT NULL,
+    "status" tracker_command_status NOT NULL DEFAULT 'PENDING',
+    "sent_at" timestamptz(0) NULL,
+    "acknowledged_at" timestamptz(0) NULL,
+    "failure_reason" text NULL,
+    "response" text NULL,
+    "vehicle_tracker_id" int NOT NULL,
+    "vehicle_id" int NULL,
+    "user_id" int NULL,
+    "organization_id" int NOT NULL
+);
+
+COMMENT ON
+COLUMN "tracker_command"."vehicle_id" IS 'The vehicle the tracker was installed on when the command was sent';
+
+COMMENT ON
+COLUMN "tracker_command"."response" IS 'The tracker response to the command, received with its acknowledgement';
+
+CREATE INDEX idx_tracker_command_vehicle_tracker_id ON "tracker_command" ("vehicle_tracker_id", "id");
+
+ALTER TABLE "tracker_command"
+ADD CONSTRAINT "tracker_command_vehicle_tracker_id_foreign" FOREIGN KEY ("vehicle_tracker_id") REFERENCES "vehicle_tracker" ("id")
+ON UPDATE CASCADE
+ON DELETE CASCADE;
+
+ALTER TABLE "tracker_command"
+ADD CONSTRAINT "tracker_command_vehicle_id_foreign" FOREIGN KEY ("vehicle_id") REFERENCES "vehicle" ("id")
+ON UPDATE CASCADE
+ON DELETE SET NULL;
+
+ALTER TABLE "tracker_command"
+ADD CONSTRAINT "tracker_command_user_id_foreign" FOREIGN KEY ("user_id") REFERENCES "user" ("id")
+ON UPDATE CASCADE
+ON DELETE SET NULL;
+
+ALTER TABLE "tracker_command"
+ADD CONSTRAINT "tracker_command_organization_id_foreign" FOREIGN KEY ("organization_id") REFERENCES "organization" ("id")
+ON UPDATE CASCADE;
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(String::from("cannot be reverted")))
+    }
+}
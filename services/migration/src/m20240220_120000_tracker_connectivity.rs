@@ -0,0 +1,37 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        let statement = r#"
+ALTER TABLE "vehicle_tracker"
+ADD COLUMN "last_seen_at" timestamptz NULL,
+ADD COLUMN "last_connected_at" timestamptz NULL,
+ADD COLUMN "last_disconnected_at" timestamptz NULL;
+
+COMMENT ON
+COLUMN "vehicle_tracker"."last_seen_at" IS 'When the API last received a location, heartbeat or connection event of the tracker';
+
+COMMENT ON
+COLUMN "vehicle_tracker"."last_connected_at" IS 'When the last TCP connection of the tracker to the decoder was opened';
+
+COMMENT ON
+COLUMN "vehicle_tracker"."last_disconnected_at" IS 'When the last TCP connection of the tracker to the decoder was closed';
+
+CREATE INDEX idx_vehicle_tracker_last_seen_at ON "vehicle_tracker" ("organization_id", "last_seen_at");
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(String::from("cannot be reverted")))
+    }
+}
@@ -0,0 +1,66 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        let statement = r#"
+ALTER TABLE "vehicle_tracker_location"
+ADD COLUMN "speed" double precision NULL,
+ADD COLUMN "heading" int NULL,
+ADD COLUMN "ignition" boolean NULL,
+ADD COLUMN "status" jsonb NULL;
+
+ALTER TABLE "vehicle_tracker_last_location"
+ADD COLUMN "speed" double precision NULL,
+ADD COLUMN "heading" int NULL,
+ADD COLUMN "ignition" boolean NULL,
+ADD COLUMN "status" jsonb NULL;
+
+COMMENT ON
+COLUMN "vehicle_tracker_location"."speed" IS 'Speed in km/h';
+
+COMMENT ON
+COLUMN "vehicle_tracker_location"."heading" IS 'Direction in degrees, 0 being north and 90 east';
+
+COMMENT ON
+COLUMN "vehicle_tracker_location"."ignition" IS 'If the vehicle ignition (ACC) was on, null if not sent by the tracker';
+
+COMMENT ON
+COLUMN "vehicle_tracker_location"."status" IS 'Protocol specific status sent with the location, eg: H02 status flags or teltonika IO elements';
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        // the last location is kept by a trigger, so it must copy the new columns
+        let statement = r#"
+        CREATE OR REPLACE FUNCTION create_last_pos_trigger_fn() RETURNS TRIGGER LANGUAGE PLPGSQL AS
+              $BODY$
+                  BEGIN
+                      INSERT INTO vehicle_tracker_last_location (vehicle_tracker_id, point, time, speed, heading, ignition, status)
+                      VALUES (NEW.vehicle_tracker_id, NEW.point, NEW.time, NEW.speed, NEW.heading, NEW.ignition, NEW.status)
+                      ON CONFLICT (vehicle_tracker_id) DO UPDATE SET 
+                      point=NEW.point,
+                      time=NEW.time,
+                      speed=NEW.speed,
+                      heading=NEW.heading,
+                      ignition=NEW.ignition,
+                      status=NEW.status;
+                      RETURN NEW;
+                  END
+              $BODY$;
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(String::from("cannot be reverted")))
+    }
+}
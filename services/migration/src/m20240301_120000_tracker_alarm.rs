@@ -0,0 +1,77 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        let statement = r#"
+CREATE TYPE "tracker_alarm_type" AS ENUM (
+    'SOS',
+    'THEFT',
+    'ROBBERY',
+    'OVERSPEED',
+    'ILLEGAL_IGNITION',
+    'STORAGE_BATTERY_REMOVED',
+    'TEMPERATURE',
+    'GPS_RECEIVER_FAULT',
+    'GPS_ANTENNA_OPEN_CIRCUIT',
+    'GPS_ANTENNA_SHORT_CIRCUIT',
+    'CROSS_BORDER_IN',
+    'CROSS_BORDER_OUT',
+    'CUSTOM'
+);
+
+CREATE TABLE "tracker_alarm" (
+    "id" serial PRIMARY KEY,
+    "created_at" timestamptz(0) NOT NULL DEFAULT now(),
+    "time" timestamptz(0) NOT NULL,
+    "alarm_type" tracker_alarm_type NOT NULL,
+    "vehicle_tracker_id" int NOT NULL,
+    "vehicle_id" int NULL,
+    "organization_id" int NOT NULL,
+    "acknowledged_at" timestamptz(0) NULL,
+    "acknowledged_by_user_id" int NULL,
+    "acknowledgement_note" text NULL
+);
+
+COMMENT ON
+COLUMN "tracker_alarm"."time" IS 'When the alarm was raised, as sent by the tracker';
+
+COMMENT ON
+COLUMN "tracker_alarm"."vehicle_id" IS 'The vehicle the tracker was installed on when the alarm was raised';
+
+CREATE INDEX idx_tracker_alarm_vehicle_tracker_id ON "tracker_alarm" ("vehicle_tracker_id", "id");
+
+ALTER TABLE "tracker_alarm"
+ADD CONSTRAINT "tracker_alarm_vehicle_tracker_id_foreign" FOREIGN KEY ("vehicle_tracker_id") REFERENCES "vehicle_tracker" ("id")
+ON UPDATE CASCADE
+ON DELETE CASCADE;
+
+ALTER TABLE "tracker_alarm"
+ADD CONSTRAINT "tracker_alarm_vehicle_id_foreign" FOREIGN KEY ("vehicle_id") REFERENCES "vehicle" ("id")
+ON UPDATE CASCADE
+ON DELETE SET NULL;
+
+ALTER TABLE "tracker_alarm"
+ADD CONSTRAINT "tracker_alarm_acknowledged_by_user_id_foreign" FOREIGN KEY ("acknowledged_by_user_id") REFERENCES "user" ("id")
+ON UPDATE CASCADE
+ON DELETE SET NULL;
+
+ALTER TABLE "tracker_alarm"
+ADD CONSTRAINT "tracker_alarm_organization_id_foreign" FOREIGN KEY ("organization_id") REFERENCES "organization" ("id")
+ON UPDATE CASCADE;
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(String::from("cannot be reverted")))
+    }
+}
@@ -0,0 +1,105 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        let statement = r#"
+CREATE TYPE "geofence_shape" AS ENUM ('POLYGON', 'CIRCLE');
+
+CREATE TYPE "geofence_event_type" AS ENUM ('ENTER', 'EXIT');
+
+CREATE TABLE "geofence" (
+    "id" serial PRIMARY KEY,
+    "created_at" timestamptz(0) NOT NULL DEFAULT now(),
+    "name" varchar(255) NOT NULL,
+    "description" text NULL,
+    "shape" geofence_shape NOT NULL,
+    "geojson" jsonb NOT NULL,
+    "radius" double precision NULL,
+    "geometry" geometry GENERATED ALWAYS AS (ST_SetSRID(ST_GeomFromGeoJSON("geojson"::text), 4326)) STORED,
+    "organization_id" int NOT NULL,
+    CONSTRAINT "geofence_circle_radius_check" CHECK (("shape" = 'CIRCLE') = ("radius" IS NOT NULL))
+);
+
+COMMENT ON
+COLUMN "geofence"."geojson" IS 'GeoJSON geometry of the geofence, a Polygon or the Point at the center of a circle';
+
+COMMENT ON
+COLUMN "geofence"."radius" IS 'Radius in meters of circle geofences';
+
+CREATE INDEX idx_geofence_organization_id ON "geofence" ("organization_id");
+
+CREATE INDEX idx_geofence_geometry ON "geofence" USING GIST ("geometry");
+
+ALTER TABLE "geofence"
+ADD CONSTRAINT "geofence_organization_id_foreign" FOREIGN KEY ("organization_id") REFERENCES "organization" ("id")
+ON UPDATE CASCADE;
+
+CREATE TABLE "vehicle_geofence" (
+    "vehicle_id" int NOT NULL,
+    "geofence_id" int NOT NULL,
+    "inside" boolean NULL,
+    CONSTRAINT "vehicle_geofence_pkey" PRIMARY KEY ("vehicle_id", "geofence_id")
+);
+
+COMMENT ON
+COLUMN "vehicle_geofence"."inside" IS 'If the last position of the vehicle was inside the geofence, null until the first position after the assignment';
+
+ALTER TABLE "vehicle_geofence"
+ADD CONSTRAINT "vehicle_geofence_vehicle_id_foreign" FOREIGN KEY ("vehicle_id") REFERENCES "vehicle" ("id")
+ON UPDATE CASCADE
+ON DELETE CASCADE;
+
+ALTER TABLE "vehicle_geofence"
+ADD CONSTRAINT "vehicle_geofence_geofence_id_foreign" FOREIGN KEY ("geofence_id") REFERENCES "geofence" ("id")
+ON UPDATE CASCADE
+ON DELETE CASCADE;
+
+CREATE TABLE "geofence_event" (
+    "id" serial PRIMARY KEY,
+    "time" timestamptz(0) NOT NULL,
+    "event_type" geofence_event_type NOT NULL,
+    "geofence_id" int NOT NULL,
+    "vehicle_id" int NOT NULL,
+    "vehicle_tracker_id" int NULL,
+    "organization_id" int NOT NULL
+);
+
+CREATE INDEX idx_geofence_event_geofence_id ON "geofence_event" ("geofence_id", "id");
+
+CREATE INDEX idx_geofence_event_vehicle_id ON "geofence_event" ("vehicle_id", "id");
+
+ALTER TABLE "geofence_event"
+ADD CONSTRAINT "geofence_event_geofence_id_foreign" FOREIGN KEY ("geofence_id") REFERENCES "geofence" ("id")
+ON UPDATE CASCADE
+ON DELETE CASCADE;
+
+ALTER TABLE "geofence_event"
+ADD CONSTRAINT "geofence_event_vehicle_id_foreign" FOREIGN KEY ("vehicle_id") REFERENCES "vehicle" ("id")
+ON UPDATE CASCADE
+ON DELETE CASCADE;
+
+ALTER TABLE "geofence_event"
+ADD CONSTRAINT "geofence_event_vehicle_tracker_id_foreign" FOREIGN KEY ("vehicle_tracker_id") REFERENCES "vehicle_tracker" ("id")
+ON UPDATE CASCADE
+ON DELETE SET NULL;
+
+ALTER TABLE "geofence_event"
+ADD CONSTRAINT "geofence_event_organization_id_foreign" FOREIGN KEY ("organization_id") REFERENCES "organization" ("id")
+ON UPDATE CASCADE;
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(String::from("cannot be reverted")))
+    }
+}
@@ -0,0 +1,97 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        let statement = r#"
+CREATE TABLE "trip" (
+    "id" serial PRIMARY KEY,
+    "vehicle_id" int NOT NULL,
+    "vehicle_tracker_id" int NULL,
+    "organization_id" int NOT NULL,
+    "in_progress" boolean NOT NULL DEFAULT true,
+    "started_at" timestamptz(0) NOT NULL,
+    "ended_at" timestamptz(0) NOT NULL,
+    "start_lat" double precision NOT NULL,
+    "start_lng" double precision NOT NULL,
+    "end_lat" double precision NOT NULL,
+    "end_lng" double precision NOT NULL,
+    "distance" double precision NOT NULL DEFAULT 0,
+    "max_speed" double precision NULL
+);
+
+COMMENT ON
+COLUMN "trip"."ended_at" IS 'When the trip ended, or the time of its last position while in progress';
+
+COMMENT ON
+COLUMN "trip"."distance" IS 'Distance driven in meters, the sum of the distances between the trip positions';
+
+COMMENT ON
+COLUMN "trip"."max_speed" IS 'Highest speed of the trip positions in km/h';
+
+CREATE INDEX idx_trip_vehicle_id ON "trip" ("vehicle_id", "started_at");
+
+CREATE UNIQUE INDEX idx_trip_vehicle_id_in_progress ON "trip" ("vehicle_id") WHERE "in_progress";
+
+ALTER TABLE "trip"
+ADD CONSTRAINT "trip_vehicle_id_foreign" FOREIGN KEY ("vehicle_id") REFERENCES "vehicle" ("id")
+ON UPDATE CASCADE
+ON DELETE CASCADE;
+
+ALTER TABLE "trip"
+ADD CONSTRAINT "trip_vehicle_tracker_id_foreign" FOREIGN KEY ("vehicle_tracker_id") REFERENCES "vehicle_tracker" ("id")
+ON UPDATE CASCADE
+ON DELETE SET NULL;
+
+ALTER TABLE "trip"
+ADD CONSTRAINT "trip_organization_id_foreign" FOREIGN KEY ("organization_id") REFERENCES "organization" ("id")
+ON UPDATE CASCADE;
+
+CREATE TABLE "stop" (
+    "id" serial PRIMARY KEY,
+    "vehicle_id" int NOT NULL,
+    "vehicle_tracker_id" int NULL,
+    "organization_id" int NOT NULL,
+    "in_progress" boolean NOT NULL DEFAULT true,
+    "started_at" timestamptz(0) NOT NULL,
+    "ended_at" timestamptz(0) NOT NULL,
+    "lat" double precision NOT NULL,
+    "lng" double precision NOT NULL
+);
+
+COMMENT ON
+COLUMN "stop"."ended_at" IS 'When the vehicle started moving again, or the time of its last position while in progress';
+
+CREATE INDEX idx_stop_vehicle_id ON "stop" ("vehicle_id", "started_at");
+
+CREATE UNIQUE INDEX idx_stop_vehicle_id_in_progress ON "stop" ("vehicle_id") WHERE "in_progress";
+
+ALTER TABLE "stop"
+ADD CONSTRAINT "stop_vehicle_id_foreign" FOREIGN KEY ("vehicle_id") REFERENCES "vehicle" ("id")
+ON UPDATE CASCADE
+ON DELETE CASCADE;
+
+ALTER TABLE "stop"
+ADD CONSTRAINT "stop_vehicle_tracker_id_foreign" FOREIGN KEY ("vehicle_tracker_id") REFERENCES "vehicle_tracker" ("id")
+ON UPDATE CASCADE
+ON DELETE SET NULL;
+
+ALTER TABLE "stop"
+ADD CONSTRAINT "stop_organization_id_foreign" FOREIGN KEY ("organization_id") REFERENCES "organization" ("id")
+ON UPDATE CASCADE;
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(String::from("cannot be reverted")))
+    }
+}
@@ -0,0 +1,110 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        let statement = r#"
+ALTER TABLE "vehicle_tracker_location"
+ADD COLUMN "distance" double precision NULL,
+ADD COLUMN "ignition_seconds" int NULL;
+
+COMMENT ON
+COLUMN "vehicle_tracker_location"."distance" IS 'Geodesic distance in meters from the previous location of the tracker';
+
+COMMENT ON
+COLUMN "vehicle_tracker_location"."ignition_seconds" IS 'Seconds since the previous location of the tracker if its ignition was on, otherwise 0';
+
+ALTER TABLE "vehicle"
+ADD COLUMN "odometer" double precision NOT NULL DEFAULT 0;
+
+COMMENT ON
+COLUMN "vehicle"."odometer" IS 'Distance driven by the vehicle in meters, increased by every location of its tracker and calibrated by users';
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        // the distance and ignition time since the previous location are computed against the
+        // last location before it is replaced, locations older than the last location of the
+        // tracker are out of order, so they are not counted and no longer replace the last
+        // location, the ignition time of gaps longer than a hour is not counted either, since
+        // the tracker was most likely offline and the ignition unknown.
+        //
+        // IMPORTANT: location points are stored with the latitude as the X coordinate,
+        // so they are flipped before being cast to geography
+        let statement = r#"
+        CREATE OR REPLACE FUNCTION create_last_pos_trigger_fn() RETURNS TRIGGER LANGUAGE PLPGSQL AS
+              $BODY$
+                  DECLARE
+                      previous vehicle_tracker_last_location%ROWTYPE;
+                  BEGIN
+                      SELECT * INTO previous FROM vehicle_tracker_last_location WHERE vehicle_tracker_id = NEW.vehicle_tracker_id;
+
+                      IF FOUND AND NEW.time > previous.time THEN
+                          NEW.distance := ST_Distance(ST_FlipCoordinates(previous.point)::geography, ST_FlipCoordinates(NEW.point)::geography);
+
+                          NEW.ignition_seconds := CASE
+                              WHEN previous.ignition AND NEW.time - previous.time <= INTERVAL '1 hour'
+                              THEN EXTRACT(EPOCH FROM NEW.time - previous.time)::int
+                              ELSE 0
+                          END;
+
+                          UPDATE vehicle SET odometer = odometer + NEW.distance
+                          WHERE id = (SELECT vehicle_id FROM vehicle_tracker WHERE id = NEW.vehicle_tracker_id);
+                      END IF;
+
+                      INSERT INTO vehicle_tracker_last_location (vehicle_tracker_id, point, time, speed, heading, ignition, status)
+                      VALUES (NEW.vehicle_tracker_id, NEW.point, NEW.time, NEW.speed, NEW.heading, NEW.ignition, NEW.status)
+                      ON CONFLICT (vehicle_tracker_id) DO UPDATE SET 
+                      point=NEW.point,
+                      time=NEW.time,
+                      speed=NEW.speed,
+                      heading=NEW.heading,
+                      ignition=NEW.ignition,
+                      status=NEW.status
+                      WHERE vehicle_tracker_last_location.time < NEW.time;
+                      RETURN NEW;
+                  END
+              $BODY$;
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        // daily rollups of each tracker, with real time aggregation so the current day is
+        // included, days are UTC days, as continuous aggregates only support fixed buckets
+        let statement = r#"
+CREATE MATERIALIZED VIEW "vehicle_tracker_daily_summary"
+WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+SELECT
+    "vehicle_tracker_id",
+    time_bucket(INTERVAL '1 day', "time") AS "day",
+    coalesce(sum("distance"), 0) AS "distance",
+    max("speed") AS "max_speed",
+    avg("speed") AS "avg_speed",
+    coalesce(sum("ignition_seconds"), 0) AS "ignition_seconds",
+    count(*) AS "positions"
+FROM "vehicle_tracker_location"
+GROUP BY "vehicle_tracker_id", "day"
+WITH NO DATA;
+
+SELECT add_continuous_aggregate_policy(
+    'vehicle_tracker_daily_summary',
+    start_offset => INTERVAL '3 days',
+    end_offset => INTERVAL '1 hour',
+    schedule_interval => INTERVAL '1 hour'
+);
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(String::from("cannot be reverted")))
+    }
+}
@@ -0,0 +1,61 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        let statement = r#"
+CREATE TYPE "position_filter_reason" AS ENUM (
+    'MAX_SPEED',
+    'PARKED_DRIFT',
+    'CLOCK_SKEW'
+);
+
+CREATE TABLE "position_filter_decision" (
+    "id" serial PRIMARY KEY,
+    "created_at" timestamptz(0) NOT NULL DEFAULT now(),
+    "time" timestamptz(0) NOT NULL,
+    "vehicle_tracker_id" int NOT NULL,
+    "reason" position_filter_reason NOT NULL,
+    "dropped" boolean NOT NULL,
+    "lat" double precision NOT NULL,
+    "lng" double precision NOT NULL,
+    "speed" double precision NULL,
+    "ignition" boolean NULL,
+    "measured" double precision NOT NULL,
+    "threshold" double precision NOT NULL
+);
+
+COMMENT ON
+COLUMN "position_filter_decision"."time" IS 'Time of the filtered position, as sent by the tracker';
+
+COMMENT ON
+COLUMN "position_filter_decision"."dropped" IS 'If the position was dropped, otherwise it was only flagged and stored as usual';
+
+COMMENT ON
+COLUMN "position_filter_decision"."measured" IS 'The value that failed the check, in km/h for MAX_SPEED, meters for PARKED_DRIFT and seconds for CLOCK_SKEW';
+
+COMMENT ON
+COLUMN "position_filter_decision"."threshold" IS 'The configured limit of the check when the position was filtered, in the unit of measured';
+
+CREATE INDEX idx_position_filter_decision_vehicle_tracker_id ON "position_filter_decision" ("vehicle_tracker_id", "id");
+
+ALTER TABLE "position_filter_decision"
+ADD CONSTRAINT "position_filter_decision_vehicle_tracker_id_foreign" FOREIGN KEY ("vehicle_tracker_id") REFERENCES "vehicle_tracker" ("id")
+ON UPDATE CASCADE
+ON DELETE CASCADE;
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(String::from("cannot be reverted")))
+    }
+}
@@ -0,0 +1,27 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // the fixed access levels are the root access levels created with every permission on
+        // signup, so they are granted the new permission, other access levels are managed by users
+        let statement = r#"
+UPDATE "access_level"
+SET "permissions" = array_append("permissions", 'SEND_TRACKER_COMMAND')
+WHERE "is_fixed" AND NOT ('SEND_TRACKER_COMMAND' = ANY("permissions"));
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(String::from("cannot be reverted")))
+    }
+}
@@ -0,0 +1,26 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // root access levels of existing organizations, new ones get every permission on signup
+        let statement = r#"
+UPDATE "access_level"
+SET "permissions" = array_append("permissions", 'ACKNOWLEDGE_TRACKER_ALARM')
+WHERE "is_fixed" AND NOT ('ACKNOWLEDGE_TRACKER_ALARM' = ANY("permissions"));
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(String::from("cannot be reverted")))
+    }
+}
@@ -0,0 +1,34 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // root access levels created before the geofence permissions existed lack them
+        let statement = r#"
+UPDATE "access_level"
+SET "permissions" = array_append("permissions", 'CREATE_GEOFENCE')
+WHERE "is_fixed" AND NOT ('CREATE_GEOFENCE' = ANY("permissions"));
+
+UPDATE "access_level"
+SET "permissions" = array_append("permissions", 'UPDATE_GEOFENCE')
+WHERE "is_fixed" AND NOT ('UPDATE_GEOFENCE' = ANY("permissions"));
+
+UPDATE "access_level"
+SET "permissions" = array_append("permissions", 'DELETE_GEOFENCE')
+WHERE "is_fixed" AND NOT ('DELETE_GEOFENCE' = ANY("permissions"));
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(String::from("cannot be reverted")))
+    }
+}
@@ -0,0 +1,116 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // the daily summaries of a vehicle were found by its current tracker, so they lost the
+        // days of its previous trackers and got the days a tracker spent on other vehicles, the
+        // vehicle of each location is stored instead, set on insert to the vehicle the tracker
+        // is installed on.
+        //
+        // IMPORTANT: the vehicle of the locations stored before this migration is not known,
+        // so they are assigned to the current vehicle of their tracker
+        let statement = r#"
+DROP MATERIALIZED VIEW "vehicle_tracker_daily_summary";
+
+ALTER TABLE "vehicle_tracker_location"
+ADD COLUMN "vehicle_id" int NULL;
+
+COMMENT ON
+COLUMN "vehicle_tracker_location"."vehicle_id" IS 'The vehicle the tracker was installed on when the location was sent';
+
+UPDATE "vehicle_tracker_location"
+SET "vehicle_id" = "vehicle_tracker"."vehicle_id"
+FROM "vehicle_tracker"
+WHERE "vehicle_tracker"."id" = "vehicle_tracker_location"."vehicle_tracker_id";
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        // same as the previous version, but setting the vehicle of new locations, which is also
+        // the vehicle whose odometer is increased
+        //
+        // IMPORTANT: location points are stored with the latitude as the X coordinate,
+        // so they are flipped before being cast to geography
+        let statement = r#"
+        CREATE OR REPLACE FUNCTION create_last_pos_trigger_fn() RETURNS TRIGGER LANGUAGE PLPGSQL AS
+              $BODY$
+                  DECLARE
+                      previous vehicle_tracker_last_location%ROWTYPE;
+                  BEGIN
+                      IF TG_OP = 'INSERT' THEN
+                          NEW.vehicle_id := (SELECT vehicle_id FROM vehicle_tracker WHERE id = NEW.vehicle_tracker_id);
+                      END IF;
+
+                      SELECT * INTO previous FROM vehicle_tracker_last_location WHERE vehicle_tracker_id = NEW.vehicle_tracker_id;
+
+                      IF FOUND AND NEW.time > previous.time THEN
+                          NEW.distance := ST_Distance(ST_FlipCoordinates(previous.point)::geography, ST_FlipCoordinates(NEW.point)::geography);
+
+                          NEW.ignition_seconds := CASE
+                              WHEN previous.ignition AND NEW.time - previous.time <= INTERVAL '1 hour'
+                              THEN EXTRACT(EPOCH FROM NEW.time - previous.time)::int
+                              ELSE 0
+                          END;
+
+                          UPDATE vehicle SET odometer = odometer + NEW.distance
+                          WHERE id = NEW.vehicle_id;
+                      END IF;
+
+                      INSERT INTO vehicle_tracker_last_location (vehicle_tracker_id, point, time, speed, heading, ignition, status)
+                      VALUES (NEW.vehicle_tracker_id, NEW.point, NEW.time, NEW.speed, NEW.heading, NEW.ignition, NEW.status)
+                      ON CONFLICT (vehicle_tracker_id) DO UPDATE SET 
+                      point=NEW.point,
+                      time=NEW.time,
+                      speed=NEW.speed,
+                      heading=NEW.heading,
+                      ignition=NEW.ignition,
+                      status=NEW.status
+                      WHERE vehicle_tracker_last_location.time < NEW.time;
+                      RETURN NEW;
+                  END
+              $BODY$;
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        // the rollups are still per tracker, but also per vehicle, so a day a tracker
+        // spent on two vehicles is split between them
+        let statement = r#"
+CREATE MATERIALIZED VIEW "vehicle_tracker_daily_summary"
+WITH (timescaledb.continuous, timescaledb.materialized_only = false) AS
+SELECT
+    "vehicle_id",
+    "vehicle_tracker_id",
+    time_bucket(INTERVAL '1 day', "time") AS "day",
+    coalesce(sum("distance"), 0) AS "distance",
+    max("speed") AS "max_speed",
+    avg("speed") AS "avg_speed",
+    coalesce(sum("ignition_seconds"), 0) AS "ignition_seconds",
+    count(*) AS "positions"
+FROM "vehicle_tracker_location"
+GROUP BY "vehicle_id", "vehicle_tracker_id", "day"
+WITH NO DATA;
+
+SELECT add_continuous_aggregate_policy(
+    'vehicle_tracker_daily_summary',
+    start_offset => INTERVAL '3 days',
+    end_offset => INTERVAL '1 hour',
+    schedule_interval => INTERVAL '1 hour'
+);
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(String::from("cannot be reverted")))
+    }
+}
@@ -0,0 +1,30 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        let db = manager.get_connection();
+
+        // positions can arrive out of order, so the time of the last position checked against
+        // each geofence is kept to ignore older positions, that would otherwise flip if the
+        // vehicle is inside the geofence and record spurious ENTER and EXIT events
+        let statement = r#"
+ALTER TABLE "vehicle_geofence"
+ADD COLUMN "checked_at" timestamptz NULL;
+
+COMMENT ON
+COLUMN "vehicle_geofence"."checked_at" IS 'Time of the last position checked against the geofence, null until the first position after the assignment';
+        "#;
+
+        db.execute_unprepared(statement).await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, _manager: &SchemaManager) -> Result<(), DbErr> {
+        Err(DbErr::Custom(String::from("cannot be reverted")))
+    }
+}
@@ -0,0 +1,128 @@
+use serde::Deserialize;
+
+fn def_debug() -> bool {
+    false
+}
+
+fn def_decoder_addr() -> String {
+    "127.0.0.1:3003".to_string()
+}
+
+fn def_trackers() -> usize {
+    1
+}
+
+fn def_imei_base() -> u64 {
+    860_000_000_000_000
+}
+
+fn def_interval_ms() -> u64 {
+    5000
+}
+
+fn def_jitter_ms() -> u64 {
+    1000
+}
+
+fn def_heartbeat_interval_secs() -> u64 {
+    60
+}
+
+fn def_reconnect_interval_secs() -> u64 {
+    5
+}
+
+fn def_speed_kmh() -> f64 {
+    40.0
+}
+
+fn def_start_lat() -> f64 {
+    -23.5505
+}
+
+fn def_start_lng() -> f64 {
+    -46.6333
+}
+
+#[derive(Deserialize, Debug)]
+pub struct AppConfig {
+    /// If the application should be run in debug mode and print every frame sent to stdout
+    #[serde(default = "def_debug")]
+    pub debug: bool,
+
+    /// Address of the decoder H02 (or multiplexed) TCP listener
+    #[serde(default = "def_decoder_addr")]
+    pub decoder_addr: String,
+
+    /// Comma separated IMEIs of the simulated trackers, if not set `TRACKERS`
+    /// trackers are simulated with sequential IMEIs starting at `IMEI_BASE`
+    pub imeis: Option<Vec<String>>,
+
+    /// Amount of trackers to simulate when `IMEIS` is not set
+    #[serde(default = "def_trackers")]
+    pub trackers: usize,
+
+    /// IMEI of the first simulated tracker when `IMEIS` is not set
+    #[serde(default = "def_imei_base")]
+    pub imei_base: u64,
+
+    /// Path of a GPX or GeoJSON file with the route to replay, if not
+    /// set each tracker follows a randomly generated route instead
+    pub route_file: Option<String>,
+
+    /// Interval between the locations sent by each tracker, in milliseconds
+    #[serde(default = "def_interval_ms")]
+    pub interval_ms: u64,
+
+    /// Maximum random delay added or subtracted from the interval, in milliseconds
+    #[serde(default = "def_jitter_ms")]
+    pub jitter_ms: u64,
+
+    /// Interval between the heartbeats sent by each tracker, in seconds
+    #[serde(default = "def_heartbeat_interval_secs")]
+    pub heartbeat_interval_secs: u64,
+
+    /// Seconds to wait before reconnecting a tracker once its connection is lost
+    #[serde(default = "def_reconnect_interval_secs")]
+    pub reconnect_interval_secs: u64,
+
+    /// Speed of the trackers on the random route, in km/h
+    #[serde(default = "def_speed_kmh")]
+    pub speed_kmh: f64,
+
+    /// Latitude where the random routes start
+    #[serde(default = "def_start_lat")]
+    pub start_lat: f64,
+
+    /// Longitude where the random routes start
+    #[serde(default = "def_start_lng")]
+    pub start_lng: f64,
+}
+
+impl AppConfig {
+    pub fn from_env() -> Result<AppConfig, envy::Error> {
+        let config = envy::from_env::<AppConfig>()?;
+
+        if config.interval_ms == 0 {
+            return Err(envy::Error::Custom(
+                "INTERVAL_MS must be greater than zero".to_string(),
+            ));
+        }
+
+        if config.debug {
+            println!("[CFG] {:?}", config);
+        }
+
+        Ok(config)
+    }
+
+    /// The IMEIs of the trackers to simulate
+    pub fn imeis(&self) -> Vec<String> {
+        match &self.imeis {
+            Some(imeis) => imeis.clone(),
+            None => (0..self.trackers as u64)
+                .map(|i| format!("{:015}", self.imei_base + i))
+                .collect(),
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! Encoding of the H02 frames sent by the simulated trackers, see the
+//! decoder H02 docs for the meaning of each field.
+
+use chrono::{DateTime, Utc};
+
+/// Status of a moving vehicle with no alarms, the status bits use negative
+/// logic so every bit is set except for the ACC (18) and engine (21) bits
+const STATUS_MOVING: u32 = !(1 << (31 - 18) | 1 << (31 - 21));
+
+/// Status of a parked vehicle with no alarms
+const STATUS_PARKED: u32 = u32::MAX;
+
+/// A position to be sent on a `V1` frame
+pub struct Position {
+    /// latitude in decimal degrees
+    pub lat: f64,
+
+    /// longitude in decimal degrees
+    pub lng: f64,
+
+    /// speed in km/h
+    pub speed: f64,
+
+    /// direction in degrees (0 degrees = north, 180 = s)
+    pub direction: f64,
+
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Formats a coordinate in the `(d)ddmm.mmmm` format used by H02, where
+/// `degree_digits` is 2 for latitudes and 3 for longitudes
+fn format_coord(value: f64, degree_digits: usize) -> String {
+    // rounded to the 4 minute decimals before splitting, so minutes
+    // such as 59.99999 are not formatted as 60.0000
+    let ten_thousandth_minutes = (value.abs() * 600_000.0).round() as u64;
+
+    let degrees = ten_thousandth_minutes / 600_000;
+    let minutes = (ten_thousandth_minutes % 600_000) as f64 / 10_000.0;
+
+    format!("{degrees:0degree_digits$}{minutes:07.4}")
+}
+
+/// Encodes a `V1` location message
+pub fn location(imei: &str, position: &Position) -> String {
+    let lat_symbol = if position.lat < 0.0 { "S" } else { "N" };
+    let lng_symbol = if position.lng < 0.0 { "W" } else { "E" };
+
+    let status = if position.speed > 0.0 {
+        STATUS_MOVING
+    } else {
+        STATUS_PARKED
+    };
+
+    format!(
+        "*HQ,{},V1,{},A,{},{},{},{},{:.2},{},{},{:08X}#",
+        imei,
+        position.timestamp.format("%H%M%S"),
+        format_coord(position.lat, 2),
+        lat_symbol,
+        format_coord(position.lng, 3),
+        lng_symbol,
+        // H02 speeds are in knots
+        position.speed / 1.852,
+        position.direction.round() as i32 % 360,
+        position.timestamp.format("%d%m%y"),
+        status,
+    )
+}
+
+/// Encodes a `HTBT` heartbeat message
+pub fn heartbeat(imei: &str) -> String {
+    format!("*HQ,{imei},HTBT#")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_coord, heartbeat, location, Position};
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn formats_coordinates() {
+        assert_eq!(format_coord(22.5, 2), "2230.0000");
+        assert_eq!(format_coord(-23.5505, 2), "2333.0300");
+        assert_eq!(format_coord(-46.6333, 3), "04637.9980");
+        assert_eq!(format_coord(9.999999999, 3), "01000.0000");
+    }
+
+    #[test]
+    fn encodes_location() {
+        let position = Position {
+            lat: -23.5505,
+            lng: -46.6333,
+            speed: 18.52,
+            direction: 359.6,
+            timestamp: Utc.with_ymd_and_hms(2024, 2, 15, 8, 5, 9).unwrap(),
+        };
+
+        assert_eq!(
+            location("867232051148352", &position),
+            "*HQ,867232051148352,V1,080509,A,2333.0300,S,04637.9980,W,10.00,0,150224,FFFFDBFF#"
+        );
+    }
+
+    #[test]
+    fn encodes_heartbeat() {
+        assert_eq!(heartbeat("867232051148352"), "*HQ,867232051148352,HTBT#");
+    }
+}
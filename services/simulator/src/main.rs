@@ -0,0 +1,67 @@
+use config::AppConfig;
+use route::{Point, Route};
+use std::{sync::Arc, time::Duration};
+use tracker::{Settings, Tracker};
+
+mod config;
+mod h02;
+mod route;
+mod tracker;
+
+#[tokio::main]
+async fn main() {
+    let config = AppConfig::from_env().expect("failed to load application config");
+
+    let route_points = config.route_file.as_ref().map(|path| {
+        let points = route::load(path).expect("failed to load route file");
+        println!("[SIM] loaded route with {} points from {}", points.len(), path);
+
+        Arc::new(points)
+    });
+
+    let settings = Settings {
+        decoder_addr: config.decoder_addr.clone(),
+        interval: Duration::from_millis(config.interval_ms),
+        jitter: Duration::from_millis(config.jitter_ms),
+        heartbeat_interval: Duration::from_secs(config.heartbeat_interval_secs),
+        reconnect_interval: Duration::from_secs(config.reconnect_interval_secs),
+        debug: config.debug,
+    };
+
+    let imeis = config.imeis();
+
+    println!(
+        "[SIM] simulating {} trackers sending to {}",
+        imeis.len(),
+        config.decoder_addr
+    );
+
+    let start = Point {
+        lat: config.start_lat,
+        lng: config.start_lng,
+    };
+
+    let handles: Vec<_> = imeis
+        .iter()
+        .enumerate()
+        .map(|(i, imei)| {
+            let route = match &route_points {
+                // spread the trackers along the route
+                Some(points) => Route::replay(points.clone(), i * points.len() / imeis.len()),
+                None => Route::random_walk(start, config.speed_kmh),
+            };
+
+            let tracker = Tracker {
+                imei: imei.clone(),
+                route,
+                settings: settings.clone(),
+            };
+
+            tokio::spawn(tracker.run())
+        })
+        .collect();
+
+    for handle in handles {
+        handle.await.unwrap();
+    }
+}
@@ -0,0 +1,339 @@
+//! Routes followed by the simulated trackers, either a route loaded
+//! from a GPX/GeoJSON file or a randomly generated one.
+
+use rand::Rng;
+use serde_json::Value;
+use std::{path::Path, sync::Arc, time::Duration};
+
+/// mean radius of the earth in meters
+const EARTH_RADIUS: f64 = 6_371_000.0;
+
+/// maximum change of direction between two points of a random route, in degrees
+const MAX_TURN: f64 = 30.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub lat: f64,
+    pub lng: f64,
+}
+
+/// The point a tracker moved to and how it moved from the previous point
+pub struct Step {
+    pub point: Point,
+
+    /// speed in km/h
+    pub speed: f64,
+
+    /// direction in degrees (0 degrees = north, 180 = s)
+    pub direction: f64,
+}
+
+/// Loads the points of a route from a GPX file (track and route points) or a GeoJSON
+/// file (coordinates of any geometry), the format is chosen by the file extension
+pub fn load(path: &str) -> Result<Vec<Point>, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+
+    let extension = Path::new(path)
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_lowercase();
+
+    let points = match extension.as_str() {
+        "gpx" => parse_gpx(&content)?,
+        "geojson" | "json" => parse_geojson(&content)?,
+        _ => return Err(format!("unsupported route file extension: {extension}")),
+    };
+
+    if points.is_empty() {
+        return Err("route file does not contain any point".to_string());
+    }
+
+    Ok(points)
+}
+
+/// Value of a attribute of a XML tag, eg: `lat="1.5"`
+fn xml_attr<'a>(tag: &'a str, name: &str) -> Option<&'a str> {
+    let start = tag.find(&format!(" {name}="))? + name.len() + 2;
+    let quote = tag[start..].chars().next()?;
+    let value = &tag[start + 1..];
+
+    value.find(quote).map(|end| &value[..end])
+}
+
+/// Parses the `trkpt` and `rtept` points of a GPX file, in the order they appear
+fn parse_gpx(content: &str) -> Result<Vec<Point>, String> {
+    let mut points = Vec::new();
+
+    for (start, _) in content.match_indices('<') {
+        let tag = &content[start..];
+
+        if !tag.starts_with("<trkpt") && !tag.starts_with("<rtept") {
+            continue;
+        }
+
+        let tag = &tag[..tag.find('>').ok_or("unclosed GPX point tag")?];
+
+        let coord = |name: &str| -> Result<f64, String> {
+            xml_attr(tag, name)
+                .and_then(|value| value.parse::<f64>().ok())
+                .ok_or(format!("GPX point without a valid {name}"))
+        };
+
+        points.push(Point {
+            lat: coord("lat")?,
+            lng: coord("lon")?,
+        });
+    }
+
+    Ok(points)
+}
+
+/// Collects every position of a GeoJSON value, positions are `[lng, lat]` arrays
+/// found on the `coordinates` of geometries, regardless of the geometry type
+fn collect_geojson_points(value: &Value, points: &mut Vec<Point>) {
+    match value {
+        Value::Array(items) => match items.as_slice() {
+            [Value::Number(lng), Value::Number(lat), ..] => {
+                if let (Some(lng), Some(lat)) = (lng.as_f64(), lat.as_f64()) {
+                    points.push(Point { lat, lng });
+                }
+            }
+            _ => items
+                .iter()
+                .for_each(|item| collect_geojson_points(item, points)),
+        },
+        Value::Object(object) => {
+            let children = ["coordinates", "geometry", "geometries", "features"];
+
+            children
+                .iter()
+                .filter_map(|key| object.get(*key))
+                .for_each(|child| collect_geojson_points(child, points));
+        }
+        _ => {}
+    }
+}
+
+fn parse_geojson(content: &str) -> Result<Vec<Point>, String> {
+    let value: Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+
+    let mut points = Vec::new();
+    collect_geojson_points(&value, &mut points);
+
+    Ok(points)
+}
+
+/// distance between two points in meters, using the haversine formula
+fn distance(a: Point, b: Point) -> f64 {
+    let d_lat = (b.lat - a.lat).to_radians();
+    let d_lng = (b.lng - a.lng).to_radians();
+
+    let h = (d_lat / 2.0).sin().powi(2)
+        + a.lat.to_radians().cos() * b.lat.to_radians().cos() * (d_lng / 2.0).sin().powi(2);
+
+    2.0 * EARTH_RADIUS * h.sqrt().asin()
+}
+
+/// initial bearing from `a` to `b` in degrees, from 0 to 360
+fn bearing(a: Point, b: Point) -> f64 {
+    let (lat_a, lat_b) = (a.lat.to_radians(), b.lat.to_radians());
+    let d_lng = (b.lng - a.lng).to_radians();
+
+    let y = d_lng.sin() * lat_b.cos();
+    let x = lat_a.cos() * lat_b.sin() - lat_a.sin() * lat_b.cos() * d_lng.cos();
+
+    y.atan2(x).to_degrees().rem_euclid(360.0)
+}
+
+/// the point reached moving `meters` from `from` in the `direction` in degrees
+fn destination(from: Point, direction: f64, meters: f64) -> Point {
+    let angular_distance = meters / EARTH_RADIUS;
+    let direction = direction.to_radians();
+    let lat = from.lat.to_radians();
+
+    let dest_lat = (lat.sin() * angular_distance.cos()
+        + lat.cos() * angular_distance.sin() * direction.cos())
+    .asin();
+
+    let dest_lng = from.lng.to_radians()
+        + (direction.sin() * angular_distance.sin() * lat.cos())
+            .atan2(angular_distance.cos() - lat.sin() * dest_lat.sin());
+
+    Point {
+        lat: dest_lat.to_degrees(),
+        lng: (dest_lng.to_degrees() + 540.0).rem_euclid(360.0) - 180.0,
+    }
+}
+
+/// The route of a single tracker
+pub enum Route {
+    /// replays the points of a route, one point per location sent,
+    /// starting over once the last point is reached
+    Replay {
+        points: Arc<Vec<Point>>,
+        index: usize,
+    },
+
+    /// moves at a constant speed turning randomly at every location sent
+    RandomWalk {
+        point: Point,
+        direction: f64,
+        speed: f64,
+    },
+}
+
+impl Route {
+    /// A replay of `points` starting at `index`, so trackers replaying
+    /// the same route are not all on the same point
+    pub fn replay(points: Arc<Vec<Point>>, index: usize) -> Route {
+        Route::Replay {
+            index: index % points.len(),
+            points,
+        }
+    }
+
+    pub fn random_walk(start: Point, speed: f64) -> Route {
+        Route::RandomWalk {
+            point: start,
+            direction: rand::thread_rng().gen_range(0.0..360.0),
+            speed,
+        }
+    }
+
+    /// The current point of the tracker, before moving
+    pub fn current(&self) -> Point {
+        match self {
+            Route::Replay { points, index } => points[*index],
+            Route::RandomWalk { point, .. } => *point,
+        }
+    }
+
+    /// Moves the tracker to its next point, `elapsed` being the time since the last
+    /// move, used to calculate the speed on replays and the distance on random walks
+    pub fn advance(&mut self, elapsed: Duration) -> Step {
+        let hours = elapsed.as_secs_f64() / 3600.0;
+
+        match self {
+            Route::Replay { points, index } => {
+                let from = points[*index];
+
+                *index = (*index + 1) % points.len();
+                let to = points[*index];
+
+                let meters = distance(from, to);
+
+                Step {
+                    point: to,
+                    speed: match hours > 0.0 {
+                        true => meters / 1000.0 / hours,
+                        false => 0.0,
+                    },
+                    direction: match meters > 0.0 {
+                        true => bearing(from, to),
+                        false => 0.0,
+                    },
+                }
+            }
+            Route::RandomWalk {
+                point,
+                direction,
+                speed,
+            } => {
+                let turn = rand::thread_rng().gen_range(-MAX_TURN..=MAX_TURN);
+
+                *direction = (*direction + turn).rem_euclid(360.0);
+                *point = destination(*point, *direction, *speed * 1000.0 * hours);
+
+                Step {
+                    point: *point,
+                    speed: *speed,
+                    direction: *direction,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bearing, destination, distance, parse_geojson, parse_gpx, Point, Route};
+    use std::{sync::Arc, time::Duration};
+
+    #[test]
+    fn parses_gpx() {
+        let gpx = r#"<?xml version="1.0"?>
+            <gpx version="1.1">
+              <wpt lat="1.0" lon="1.0"><name>ignored</name></wpt>
+              <trk><trkseg>
+                <trkpt lat="-23.5505" lon="-46.6333"><ele>760</ele></trkpt>
+                <trkpt lon='-46.6340' lat='-23.5510'/>
+              </trkseg></trk>
+            </gpx>"#;
+
+        assert_eq!(
+            parse_gpx(gpx).unwrap(),
+            vec![
+                Point {
+                    lat: -23.5505,
+                    lng: -46.6333
+                },
+                Point {
+                    lat: -23.5510,
+                    lng: -46.6340
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_geojson() {
+        let geojson = r#"{
+            "type": "FeatureCollection",
+            "features": [{
+                "type": "Feature",
+                "properties": { "name": "route" },
+                "geometry": {
+                    "type": "LineString",
+                    "coordinates": [[-46.6333, -23.5505], [-46.6340, -23.5510, 760]]
+                }
+            }]
+        }"#;
+
+        let points = parse_geojson(geojson).unwrap();
+
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[1].lat, -23.5510);
+        assert_eq!(points[1].lng, -46.6340);
+    }
+
+    #[test]
+    fn moves_to_destination() {
+        let from = Point {
+            lat: -23.5505,
+            lng: -46.6333,
+        };
+
+        let to = destination(from, 90.0, 1000.0);
+
+        assert!((distance(from, to) - 1000.0).abs() < 0.01);
+        assert!((bearing(from, to) - 90.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn replays_route_in_a_loop() {
+        let points = Arc::new(vec![
+            Point { lat: 0.0, lng: 0.0 },
+            Point { lat: 0.0, lng: 0.01 },
+        ]);
+
+        let mut route = Route::replay(points.clone(), 1);
+
+        let step = route.advance(Duration::from_secs(60));
+
+        assert_eq!(step.point, points[0]);
+        assert!((step.speed - 66.7).abs() < 0.1);
+        assert!((step.direction - 270.0).abs() < 0.01);
+        assert_eq!(route.current(), points[0]);
+    }
+}
@@ -0,0 +1,133 @@
+use crate::h02::{self, Position};
+use crate::route::Route;
+use chrono::Utc;
+use rand::Rng;
+use std::time::{Duration, Instant};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+/// Settings shared by every simulated tracker
+#[derive(Clone)]
+pub struct Settings {
+    pub decoder_addr: String,
+    pub interval: Duration,
+    pub jitter: Duration,
+    pub heartbeat_interval: Duration,
+    pub reconnect_interval: Duration,
+    pub debug: bool,
+}
+
+impl Settings {
+    /// the interval until the next location, with a random jitter
+    fn next_interval(&self) -> Duration {
+        let jitter = self.jitter.as_millis() as i64;
+        let offset = rand::thread_rng().gen_range(-jitter..=jitter);
+
+        Duration::from_millis((self.interval.as_millis() as i64 + offset).max(0) as u64)
+    }
+}
+
+/// A simulated H02 tracker, sending its locations and heartbeats to the
+/// decoder over TCP and reconnecting whenever the connection is lost
+pub struct Tracker {
+    pub imei: String,
+    pub route: Route,
+    pub settings: Settings,
+}
+
+impl Tracker {
+    pub async fn run(mut self) {
+        // spreads the first connection of each tracker over the interval,
+        // so the decoder does not receive every location at the same time
+        let delay = rand::thread_rng().gen_range(Duration::ZERO..=self.settings.interval);
+        tokio::time::sleep(delay).await;
+
+        loop {
+            match TcpStream::connect(&self.settings.decoder_addr).await {
+                Ok(stream) => {
+                    if self.settings.debug {
+                        println!("[{}] connected", self.imei);
+                    }
+
+                    let err = self.send_frames(stream).await;
+                    println!("[{}] connection lost: {}", self.imei, err);
+                }
+                Err(err) => println!("[{}] failed to connect: {}", self.imei, err),
+            }
+
+            tokio::time::sleep(self.settings.reconnect_interval).await;
+        }
+    }
+
+    /// Sends frames until the connection fails, returning the error
+    async fn send_frames(&mut self, stream: TcpStream) -> String {
+        let (mut reader, mut writer) = stream.into_split();
+
+        let mut last_heartbeat = Instant::now();
+        let mut last_location = Instant::now();
+
+        // replies and commands sent by the decoder are read and discarded,
+        // as the tracker may stop receiving data once its buffer is full
+        let mut buffer = [0; 1024];
+
+        // the first location is the start of the route, as the tracker did not move yet
+        let mut frame = h02::location(
+            &self.imei,
+            &Position {
+                lat: self.route.current().lat,
+                lng: self.route.current().lng,
+                speed: 0.0,
+                direction: 0.0,
+                timestamp: Utc::now(),
+            },
+        );
+
+        loop {
+            if self.settings.debug {
+                println!("[{}] sending: {}", self.imei, frame);
+            }
+
+            if let Err(err) = writer.write_all(frame.as_bytes()).await {
+                return err.to_string();
+            }
+
+            let sleep = tokio::time::sleep(self.settings.next_interval());
+            tokio::pin!(sleep);
+
+            loop {
+                tokio::select! {
+                    _ = &mut sleep => break,
+                    read = reader.read(&mut buffer) => match read {
+                        Ok(0) => return "closed by the decoder".to_string(),
+                        Ok(_) => {}
+                        Err(err) => return err.to_string(),
+                    }
+                }
+            }
+
+            frame = match last_heartbeat.elapsed() >= self.settings.heartbeat_interval {
+                true => {
+                    last_heartbeat = Instant::now();
+                    h02::heartbeat(&self.imei)
+                }
+                false => {
+                    let step = self.route.advance(last_location.elapsed());
+                    last_location = Instant::now();
+
+                    h02::location(
+                        &self.imei,
+                        &Position {
+                            lat: step.point.lat,
+                            lng: step.point.lng,
+                            speed: step.speed,
+                            direction: step.direction,
+                            timestamp: Utc::now(),
+                        },
+                    )
+                }
+            };
+        }
+    }
+}
@@ -20,6 +20,8 @@ pub enum Permission {
     CreateTracker,
     UpdateTracker,
     DeleteTracker,
+    SendTrackerCommand,
+    AcknowledgeTrackerAlarm,
 
     CreateVehicle,
     UpdateVehicle,
@@ -30,6 +32,10 @@ pub enum Permission {
     CreateSimCard,
 
     UpdateOrganization,
+
+    CreateGeofence,
+    UpdateGeofence,
+    DeleteGeofence,
 }
 
 impl Permission {
@@ -44,6 +50,9 @@ impl Permission {
 pub struct TrackerModelInfo {
     /// amount of sim cards that can be installed on a tracker
     pub sim_card_slots: u8,
+
+    /// slug of the protocol used by the tracker on the decoder service
+    pub protocol: &'static str,
 }
 
 /// All the tracker models that are supported by rastercar
@@ -65,6 +74,15 @@ pub struct TrackerModelInfo {
 pub enum TrackerModel {
     #[sea_orm(string_value = "H02")]
     H02,
+
+    #[sea_orm(string_value = "GT06")]
+    GT06,
+
+    /// Teltonika FMB devices, using the Codec 8 and Codec 8 Extended protocols
+    #[strum(serialize = "TELTONIKA")]
+    #[serde(rename = "TELTONIKA")]
+    #[sea_orm(string_value = "TELTONIKA")]
+    Teltonika,
 }
 
 impl TrackerModel {
@@ -76,7 +94,18 @@ impl TrackerModel {
 
     pub const fn get_info(self) -> TrackerModelInfo {
         match self {
-            Self::H02 => TrackerModelInfo { sim_card_slots: 1 },
+            Self::H02 => TrackerModelInfo {
+                sim_card_slots: 1,
+                protocol: "h02",
+            },
+            Self::GT06 => TrackerModelInfo {
+                sim_card_slots: 1,
+                protocol: "gt06",
+            },
+            Self::Teltonika => TrackerModelInfo {
+                sim_card_slots: 1,
+                protocol: "teltonika",
+            },
         }
     }
 }
@@ -87,7 +116,236 @@ impl FromStr for TrackerModel {
     fn from_str(input: &str) -> Result<TrackerModel, Self::Err> {
         match input {
             "H02" => Ok(TrackerModel::H02),
+            "GT06" => Ok(TrackerModel::GT06),
+            "TELTONIKA" => Ok(TrackerModel::Teltonika),
             _ => Err(()),
         }
     }
 }
+
+/// Status of a command sent to a tracker
+///
+/// also the native ENUM for the rastercar postgres database
+#[derive(
+    Eq,
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumIter,
+    ToSchema,
+    Serialize,
+    PartialEq,
+    Deserialize,
+    DeriveActiveEnum,
+)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "tracker_command_status"
+)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TrackerCommandStatus {
+    /// waiting to be sent to the tracker by the decoder service
+    #[sea_orm(string_value = "PENDING")]
+    Pending,
+
+    /// written to the tracker connection, waiting for its acknowledgement
+    #[sea_orm(string_value = "SENT")]
+    Sent,
+
+    /// confirmed by the tracker
+    #[sea_orm(string_value = "ACKNOWLEDGED")]
+    Acknowledged,
+
+    /// could not be sent to the tracker, eg: the tracker was not connected
+    #[sea_orm(string_value = "FAILED")]
+    Failed,
+
+    /// not acknowledged by the tracker in time
+    #[sea_orm(string_value = "EXPIRED")]
+    Expired,
+}
+
+/// Seconds without receiving any event of a tracker to consider it offline
+pub const TRACKER_OFFLINE_AFTER_SECS: i64 = 10 * 60;
+
+/// Connectivity status of a tracker, based on when the tracker was last seen
+/// and on the connection events of the decoder service
+#[derive(
+    Eq, Copy, Clone, Debug, Display, EnumIter, ToSchema, Serialize, PartialEq, Deserialize,
+)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TrackerConnectivity {
+    /// the tracker was seen recently and its connection was not closed
+    Online,
+
+    /// the tracker was not seen recently or its connection was closed
+    Offline,
+
+    /// no event of the tracker was ever received
+    NeverSeen,
+}
+
+/// Alarm raised by a tracker, such as a SOS button press
+///
+/// also the native ENUM for the rastercar postgres database
+#[derive(
+    Eq,
+    Hash,
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumIter,
+    ToSchema,
+    Serialize,
+    PartialEq,
+    Deserialize,
+    DeriveActiveEnum,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "tracker_alarm_type")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum TrackerAlarmType {
+    #[sea_orm(string_value = "SOS")]
+    Sos,
+
+    #[sea_orm(string_value = "THEFT")]
+    Theft,
+
+    #[sea_orm(string_value = "ROBBERY")]
+    Robbery,
+
+    #[sea_orm(string_value = "OVERSPEED")]
+    Overspeed,
+
+    /// the vehicle ignition was turned on while it was fortified
+    #[sea_orm(string_value = "ILLEGAL_IGNITION")]
+    IllegalIgnition,
+
+    /// the tracker battery was removed or its main power was cut
+    #[sea_orm(string_value = "STORAGE_BATTERY_REMOVED")]
+    StorageBatteryRemoved,
+
+    #[sea_orm(string_value = "TEMPERATURE")]
+    Temperature,
+
+    #[sea_orm(string_value = "GPS_RECEIVER_FAULT")]
+    GpsReceiverFault,
+
+    #[sea_orm(string_value = "GPS_ANTENNA_OPEN_CIRCUIT")]
+    GpsAntennaOpenCircuit,
+
+    #[sea_orm(string_value = "GPS_ANTENNA_SHORT_CIRCUIT")]
+    GpsAntennaShortCircuit,
+
+    /// the vehicle entered a area configured on the tracker itself
+    #[sea_orm(string_value = "CROSS_BORDER_IN")]
+    CrossBorderIn,
+
+    /// the vehicle left a area configured on the tracker itself
+    #[sea_orm(string_value = "CROSS_BORDER_OUT")]
+    CrossBorderOut,
+
+    #[sea_orm(string_value = "CUSTOM")]
+    Custom,
+}
+
+/// Shape of a geofence
+///
+/// also the native ENUM for the rastercar postgres database
+#[derive(
+    Eq,
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumIter,
+    ToSchema,
+    Serialize,
+    PartialEq,
+    Deserialize,
+    DeriveActiveEnum,
+)]
+#[sea_orm(rs_type = "String", db_type = "Enum", enum_name = "geofence_shape")]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GeofenceShape {
+    #[sea_orm(string_value = "POLYGON")]
+    Polygon,
+
+    /// a radius in meters around a point
+    #[sea_orm(string_value = "CIRCLE")]
+    Circle,
+}
+
+/// A vehicle crossing the border of a geofence
+///
+/// also the native ENUM for the rastercar postgres database
+#[derive(
+    Eq,
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumIter,
+    ToSchema,
+    Serialize,
+    PartialEq,
+    Deserialize,
+    DeriveActiveEnum,
+)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "geofence_event_type"
+)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum GeofenceEventType {
+    #[sea_orm(string_value = "ENTER")]
+    Enter,
+
+    #[sea_orm(string_value = "EXIT")]
+    Exit,
+}
+
+/// A check of the position quality filter a tracker position failed
+///
+/// also the native ENUM for the rastercar postgres database
+#[derive(
+    Eq,
+    Copy,
+    Clone,
+    Debug,
+    Display,
+    EnumIter,
+    ToSchema,
+    Serialize,
+    PartialEq,
+    Deserialize,
+    DeriveActiveEnum,
+)]
+#[sea_orm(
+    rs_type = "String",
+    db_type = "Enum",
+    enum_name = "position_filter_reason"
+)]
+#[strum(serialize_all = "SCREAMING_SNAKE_CASE")]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum PositionFilterReason {
+    /// the speed needed to reach the position from the previous one is not plausible
+    #[sea_orm(string_value = "MAX_SPEED")]
+    MaxSpeed,
+
+    /// the position barely moved from the previous one while the ignition is off
+    #[sea_orm(string_value = "PARKED_DRIFT")]
+    ParkedDrift,
+
+    /// the position timestamp is too far in the future
+    #[sea_orm(string_value = "CLOCK_SKEW")]
+    ClockSkew,
+}
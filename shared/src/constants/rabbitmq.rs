@@ -12,3 +12,6 @@ pub static TRACKER_EVENTS_EXCHANGE: &str = "tracker_events";
 
 /// RPC operation to send a email
 pub static OP_SEND_EMAIL: &str = "sendEmail";
+
+/// RabbitMQ queue to publish commands to be sent to trackers by the decoder service
+pub static TRACKER_COMMANDS_QUEUE: &str = "tracker_commands";
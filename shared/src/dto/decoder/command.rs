@@ -0,0 +1,59 @@
+//! Commands sent to trackers through the decoder service and the events
+//! published by the decoder about them, commands are protocol agnostic and
+//! encoded by the decoder according to the protocol of the tracker.
+
+use serde::{Deserialize, Serialize};
+
+/// A command to send to a tracker, published on the tracker commands queue
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct TrackerCommand {
+    /// identifier of the command, sent back on the command events so
+    /// the publisher can track the command status
+    pub id: i32,
+
+    /// imei of the tracker to send the command to
+    pub imei: String,
+
+    /// the tracker protocol slug, eg: `h02`, `gt06`, `teltonika`
+    pub protocol: String,
+
+    pub command: Command,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Command {
+    /// cuts off the vehicle oil and engine
+    EngineStop,
+
+    /// recovers the vehicle oil and engine
+    EngineResume,
+
+    /// sets the interval the tracker sends its position
+    PositionInterval { seconds: u32 },
+
+    /// reboots the tracker
+    Reboot,
+
+    /// a protocol specific command, sent as is inside the protocol
+    /// packet, eg: `S20,1,1` for H02 or `setdigout 1` for Teltonika
+    Custom { content: String },
+}
+
+/// Sent once the command is written to the tracker connection, this does not mean the
+/// tracker executed the command, which is confirmed by the `command_ack` event
+#[derive(Serialize, Deserialize)]
+pub struct CommandSentMsg {
+    /// identifier of the command
+    pub id: i32,
+}
+
+/// Sent when the command could not be sent to the tracker
+#[derive(Serialize, Deserialize)]
+pub struct CommandFailedMsg {
+    /// identifier of the command
+    pub id: i32,
+
+    /// why the command could not be sent, eg: the tracker is not connected
+    pub reason: String,
+}
@@ -0,0 +1,126 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize)]
+pub struct LocationMsg {
+    /// latitude (90 to -90) in decimal degrees
+    pub lat: f64,
+
+    /// longitude (180 to -180) in decimal degrees
+    pub lng: f64,
+
+    /// speed in km/h
+    pub speed: f64,
+
+    /// direction in degrees (0 degrees = north, 180 = s)
+    pub direction: i32,
+
+    /// amount of satellites in use
+    pub satellites: u8,
+
+    /// if the tracker had a GPS fix, if false the coordinates
+    /// are the last known position and should not be trusted
+    pub positioned: bool,
+
+    /// the cell tower the tracker is connected to, not sent by all models
+    pub cell_tower: Option<CellTower>,
+
+    /// if the ACC (ignition) is on, only sent by newer models
+    pub acc: Option<bool>,
+
+    /// vehicle date and time sent by the tracker
+    pub timestamp: DateTime<Utc>,
+}
+
+/// a cell tower (base station) the tracker is connected to
+#[derive(Serialize, Deserialize)]
+pub struct CellTower {
+    /// mobile country code
+    pub mcc: i32,
+
+    /// mobile network code
+    pub mnc: i32,
+
+    /// location area code
+    pub lac: i32,
+
+    /// cell id
+    pub cell_id: i32,
+}
+
+/// Sent by the tracker once it connects, identifying itself by its IMEI
+#[derive(Serialize, Deserialize)]
+pub struct LoginMsg {
+    /// 15 digit imei number
+    pub imei: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Alarm {
+    Sos,
+    PowerCut,
+    Shock,
+    FenceIn,
+    FenceOut,
+    LowBattery,
+
+    /// alarm code not documented on the GT06 protocol
+    Other(u8),
+}
+
+/// Info about the tracker state, sent on status and alarm packets
+#[derive(Serialize, Deserialize)]
+pub struct TerminalInfo {
+    /// if the oil and electricity of the vehicle is disconnected
+    pub oil_and_electricity_disconnected: bool,
+
+    /// if GPS tracking is on
+    pub gps_tracking: bool,
+
+    /// the alarm being raised, if any
+    pub alarm: Option<Alarm>,
+
+    /// if the tracker is charging
+    pub charging: bool,
+
+    /// if the ACC (ignition) is on
+    pub acc: bool,
+
+    /// if the tracker defense (fortification) is activated
+    pub armed: bool,
+}
+
+/// Status info, sent periodically by the tracker as a heartbeat
+#[derive(Serialize, Deserialize)]
+pub struct StatusMsg {
+    pub terminal_info: TerminalInfo,
+
+    /// battery voltage level from 0 (no power) to 6 (very high)
+    pub voltage_level: u8,
+
+    /// GSM signal strength from 0 (no signal) to 4 (very strong)
+    pub gsm_signal: u8,
+
+    /// the alarm that triggered the packet, if any
+    pub alarm: Option<Alarm>,
+}
+
+/// An alarm raised by the tracker, with its location when the alarm happened,
+/// the raised alarm is on the `status`
+#[derive(Serialize, Deserialize)]
+pub struct AlarmMsg {
+    pub location: LocationMsg,
+
+    pub status: StatusMsg,
+}
+
+/// Response of the tracker to a online command (`0x15` packet)
+#[derive(Serialize, Deserialize)]
+pub struct CommandAckMsg {
+    /// the server flag sent with the command, the command id
+    pub server_flag: u32,
+
+    /// the tracker response, eg: `DYD=Success!`
+    pub content: String,
+}
@@ -1,3 +1,4 @@
+use crate::constants::TrackerAlarmType;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
@@ -55,3 +56,143 @@ pub struct Status {
     pub gps_antenna_short_circuit_alarm: bool,
     pub no_entry_cross_border_alarm_out: bool,
 }
+
+impl Status {
+    /// if any of the alarm flags is active
+    pub fn has_alarm(&self) -> bool {
+        self.temperature_alarm
+            || self.three_times_pass_error_alarm
+            || self.gprs_occlusion_alarm
+            || self.gps_receiver_fault_alarm
+            || self.analog_quantity_transfinit_alarm
+            || self.sos_alarm
+            || self.custom_alarm
+            || self.theft_alarm
+            || self.roberry_alarm
+            || self.overspeed_alarm
+            || self.illegal_ignition_alarm
+            || self.no_entry_cross_border_alarm_in
+            || self.gps_antenna_open_circuit_alarm
+            || self.gps_antenna_short_circuit_alarm
+            || self.no_entry_cross_border_alarm_out
+    }
+
+    /// the alarms active on the status, the storage battery removal is not a alarm
+    /// flag on the protocol but it usually means the tracker is being tampered with
+    pub fn alarms(&self) -> Vec<TrackerAlarmType> {
+        [
+            (self.sos_alarm, TrackerAlarmType::Sos),
+            (self.theft_alarm, TrackerAlarmType::Theft),
+            (self.roberry_alarm, TrackerAlarmType::Robbery),
+            (self.overspeed_alarm, TrackerAlarmType::Overspeed),
+            (
+                self.illegal_ignition_alarm,
+                TrackerAlarmType::IllegalIgnition,
+            ),
+            (
+                self.storage_battery_removed,
+                TrackerAlarmType::StorageBatteryRemoved,
+            ),
+            (self.temperature_alarm, TrackerAlarmType::Temperature),
+            (
+                self.gps_receiver_fault_alarm,
+                TrackerAlarmType::GpsReceiverFault,
+            ),
+            (
+                self.gps_antenna_open_circuit_alarm,
+                TrackerAlarmType::GpsAntennaOpenCircuit,
+            ),
+            (
+                self.gps_antenna_short_circuit_alarm,
+                TrackerAlarmType::GpsAntennaShortCircuit,
+            ),
+            (
+                self.no_entry_cross_border_alarm_in,
+                TrackerAlarmType::CrossBorderIn,
+            ),
+            (
+                self.no_entry_cross_border_alarm_out,
+                TrackerAlarmType::CrossBorderOut,
+            ),
+            (self.custom_alarm, TrackerAlarmType::Custom),
+        ]
+        .into_iter()
+        .filter_map(|(active, alarm)| active.then_some(alarm))
+        .collect()
+    }
+}
+
+/// a cell tower (base station) seen by the tracker
+#[derive(Serialize, Deserialize)]
+pub struct CellTower {
+    /// location area code
+    pub lac: i32,
+
+    /// cell id
+    pub cell_id: i32,
+
+    /// received signal level
+    pub rx_level: i32,
+}
+
+/// Base station (LBS) info, sent by trackers without a GPS fix
+/// so their location can be estimated by the cell towers
+#[derive(Serialize, Deserialize)]
+pub struct LbsMsg {
+    /// mobile country code
+    pub mcc: i32,
+
+    /// mobile network code
+    pub mnc: i32,
+
+    /// GSM timing advance
+    pub timing_advance: i32,
+
+    /// cell towers seen by the tracker, 6 at most
+    pub cell_towers: Vec<CellTower>,
+
+    /// info about vehicle / tracker status
+    pub status: Status,
+
+    /// battery level from 1 to 6, only sent by some models
+    pub battery_level: Option<u8>,
+
+    /// date and time sent by the tracker
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Periodic link info sent by trackers
+#[derive(Serialize, Deserialize)]
+pub struct LinkMsg {
+    /// GSM signal strength
+    pub gsm_signal: i32,
+
+    /// amount of satellites in use
+    pub satellites: i32,
+
+    /// battery level (percentage)
+    pub battery_level: i32,
+
+    /// pedometer steps, only used by personal trackers
+    pub steps: i32,
+
+    /// turnovers, only used by personal trackers
+    pub turnovers: i32,
+
+    /// info about vehicle / tracker status
+    pub status: Status,
+
+    /// date and time sent by the tracker
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Acknowledgement of a command sent to the tracker (V4 message)
+#[derive(Serialize, Deserialize)]
+pub struct CommandAckMsg {
+    /// the acknowledged command, eg: `S20` for oil and engine cut-off
+    pub command: String,
+
+    /// the command arguments echoed back by the tracker, usually
+    /// starting with the time of the command in the hhmmss format
+    pub args: Vec<String>,
+}
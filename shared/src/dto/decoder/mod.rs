@@ -1 +1,5 @@
+pub mod command;
+pub mod gt06;
 pub mod h02;
+pub mod session;
+pub mod teltonika;
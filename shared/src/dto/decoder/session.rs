@@ -0,0 +1,62 @@
+//! Events published by the decoder about the TCP connections of trackers, trackers
+//! connected over UDP have no connection and never publish these events.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use strum::{Display, EnumString};
+
+/// Sent once the first valid packet of a connection identifies the tracker
+#[derive(Serialize, Deserialize)]
+pub struct ConnectedMsg {
+    /// address of the tracker connection, eg: `177.10.20.30:51234`
+    pub peer: Option<String>,
+
+    /// when the connection was opened
+    pub connected_at: DateTime<Utc>,
+
+    /// when the tracker was identified
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Why a tracker connection was closed
+#[derive(Serialize, Deserialize, Display, EnumString, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum DisconnectReason {
+    /// the tracker closed the connection
+    Closed,
+
+    /// failed to read from the connection, eg: a connection reset
+    ReadError,
+
+    /// failed to write a response to the tracker
+    WriteError,
+
+    /// the tracker sent too many packets that could not be decoded
+    InvalidPackets,
+}
+
+/// Sent once the connection of a identified tracker is closed
+#[derive(Serialize, Deserialize)]
+pub struct DisconnectedMsg {
+    /// address of the tracker connection, eg: `177.10.20.30:51234`
+    pub peer: Option<String>,
+
+    /// when the connection was opened
+    pub connected_at: DateTime<Utc>,
+
+    /// when the connection was closed
+    pub timestamp: DateTime<Utc>,
+
+    pub reason: DisconnectReason,
+
+    /// amount of bytes received on the connection
+    pub bytes_received: usize,
+
+    /// amount of packets decoded on the connection
+    pub packets_decoded: usize,
+
+    /// amount of packets on the connection that could not be decoded
+    /// or were rejected for claiming a different imei
+    pub invalid_packets: usize,
+}
@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Sent by the tracker once it connects, identifying itself by its IMEI
+#[derive(Serialize, Deserialize)]
+pub struct LoginMsg {
+    /// 15 digit imei number
+    pub imei: String,
+}
+
+/// The value of a IO element, fixed size elements are numbers while
+/// variable size elements (Codec 8 Extended only) are hex encoded bytes
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum IoValue {
+    Number(u64),
+    Bytes(String),
+}
+
+/// A AVL data record, a single packet can contain many records
+#[derive(Serialize, Deserialize)]
+pub struct LocationMsg {
+    /// latitude (90 to -90) in decimal degrees
+    pub lat: f64,
+
+    /// longitude (180 to -180) in decimal degrees
+    pub lng: f64,
+
+    /// altitude in meters above sea level
+    pub altitude: i32,
+
+    /// speed in km/h
+    pub speed: f64,
+
+    /// direction in degrees (0 degrees = north, 180 = s)
+    pub direction: i32,
+
+    /// amount of satellites in use
+    pub satellites: u8,
+
+    /// if the tracker had a GPS fix, if false the coordinates
+    /// are the last known position or zero and should not be trusted
+    pub positioned: bool,
+
+    /// record priority, 0 for low, 1 for high and 2 for panic
+    pub priority: u8,
+
+    /// id of the IO element that triggered the record, 0 if it was not
+    /// triggered by a IO element change (eg: periodic records)
+    pub event_io_id: u16,
+
+    /// the IO elements of the record, keyed by their name for well known IO
+    /// elements, eg: `ignition`, `external_voltage`, `total_odometer`, or by
+    /// their id otherwise, since the meaning of most IO ids depends on the model
+    pub io: BTreeMap<String, IoValue>,
+
+    /// vehicle date and time sent by the tracker
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Response of the tracker to a Codec 12 command
+#[derive(Serialize, Deserialize)]
+pub struct CommandAckMsg {
+    /// the tracker response, eg: `DOUT1:1 Timeout:INFINITY`
+    pub response: String,
+}
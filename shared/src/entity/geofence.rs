@@ -0,0 +1,89 @@
+use super::traits::QueryableByIdAndOrgId;
+use crate::constants::GeofenceShape;
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// A area vehicles are monitored entering and leaving, its PostGIS geometry
+/// is a generated column of the `geojson` and is not part of the model
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, ToSchema)]
+#[schema(as = entity::geofence::Model)]
+#[sea_orm(table_name = "geofence")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    pub name: String,
+    pub description: Option<String>,
+    pub shape: GeofenceShape,
+    /// GeoJSON geometry of the geofence, a `Polygon` or the `Point` at the center of a circle
+    #[schema(value_type = Object)]
+    pub geojson: Json,
+    /// radius in meters of circle geofences
+    pub radius: Option<f64>,
+    pub organization_id: i32,
+}
+
+impl QueryableByIdAndOrgId for Entity {
+    type Model = Model;
+
+    async fn find_by_id_and_org_id(
+        id: i32,
+        org_id: i32,
+        db: &DatabaseConnection,
+    ) -> Result<Option<Model>, DbErr> {
+        Self::find()
+            .filter(Column::Id.eq(id))
+            .filter(Column::OrganizationId.eq(org_id))
+            .one(db)
+            .await
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "Cascade",
+        on_delete = "NoAction"
+    )]
+    Organization,
+    #[sea_orm(has_many = "super::vehicle_geofence::Entity")]
+    VehicleGeofence,
+    #[sea_orm(has_many = "super::geofence_event::Entity")]
+    GeofenceEvent,
+}
+
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
+impl Related<super::vehicle_geofence::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VehicleGeofence.def()
+    }
+}
+
+impl Related<super::geofence_event::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GeofenceEvent.def()
+    }
+}
+
+impl Related<super::vehicle::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::vehicle_geofence::Relation::Vehicle.def()
+    }
+
+    fn via() -> Option<RelationDef> {
+        Some(super::vehicle_geofence::Relation::Geofence.def().rev())
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,84 @@
+use crate::constants::GeofenceEventType;
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// A vehicle entering or leaving a geofence
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, ToSchema)]
+#[schema(as = entity::geofence_event::Model)]
+#[sea_orm(table_name = "geofence_event")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// time of the first position of the vehicle after crossing the geofence border
+    pub time: DateTime<Utc>,
+    pub event_type: GeofenceEventType,
+    pub geofence_id: i32,
+    pub vehicle_id: i32,
+    pub vehicle_tracker_id: Option<i32>,
+    pub organization_id: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::geofence::Entity",
+        from = "Column::GeofenceId",
+        to = "super::geofence::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Geofence,
+    #[sea_orm(
+        belongs_to = "super::vehicle::Entity",
+        from = "Column::VehicleId",
+        to = "super::vehicle::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Vehicle,
+    #[sea_orm(
+        belongs_to = "super::vehicle_tracker::Entity",
+        from = "Column::VehicleTrackerId",
+        to = "super::vehicle_tracker::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    VehicleTracker,
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "Cascade",
+        on_delete = "NoAction"
+    )]
+    Organization,
+}
+
+impl Related<super::geofence::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Geofence.def()
+    }
+}
+
+impl Related<super::vehicle::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vehicle.def()
+    }
+}
+
+impl Related<super::vehicle_tracker::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VehicleTracker.def()
+    }
+}
+
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
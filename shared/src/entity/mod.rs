@@ -2,12 +2,20 @@ pub mod prelude;
 pub mod traits;
 
 pub mod access_level;
+pub mod geofence;
+pub mod geofence_event;
 pub mod organization;
+pub mod position_filter_decision;
 pub mod session;
 pub mod sim_card;
 pub mod spatial_ref_sys;
+pub mod stop;
+pub mod tracker_alarm;
+pub mod tracker_command;
+pub mod trip;
 pub mod user;
 pub mod vehicle;
+pub mod vehicle_geofence;
 pub mod vehicle_tracker;
 pub mod vehicle_tracker_last_location;
 pub mod vehicle_tracker_location;
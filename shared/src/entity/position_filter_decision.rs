@@ -0,0 +1,52 @@
+use crate::constants::PositionFilterReason;
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// A tracker position that failed a check of the position quality filter,
+/// kept to debug the filter limits
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, ToSchema)]
+#[schema(as = entity::position_filter_decision::Model)]
+#[sea_orm(table_name = "position_filter_decision")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    /// time of the filtered position, as sent by the tracker
+    pub time: DateTime<Utc>,
+    pub vehicle_tracker_id: i32,
+    pub reason: PositionFilterReason,
+    /// if the position was dropped, otherwise it was only flagged and stored as usual
+    pub dropped: bool,
+    pub lat: f64,
+    pub lng: f64,
+    pub speed: Option<f64>,
+    pub ignition: Option<bool>,
+    /// the value that failed the check, in km/h for `MAX_SPEED`, meters
+    /// for `PARKED_DRIFT` and seconds for `CLOCK_SKEW`
+    pub measured: f64,
+    /// the configured limit of the check, in the unit of `measured`
+    pub threshold: f64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vehicle_tracker::Entity",
+        from = "Column::VehicleTrackerId",
+        to = "super::vehicle_tracker::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    VehicleTracker,
+}
+
+impl Related<super::vehicle_tracker::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VehicleTracker.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
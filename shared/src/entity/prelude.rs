@@ -1,10 +1,18 @@
 pub use super::access_level::Entity as AccessLevel;
+pub use super::geofence::Entity as Geofence;
+pub use super::geofence_event::Entity as GeofenceEvent;
 pub use super::organization::Entity as Organization;
+pub use super::position_filter_decision::Entity as PositionFilterDecision;
 pub use super::session::Entity as Session;
 pub use super::sim_card::Entity as SimCard;
 pub use super::spatial_ref_sys::Entity as SpatialRefSys;
+pub use super::stop::Entity as Stop;
+pub use super::tracker_alarm::Entity as TrackerAlarm;
+pub use super::tracker_command::Entity as TrackerCommand;
+pub use super::trip::Entity as Trip;
 pub use super::user::Entity as User;
 pub use super::vehicle::Entity as Vehicle;
+pub use super::vehicle_geofence::Entity as VehicleGeofence;
 pub use super::vehicle_tracker::Entity as VehicleTracker;
 pub use super::vehicle_tracker_last_location::Entity as VehicleTrackerLastLocation;
 pub use super::vehicle_tracker_location::Entity as VehicleTrackerLocation;
@@ -0,0 +1,73 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// A vehicle stop between trips, where the vehicle stayed still for a while
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, ToSchema)]
+#[schema(as = entity::stop::Model)]
+#[sea_orm(table_name = "stop")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub vehicle_id: i32,
+    /// the tracker that sent the stop positions
+    pub vehicle_tracker_id: Option<i32>,
+    pub organization_id: i32,
+    /// if the vehicle did not start moving yet
+    pub in_progress: bool,
+    pub started_at: DateTime<Utc>,
+    /// when the vehicle started moving again, or the time of its last position while in progress
+    pub ended_at: DateTime<Utc>,
+    pub lat: f64,
+    pub lng: f64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vehicle::Entity",
+        from = "Column::VehicleId",
+        to = "super::vehicle::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Vehicle,
+    #[sea_orm(
+        belongs_to = "super::vehicle_tracker::Entity",
+        from = "Column::VehicleTrackerId",
+        to = "super::vehicle_tracker::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    VehicleTracker,
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "Cascade",
+        on_delete = "NoAction"
+    )]
+    Organization,
+}
+
+impl Related<super::vehicle::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vehicle.def()
+    }
+}
+
+impl Related<super::vehicle_tracker::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VehicleTracker.def()
+    }
+}
+
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,89 @@
+use crate::constants::TrackerAlarmType;
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// A alarm raised by a tracker, stored once when the alarm flag is raised
+/// and kept until a user acknowledges it
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, ToSchema)]
+#[schema(as = entity::tracker_alarm::Model)]
+#[sea_orm(table_name = "tracker_alarm")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    /// when the alarm was raised, as sent by the tracker
+    pub time: DateTime<Utc>,
+    pub alarm_type: TrackerAlarmType,
+    pub vehicle_tracker_id: i32,
+    pub vehicle_id: Option<i32>,
+    pub organization_id: i32,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    /// the user who acknowledged the alarm
+    pub acknowledged_by_user_id: Option<i32>,
+    pub acknowledgement_note: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vehicle_tracker::Entity",
+        from = "Column::VehicleTrackerId",
+        to = "super::vehicle_tracker::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    VehicleTracker,
+    #[sea_orm(
+        belongs_to = "super::vehicle::Entity",
+        from = "Column::VehicleId",
+        to = "super::vehicle::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    Vehicle,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::AcknowledgedByUserId",
+        to = "super::user::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    User,
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "Cascade",
+        on_delete = "NoAction"
+    )]
+    Organization,
+}
+
+impl Related<super::vehicle_tracker::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VehicleTracker.def()
+    }
+}
+
+impl Related<super::vehicle::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vehicle.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,92 @@
+use crate::constants::TrackerCommandStatus;
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// A command sent to a tracker, kept as the audit trail of
+/// who sent which command and when the tracker confirmed it
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, ToSchema)]
+#[schema(as = entity::tracker_command::Model)]
+#[sea_orm(table_name = "tracker_command")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub created_at: DateTime<Utc>,
+    /// the command sent to the tracker, eg: `{ "type": "engine_stop" }`
+    #[schema(value_type = Object)]
+    pub command: Json,
+    pub status: TrackerCommandStatus,
+    pub sent_at: Option<DateTime<Utc>>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub failure_reason: Option<String>,
+    pub response: Option<String>,
+    pub vehicle_tracker_id: i32,
+    pub vehicle_id: Option<i32>,
+    /// the user who sent the command
+    pub user_id: Option<i32>,
+    pub organization_id: i32,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vehicle_tracker::Entity",
+        from = "Column::VehicleTrackerId",
+        to = "super::vehicle_tracker::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    VehicleTracker,
+    #[sea_orm(
+        belongs_to = "super::vehicle::Entity",
+        from = "Column::VehicleId",
+        to = "super::vehicle::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    Vehicle,
+    #[sea_orm(
+        belongs_to = "super::user::Entity",
+        from = "Column::UserId",
+        to = "super::user::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    User,
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "Cascade",
+        on_delete = "NoAction"
+    )]
+    Organization,
+}
+
+impl Related<super::vehicle_tracker::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VehicleTracker.def()
+    }
+}
+
+impl Related<super::vehicle::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vehicle.def()
+    }
+}
+
+impl Related<super::user::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::User.def()
+    }
+}
+
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
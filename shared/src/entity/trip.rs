@@ -0,0 +1,79 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use serde::Serialize;
+use utoipa::ToSchema;
+
+/// A vehicle trip, from when the vehicle started moving to when it stopped
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, ToSchema)]
+#[schema(as = entity::trip::Model)]
+#[sea_orm(table_name = "trip")]
+#[serde(rename_all = "camelCase")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub vehicle_id: i32,
+    /// the tracker that sent the trip positions
+    pub vehicle_tracker_id: Option<i32>,
+    pub organization_id: i32,
+    /// if the vehicle did not stop yet
+    pub in_progress: bool,
+    pub started_at: DateTime<Utc>,
+    /// when the trip ended, or the time of its last position while in progress
+    pub ended_at: DateTime<Utc>,
+    pub start_lat: f64,
+    pub start_lng: f64,
+    pub end_lat: f64,
+    pub end_lng: f64,
+    /// distance driven in meters
+    pub distance: f64,
+    /// highest speed of the trip positions in km/h
+    pub max_speed: Option<f64>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vehicle::Entity",
+        from = "Column::VehicleId",
+        to = "super::vehicle::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Vehicle,
+    #[sea_orm(
+        belongs_to = "super::vehicle_tracker::Entity",
+        from = "Column::VehicleTrackerId",
+        to = "super::vehicle_tracker::Column::Id",
+        on_update = "Cascade",
+        on_delete = "SetNull"
+    )]
+    VehicleTracker,
+    #[sea_orm(
+        belongs_to = "super::organization::Entity",
+        from = "Column::OrganizationId",
+        to = "super::organization::Column::Id",
+        on_update = "Cascade",
+        on_delete = "NoAction"
+    )]
+    Organization,
+}
+
+impl Related<super::vehicle::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vehicle.def()
+    }
+}
+
+impl Related<super::vehicle_tracker::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::VehicleTracker.def()
+    }
+}
+
+impl Related<super::organization::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Organization.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -4,7 +4,7 @@ use sea_orm::{entity::prelude::*, QuerySelect};
 use serde::Serialize;
 use utoipa::ToSchema;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, ToSchema)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, ToSchema)]
 #[schema(as = entity::vehicle::Model)]
 #[sea_orm(table_name = "vehicle")]
 #[serde(rename_all = "camelCase")]
@@ -22,6 +22,8 @@ pub struct Model {
     pub color: Option<String>,
     pub additional_info: Option<String>,
     pub organization_id: i32,
+    /// distance driven by the vehicle in meters, calibrated by users
+    pub odometer: f64,
 }
 
 impl QueryableByIdAndOrgId for Entity {
@@ -0,0 +1,51 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+
+/// Assignment of a geofence to a vehicle, keeping if the
+/// vehicle was inside the geofence on its last position
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[sea_orm(table_name = "vehicle_geofence")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub vehicle_id: i32,
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub geofence_id: i32,
+    /// `None` until the first position of the vehicle after the assignment
+    pub inside: Option<bool>,
+    /// time of the last position checked, older positions are ignored
+    pub checked_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::vehicle::Entity",
+        from = "Column::VehicleId",
+        to = "super::vehicle::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Vehicle,
+    #[sea_orm(
+        belongs_to = "super::geofence::Entity",
+        from = "Column::GeofenceId",
+        to = "super::geofence::Column::Id",
+        on_update = "Cascade",
+        on_delete = "Cascade"
+    )]
+    Geofence,
+}
+
+impl Related<super::vehicle::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Vehicle.def()
+    }
+}
+
+impl Related<super::geofence::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Geofence.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,7 +1,7 @@
 use super::traits::QueryableByIdAndOrgId;
-use crate::constants::TrackerModel;
-use chrono::{DateTime, Utc};
-use sea_orm::entity::prelude::*;
+use crate::constants::{TrackerConnectivity, TrackerModel, TRACKER_OFFLINE_AFTER_SECS};
+use chrono::{DateTime, Duration, Utc};
+use sea_orm::{entity::prelude::*, Condition};
 use serde::Serialize;
 use utoipa::ToSchema;
 
@@ -17,6 +17,30 @@ pub struct Model {
     pub imei: String,
     pub organization_id: i32,
     pub vehicle_id: Option<i32>,
+    pub last_seen_at: Option<DateTime<Utc>>,
+    pub last_connected_at: Option<DateTime<Utc>>,
+    pub last_disconnected_at: Option<DateTime<Utc>>,
+}
+
+impl Model {
+    /// The connectivity of the tracker at `now`
+    pub fn connectivity(&self, now: DateTime<Utc>) -> TrackerConnectivity {
+        let Some(last_seen_at) = self.last_seen_at else {
+            return TrackerConnectivity::NeverSeen;
+        };
+
+        let disconnected = match (self.last_disconnected_at, self.last_connected_at) {
+            (Some(disconnected_at), Some(connected_at)) => disconnected_at >= connected_at,
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if disconnected || last_seen_at < now - Duration::seconds(TRACKER_OFFLINE_AFTER_SECS) {
+            TrackerConnectivity::Offline
+        } else {
+            TrackerConnectivity::Online
+        }
+    }
 }
 
 impl QueryableByIdAndOrgId for Entity {
@@ -36,6 +60,33 @@ impl QueryableByIdAndOrgId for Entity {
 }
 
 impl Entity {
+    /// Condition matching the trackers with the `connectivity` at `now`,
+    /// the query equivalent of `Model::connectivity`
+    pub fn connectivity_condition(
+        connectivity: TrackerConnectivity,
+        now: DateTime<Utc>,
+    ) -> Condition {
+        let seen_recently =
+            Column::LastSeenAt.gte(now - Duration::seconds(TRACKER_OFFLINE_AFTER_SECS));
+
+        // the last connection was closed and the tracker did not connect again
+        let disconnected = Condition::all()
+            .add(Column::LastDisconnectedAt.is_not_null())
+            .add(Condition::any().add(Column::LastConnectedAt.is_null()).add(
+                Expr::col(Column::LastDisconnectedAt).gte(Expr::col(Column::LastConnectedAt)),
+            ));
+
+        match connectivity {
+            TrackerConnectivity::NeverSeen => Condition::all().add(Column::LastSeenAt.is_null()),
+            TrackerConnectivity::Online => {
+                Condition::all().add(seen_recently).add(disconnected.not())
+            }
+            TrackerConnectivity::Offline => Condition::all()
+                .add(Column::LastSeenAt.is_not_null())
+                .add(Condition::any().add(seen_recently.not()).add(disconnected)),
+        }
+    }
+
     pub async fn find_by_vehicle_and_org_id(
         vehicle_id: i32,
         organization_id: i32,
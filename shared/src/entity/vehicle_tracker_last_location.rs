@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use sea_orm::entity::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "vehicle_tracker_last_location")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false, unique)]
@@ -9,6 +9,14 @@ pub struct Model {
     pub time: DateTime<Utc>,
     #[sea_orm(column_type = "custom(\"geometry\")")]
     pub point: String,
+    /// speed in km/h
+    pub speed: Option<f64>,
+    /// direction in degrees, 0 being north and 90 east
+    pub heading: Option<i32>,
+    /// if the vehicle ignition (ACC) was on, `None` if not sent by the tracker
+    pub ignition: Option<bool>,
+    /// protocol specific status, eg: H02 status flags or teltonika IO elements
+    pub status: Option<Json>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
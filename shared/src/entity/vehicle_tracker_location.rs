@@ -1,7 +1,7 @@
 use chrono::{DateTime, Utc};
 use sea_orm::entity::prelude::*;
 
-#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq)]
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "vehicle_tracker_location")]
 pub struct Model {
     #[sea_orm(primary_key, auto_increment = false)]
@@ -10,6 +10,20 @@ pub struct Model {
     pub vehicle_tracker_id: i32,
     #[sea_orm(column_type = "custom(\"geometry\")")]
     pub point: String,
+    /// speed in km/h
+    pub speed: Option<f64>,
+    /// direction in degrees, 0 being north and 90 east
+    pub heading: Option<i32>,
+    /// if the vehicle ignition (ACC) was on, `None` if not sent by the tracker
+    pub ignition: Option<bool>,
+    /// protocol specific status, eg: H02 status flags or teltonika IO elements
+    pub status: Option<Json>,
+    /// geodesic distance in meters from the previous location of the tracker
+    pub distance: Option<f64>,
+    /// seconds since the previous location of the tracker if its ignition was on, otherwise 0
+    pub ignition_seconds: Option<i32>,
+    /// the vehicle the tracker was installed on when the location was sent
+    pub vehicle_id: Option<i32>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]